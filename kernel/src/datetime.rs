@@ -0,0 +1,342 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Offset {
+    Z,
+    Custom { minutes: i16 },
+}
+
+/// An RFC 3339 date, time or datetime. A datetime without an offset is a
+/// local datetime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Datetime {
+    pub date: Option<Date>,
+    pub time: Option<Time>,
+    pub offset: Option<Offset>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("invalid RFC 3339 date, time or datetime: '{0}'")]
+pub struct DatetimeParseError(String);
+
+impl Datetime {
+    pub fn kind(&self) -> &'static str {
+        match (self.date, self.time) {
+            (Some(_), None) => "date",
+            (None, Some(_)) => "time",
+            _ => "datetime",
+        }
+    }
+
+    /// Whether `s` has the shape of a date (`2024-01-15...`) or a time
+    /// (`10:30...`), even if it turns out not to be a valid one.
+    pub fn looks_like(s: &str) -> bool {
+        let bytes = s.as_bytes();
+        let is_digits = |range: std::ops::Range<usize>| {
+            bytes
+                .get(range)
+                .is_some_and(|b| b.iter().all(u8::is_ascii_digit))
+        };
+
+        (is_digits(0..4) && bytes.get(4) == Some(&b'-'))
+            || (is_digits(0..2) && bytes.get(2) == Some(&b':'))
+    }
+
+    fn parse_date(s: &str) -> Option<Date> {
+        if s.len() != 10 || &s[4..5] != "-" || &s[7..8] != "-" {
+            return None;
+        }
+
+        let date = Date {
+            year: digits(&s[0..4])? as u16,
+            month: digits(&s[5..7])? as u8,
+            day: digits(&s[8..10])? as u8,
+        };
+
+        let valid = (1..=12).contains(&date.month)
+            && date.day >= 1
+            && date.day <= days_in_month(date.year, date.month);
+        valid.then_some(date)
+    }
+
+    fn parse_time(s: &str) -> Option<(Time, &str)> {
+        if s.len() < 8 || &s[2..3] != ":" || &s[5..6] != ":" {
+            return None;
+        }
+
+        let mut time = Time {
+            hour: digits(&s[0..2])? as u8,
+            minute: digits(&s[3..5])? as u8,
+            second: digits(&s[6..8])? as u8,
+            nanosecond: 0,
+        };
+
+        let mut rest = &s[8..];
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let len = fraction
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(fraction.len());
+            if !(1..=9).contains(&len) {
+                return None;
+            }
+            time.nanosecond = digits(&fraction[..len])? * 10u32.pow(9 - len as u32);
+            rest = &fraction[len..];
+        }
+
+        // A second of 60 is allowed for leap seconds.
+        let valid = time.hour < 24 && time.minute < 60 && time.second <= 60;
+        valid.then_some((time, rest))
+    }
+
+    fn parse_offset(s: &str) -> Option<Offset> {
+        if s == "Z" || s == "z" {
+            return Some(Offset::Z);
+        }
+
+        let sign = match s.get(0..1)? {
+            "+" => 1,
+            "-" => -1,
+            _ => return None,
+        };
+        if s.len() != 6 || &s[3..4] != ":" {
+            return None;
+        }
+
+        let hours = digits(&s[1..3])? as i16;
+        let minutes = digits(&s[4..6])? as i16;
+        if hours >= 24 || minutes >= 60 {
+            return None;
+        }
+
+        Some(Offset::Custom {
+            minutes: sign * (hours * 60 + minutes),
+        })
+    }
+}
+
+impl FromStr for Datetime {
+    type Err = DatetimeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || DatetimeParseError(s.to_string());
+
+        if !s.is_ascii() {
+            return Err(error());
+        }
+
+        let (date, time_part) = if s.len() >= 10 && &s[4..5] == "-" {
+            let date = Self::parse_date(&s[..10]).ok_or_else(error)?;
+            match s[10..].chars().next() {
+                None => {
+                    return Ok(Datetime {
+                        date: Some(date),
+                        time: None,
+                        offset: None,
+                    });
+                }
+                Some('T' | 't') => (Some(date), &s[11..]),
+                Some(_) => return Err(error()),
+            }
+        } else {
+            (None, s)
+        };
+
+        let (time, rest) = Self::parse_time(time_part).ok_or_else(error)?;
+
+        let offset = match (date, rest) {
+            (_, "") => None,
+            (Some(_), rest) => Some(Self::parse_offset(rest).ok_or_else(error)?),
+            (None, _) => return Err(error()),
+        };
+
+        Ok(Datetime {
+            date,
+            time: Some(time),
+            offset,
+        })
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)?;
+        if self.nanosecond > 0 {
+            let fraction = format!("{:09}", self.nanosecond);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Offset::Z => write!(f, "Z"),
+            Offset::Custom { minutes } => {
+                let sign = if *minutes < 0 { '-' } else { '+' };
+                let minutes = minutes.unsigned_abs();
+                write!(f, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+            }
+        }
+    }
+}
+
+impl Display for Datetime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(date) = &self.date {
+            write!(f, "{}", date)?;
+            if self.time.is_some() {
+                write!(f, "T")?;
+            }
+        }
+        if let Some(time) = &self.time {
+            write!(f, "{}", time)?;
+        }
+        if let Some(offset) = &self.offset {
+            write!(f, "{}", offset)?;
+        }
+        Ok(())
+    }
+}
+
+fn digits(s: &str) -> Option<u32> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod valid {
+        use super::*;
+
+        #[test]
+        fn test_parse_date() {
+            let datetime: Datetime = "2024-02-29".parse().unwrap();
+            assert_eq!(datetime.kind(), "date");
+            assert_eq!(
+                datetime.date,
+                Some(Date {
+                    year: 2024,
+                    month: 2,
+                    day: 29
+                })
+            );
+            assert_eq!(datetime.to_string(), "2024-02-29");
+        }
+
+        #[test]
+        fn test_parse_time() {
+            let datetime: Datetime = "23:59:60.250".parse().unwrap();
+            assert_eq!(datetime.kind(), "time");
+            assert_eq!(
+                datetime.time,
+                Some(Time {
+                    hour: 23,
+                    minute: 59,
+                    second: 60,
+                    nanosecond: 250_000_000
+                })
+            );
+            assert_eq!(datetime.to_string(), "23:59:60.25");
+        }
+
+        #[test]
+        fn test_parse_datetime() {
+            let datetime: Datetime = "2024-01-15T10:30:00Z".parse().unwrap();
+            assert_eq!(datetime.kind(), "datetime");
+            assert_eq!(datetime.offset, Some(Offset::Z));
+            assert_eq!(datetime.to_string(), "2024-01-15T10:30:00Z");
+
+            let datetime: Datetime = "2024-01-15t10:30:00.123456789-05:30".parse().unwrap();
+            assert_eq!(datetime.offset, Some(Offset::Custom { minutes: -330 }));
+            assert_eq!(datetime.to_string(), "2024-01-15T10:30:00.123456789-05:30");
+
+            let datetime: Datetime = "2024-01-15T10:30:00".parse().unwrap();
+            assert_eq!(datetime.offset, None);
+            assert_eq!(datetime.to_string(), "2024-01-15T10:30:00");
+        }
+
+        #[test]
+        fn test_looks_like() {
+            assert!(Datetime::looks_like("2024-13-01"));
+            assert!(Datetime::looks_like("25:00"));
+            assert!(!Datetime::looks_like("2024"));
+            assert!(!Datetime::looks_like("localhost"));
+            assert!(!Datetime::looks_like("12-34"));
+        }
+    }
+
+    mod invalid {
+        use super::*;
+
+        #[test]
+        fn test_invalid_dates() {
+            for input in [
+                "2023-02-29",
+                "2024-13-01",
+                "2024-04-31",
+                "2024-1-01",
+                "2024-01-15X",
+            ] {
+                assert!(input.parse::<Datetime>().is_err(), "{}", input);
+            }
+        }
+
+        #[test]
+        fn test_invalid_times() {
+            for input in ["24:00:00", "10:60:00", "10:30", "10:30:00.", "10:30:00Z"] {
+                assert!(input.parse::<Datetime>().is_err(), "{}", input);
+            }
+        }
+
+        #[test]
+        fn test_invalid_offsets() {
+            for input in [
+                "2024-01-15T10:30:00+9:00",
+                "2024-01-15T10:30:00+24:00",
+                "2024-01-15T10:30:00 ",
+            ] {
+                assert!(input.parse::<Datetime>().is_err(), "{}", input);
+            }
+        }
+    }
+}
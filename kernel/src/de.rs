@@ -1,17 +1,47 @@
 use crate::{
+    datetime::Datetime,
     shared::Map,
+    units::{parse_duration, parse_size},
     value::{Number, Value},
 };
 use serde::de::{
-    self, Deserialize, Deserializer, Error as DeError, IntoDeserializer, MapAccess, Visitor,
+    self, Deserialize, Deserializer, Error as DeError, IntoDeserializer, MapAccess, SeqAccess,
+    Visitor,
 };
 use std::fmt;
 
+/// The newtype name `Value` asks its deserializer for. Only a `Value`
+/// deserializer recognizes it, handing datetimes, durations and sizes over
+/// as single-entry maps with the keys below, so that they can be told apart
+/// from strings and numbers. Every other target, through `deserialize_any`
+/// or a typed method, reads datetimes and durations as the text they were
+/// written as and sizes as numbers of bytes.
+const VALUE_TOKEN: &str = "$__sorbe_tpl_private_value";
+const DATETIME_FIELD: &str = "$__sorbe_tpl_private_datetime";
+const DURATION_FIELD: &str = "$__sorbe_tpl_private_duration";
+const SIZE_FIELD: &str = "$__sorbe_tpl_private_size";
+
 impl Value {
+    fn describe(&self) -> String {
+        match self {
+            Value::Null => "null".to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Number(n) => n.to_string(),
+            Value::String(s) => format!("'{}'", s),
+            Value::Datetime(_, text) | Value::Duration(_, text) | Value::Size(_, text) => {
+                text.clone()
+            }
+            Value::Array(_) => "array".to_string(),
+            Value::Dict(_) => "dict".to_string(),
+        }
+    }
+
     fn as_i64(&self) -> Option<i64> {
         match self {
             Value::Number(Number::Int(i)) => Some(*i),
-            Value::Number(Number::UInt(u)) if *u <= i64::MAX as u64 => Some(*u as i64),
+            Value::Number(Number::UInt(u)) | Value::Size(u, _) if *u <= i64::MAX as u64 => {
+                Some(*u as i64)
+            }
             Value::Number(Number::Float(f))
                 if f.fract() == 0.0 && *f >= i64::MIN as f64 && *f <= i64::MAX as f64 =>
             {
@@ -23,7 +53,7 @@ impl Value {
 
     fn as_u64(&self) -> Option<u64> {
         match self {
-            Value::Number(Number::UInt(u)) => Some(*u),
+            Value::Number(Number::UInt(u)) | Value::Size(u, _) => Some(*u),
             Value::Number(Number::Int(i)) if *i >= 0 => Some(*i as u64),
             Value::Number(Number::Float(f))
                 if f.fract() == 0.0 && *f >= 0.0 && *f <= u64::MAX as f64 =>
@@ -49,7 +79,7 @@ impl<'de> Deserialize<'de> for Value {
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_any(ValueVisitor)
+        deserializer.deserialize_newtype_struct(VALUE_TOKEN, ValueVisitor)
     }
 }
 
@@ -90,24 +120,98 @@ impl<'de> Visitor<'de> for ValueVisitor {
         Ok(Value::Null)
     }
 
+    /// Other deserializers hand the newtype of `VALUE_TOKEN` over as is.
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<S>(self, mut seq: S) -> Result<Value, S::Error>
+    where
+        S: SeqAccess<'de>,
+    {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element::<Value>()? {
+            items.push(item);
+        }
+        Ok(Value::Array(items))
+    }
+
     fn visit_map<M>(self, mut map: M) -> Result<Value, M::Error>
     where
         M: MapAccess<'de>,
     {
         let mut dict = Map::new();
-        while let Some((key, value)) = map.next_entry::<String, Value>()? {
-            dict.insert(key, value);
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                DATETIME_FIELD => {
+                    let datetime = map.next_value::<String>()?;
+                    return match datetime.parse::<Datetime>() {
+                        Ok(dt) => Ok(Value::Datetime(dt, datetime)),
+                        Err(e) => Err(M::Error::custom(e)),
+                    };
+                }
+                DURATION_FIELD => {
+                    let duration = map.next_value::<String>()?;
+                    return match parse_duration(&duration) {
+                        Some(Ok(d)) => Ok(Value::Duration(d, duration)),
+                        _ => Err(M::Error::custom(format!("invalid duration: {}", duration))),
+                    };
+                }
+                SIZE_FIELD => {
+                    let size = map.next_value::<String>()?;
+                    return match parse_size(&size) {
+                        Some(Ok(bytes)) => Ok(Value::Size(bytes, size)),
+                        _ => Err(M::Error::custom(format!("invalid size: {}", size))),
+                    };
+                }
+                _ => {
+                    dict.insert(key, map.next_value::<Value>()?);
+                }
+            }
         }
         Ok(Value::Dict(dict))
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct DeserializeError(String);
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeserializeError {
+    path: Vec<String>,
+    message: String,
+}
+
+impl DeserializeError {
+    fn expected(expected: &str, found: &Value) -> Self {
+        Self::custom(format!("expected {}, found {}", expected, found.describe()))
+    }
+
+    fn with_parent(mut self, key: &str) -> Self {
+        self.path.insert(0, key.to_string());
+        self
+    }
+
+    pub fn path(&self) -> String {
+        self.path.join(".")
+    }
+
+    pub fn path_segments(&self) -> &[String] {
+        &self.path
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
 
 impl fmt::Display for DeserializeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.0.fmt(f)
+        if self.path.is_empty() {
+            self.message.fmt(f)
+        } else {
+            write!(f, "{}: {}", self.path(), self.message)
+        }
     }
 }
 
@@ -115,7 +219,10 @@ impl std::error::Error for DeserializeError {}
 
 impl DeError for DeserializeError {
     fn custom<T: fmt::Display>(msg: T) -> Self {
-        DeserializeError(msg.to_string())
+        DeserializeError {
+            path: Vec::new(),
+            message: msg.to_string(),
+        }
     }
 }
 
@@ -132,7 +239,11 @@ impl<'de> Deserializer<'de> for Value {
             Value::Number(Number::Int(i)) => visitor.visit_i64(i),
             Value::Number(Number::UInt(u)) => visitor.visit_u64(u),
             Value::Number(Number::Float(f)) => visitor.visit_f64(f),
-            Value::String(s) => visitor.visit_string(s),
+            Value::String(s) | Value::Datetime(_, s) | Value::Duration(_, s) => {
+                visitor.visit_string(s)
+            }
+            Value::Size(bytes, _) => visitor.visit_u64(bytes),
+            Value::Array(items) => visitor.visit_seq(ArrayAccess::new(items)),
             Value::Dict(dict) => visitor.visit_map(DictAccess::new(dict)),
         }
     }
@@ -143,7 +254,7 @@ impl<'de> Deserializer<'de> for Value {
     {
         match self {
             Value::Bool(b) => visitor.visit_bool(b),
-            _ => Err(DeserializeError::custom("expected bool")),
+            other => Err(DeserializeError::expected("bool", &other)),
         }
     }
 
@@ -151,39 +262,39 @@ impl<'de> Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        if let Some(i) = self.as_i64() {
-            if i >= i8::MIN as i64 && i <= i8::MAX as i64 {
-                visitor.visit_i8(i as i8)
-            } else {
-                Err(DeserializeError::custom("expected i8"))
-            }
-        } else {
-            Err(DeserializeError::custom("expected i8"))
+        if let Some(i) = self.as_i64()
+            && i >= i8::MIN as i64
+            && i <= i8::MAX as i64
+        {
+            return visitor.visit_i8(i as i8);
         }
+        Err(DeserializeError::expected("i8", &self))
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        if let Some(i) = self.as_i64() {
-            if i >= i16::MIN as i64 && i <= i16::MAX as i64 {
-                return visitor.visit_i16(i as i16);
-            }
+        if let Some(i) = self.as_i64()
+            && i >= i16::MIN as i64
+            && i <= i16::MAX as i64
+        {
+            return visitor.visit_i16(i as i16);
         }
-        Err(DeserializeError::custom("expected i16"))
+        Err(DeserializeError::expected("i16", &self))
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        if let Some(i) = self.as_i64() {
-            if i >= i32::MIN as i64 && i <= i32::MAX as i64 {
-                return visitor.visit_i32(i as i32);
-            }
+        if let Some(i) = self.as_i64()
+            && i >= i32::MIN as i64
+            && i <= i32::MAX as i64
+        {
+            return visitor.visit_i32(i as i32);
         }
-        Err(DeserializeError::custom("expected i32"))
+        Err(DeserializeError::expected("i32", &self))
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -193,43 +304,43 @@ impl<'de> Deserializer<'de> for Value {
         if let Some(i) = self.as_i64() {
             return visitor.visit_i64(i);
         }
-        Err(DeserializeError::custom("expected i64"))
+        Err(DeserializeError::expected("i64", &self))
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        if let Some(u) = self.as_u64() {
-            if u <= u8::MAX as u64 {
-                return visitor.visit_u8(u as u8);
-            }
+        if let Some(u) = self.as_u64()
+            && u <= u8::MAX as u64
+        {
+            return visitor.visit_u8(u as u8);
         }
-        Err(DeserializeError::custom("expected u8"))
+        Err(DeserializeError::expected("u8", &self))
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        if let Some(u) = self.as_u64() {
-            if u <= u16::MAX as u64 {
-                return visitor.visit_u16(u as u16);
-            }
+        if let Some(u) = self.as_u64()
+            && u <= u16::MAX as u64
+        {
+            return visitor.visit_u16(u as u16);
         }
-        Err(DeserializeError::custom("expected u16"))
+        Err(DeserializeError::expected("u16", &self))
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        if let Some(u) = self.as_u64() {
-            if u <= u32::MAX as u64 {
-                return visitor.visit_u32(u as u32);
-            }
+        if let Some(u) = self.as_u64()
+            && u <= u32::MAX as u64
+        {
+            return visitor.visit_u32(u as u32);
         }
-        Err(DeserializeError::custom("expected u32"))
+        Err(DeserializeError::expected("u32", &self))
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -239,19 +350,21 @@ impl<'de> Deserializer<'de> for Value {
         if let Some(u) = self.as_u64() {
             return visitor.visit_u64(u);
         }
-        Err(DeserializeError::custom("expected u64"))
+        Err(DeserializeError::expected("u64", &self))
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        if let Some(f) = self.as_f64() {
-            if f.is_finite() && f >= f32::MIN as f64 && f <= f32::MAX as f64 {
-                return visitor.visit_f32(f as f32);
-            }
+        if let Some(f) = self.as_f64()
+            && f.is_finite()
+            && f >= f32::MIN as f64
+            && f <= f32::MAX as f64
+        {
+            return visitor.visit_f32(f as f32);
         }
-        Err(DeserializeError::custom("expected f32"))
+        Err(DeserializeError::expected("f32", &self))
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -261,7 +374,7 @@ impl<'de> Deserializer<'de> for Value {
         if let Some(f) = self.as_f64() {
             return visitor.visit_f64(f);
         }
-        Err(DeserializeError::custom("expected f64"))
+        Err(DeserializeError::expected("f64", &self))
     }
 
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -273,10 +386,13 @@ impl<'de> Deserializer<'de> for Value {
                 let mut chars = s.chars();
                 match (chars.next(), chars.next()) {
                     (Some(c), None) => visitor.visit_char(c),
-                    _ => Err(DeserializeError::custom("expected single character")),
+                    _ => Err(DeserializeError::custom(format!(
+                        "expected single character, found '{}'",
+                        s
+                    ))),
                 }
             }
-            _ => Err(DeserializeError::custom("expected char")),
+            other => Err(DeserializeError::expected("char", &other)),
         }
     }
 
@@ -285,8 +401,11 @@ impl<'de> Deserializer<'de> for Value {
         V: Visitor<'de>,
     {
         match self {
-            Value::String(s) => visitor.visit_string(s),
-            _ => Err(DeserializeError::custom("expected string")),
+            Value::String(s)
+            | Value::Datetime(_, s)
+            | Value::Duration(_, s)
+            | Value::Size(_, s) => visitor.visit_string(s),
+            other => Err(DeserializeError::expected("string", &other)),
         }
     }
 
@@ -303,7 +422,7 @@ impl<'de> Deserializer<'de> for Value {
     {
         match self {
             Value::String(s) => visitor.visit_byte_buf(s.into_bytes()),
-            _ => Err(DeserializeError::custom("expected bytes")),
+            other => Err(DeserializeError::expected("bytes", &other)),
         }
     }
 
@@ -330,7 +449,7 @@ impl<'de> Deserializer<'de> for Value {
     {
         match self {
             Value::Null => visitor.visit_unit(),
-            _ => Err(DeserializeError::custom("expected null")),
+            other => Err(DeserializeError::expected("null", &other)),
         }
     }
 
@@ -347,39 +466,64 @@ impl<'de> Deserializer<'de> for Value {
 
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        if name != VALUE_TOKEN {
+            return visitor.visit_newtype_struct(self);
+        }
+
+        match self {
+            Value::Datetime(_, text) => {
+                visitor.visit_map(DictAccess::marker(DATETIME_FIELD, Value::String(text)))
+            }
+            Value::Duration(_, text) => {
+                visitor.visit_map(DictAccess::marker(DURATION_FIELD, Value::String(text)))
+            }
+            Value::Size(_, text) => {
+                visitor.visit_map(DictAccess::marker(SIZE_FIELD, Value::String(text)))
+            }
+            other => other.deserialize_any(visitor),
+        }
     }
 
-    fn deserialize_seq<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        Err(DeserializeError::custom("sequences not supported"))
+        match self {
+            Value::Array(items) => visitor.visit_seq(ArrayAccess::new(items)),
+            other => Err(DeserializeError::expected("sequence", &other)),
+        }
     }
 
-    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        match self {
+            Value::Array(items) if items.len() != len => Err(DeserializeError::custom(format!(
+                "expected {} elements, found {}",
+                len,
+                items.len()
+            ))),
+            other => other.deserialize_seq(visitor),
+        }
     }
 
     fn deserialize_tuple_struct<V>(
         self,
         _name: &'static str,
-        _len: usize,
+        len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        self.deserialize_tuple(len, visitor)
     }
 
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -388,7 +532,7 @@ impl<'de> Deserializer<'de> for Value {
     {
         match self {
             Value::Dict(dict) => visitor.visit_map(DictAccess::new(dict)),
-            _ => Err(DeserializeError::custom("expected map")),
+            other => Err(DeserializeError::expected("map", &other)),
         }
     }
 
@@ -401,7 +545,19 @@ impl<'de> Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_map(visitor)
+        match self {
+            // `std::time::Duration` deserializes as a `{ secs, nanos }` struct.
+            Value::Duration(d, _) => {
+                let mut dict = Map::new();
+                dict.insert("secs".to_string(), Value::Number(Number::UInt(d.as_secs())));
+                dict.insert(
+                    "nanos".to_string(),
+                    Value::Number(Number::UInt(d.subsec_nanos().into())),
+                );
+                visitor.visit_map(DictAccess::new(dict))
+            }
+            other => other.deserialize_map(visitor),
+        }
     }
 
     fn deserialize_enum<V>(
@@ -415,7 +571,7 @@ impl<'de> Deserializer<'de> for Value {
     {
         match self {
             Value::String(s) => visitor.visit_enum(s.into_deserializer()),
-            _ => Err(DeserializeError::custom("expected enum")),
+            other => Err(DeserializeError::expected("enum", &other)),
         }
     }
 
@@ -434,18 +590,57 @@ impl<'de> Deserializer<'de> for Value {
     }
 }
 
+struct ArrayAccess {
+    iter: std::iter::Enumerate<std::vec::IntoIter<Value>>,
+}
+
+impl ArrayAccess {
+    fn new(items: Vec<Value>) -> Self {
+        ArrayAccess {
+            iter: items.into_iter().enumerate(),
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for ArrayAccess {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some((index, value)) => seed
+                .deserialize(value)
+                .map(Some)
+                .map_err(|e| e.with_parent(&index.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
 struct DictAccess {
     iter: std::vec::IntoIter<(String, Value)>,
-    value: Option<Value>,
+    entry: Option<(String, Value)>,
 }
 
 impl DictAccess {
     fn new(dict: Map<String, Value>) -> Self {
         DictAccess {
             iter: dict.into_iter().collect::<Vec<_>>().into_iter(),
-            value: None,
+            entry: None,
         }
     }
+
+    fn marker(field: &str, value: Value) -> Self {
+        let mut dict = Map::new();
+        dict.insert(field.to_string(), value);
+        Self::new(dict)
+    }
 }
 
 impl<'de> MapAccess<'de> for DictAccess {
@@ -457,8 +652,10 @@ impl<'de> MapAccess<'de> for DictAccess {
     {
         match self.iter.next() {
             Some((key, value)) => {
-                self.value = Some(value);
-                seed.deserialize(key.into_deserializer()).map(Some)
+                self.entry = Some((key.clone(), value));
+                seed.deserialize(key.clone().into_deserializer())
+                    .map(Some)
+                    .map_err(|e: DeserializeError| e.with_parent(&key))
             }
             None => Ok(None),
         }
@@ -468,8 +665,8 @@ impl<'de> MapAccess<'de> for DictAccess {
     where
         V: de::DeserializeSeed<'de>,
     {
-        match self.value.take() {
-            Some(value) => seed.deserialize(value),
+        match self.entry.take() {
+            Some((key, value)) => seed.deserialize(value).map_err(|e| e.with_parent(&key)),
             None => Err(DeserializeError::custom("value is missing")),
         }
     }
@@ -0,0 +1,449 @@
+use crate::error::{
+    Error, Errors, KeyError, LineStructureError, ParseError, SyntaxValidationError, TokenError,
+    TokenValidationError, ValueError,
+};
+use crate::span::Span;
+
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Style {
+    #[default]
+    Plain,
+    Ansi,
+}
+
+impl Style {
+    fn paint(&self, code: &str, text: &str) -> String {
+        match self {
+            Style::Plain => text.to_string(),
+            Style::Ansi => format!("\x1b[{}m{}\x1b[0m", code, text),
+        }
+    }
+}
+
+const ERROR: &str = "1;31";
+const GUTTER: &str = "1;34";
+const HELP: &str = "1;36";
+
+pub struct DiagnosticRenderer<'a> {
+    source: &'a str,
+    origin: Option<&'a str>,
+    style: Style,
+}
+
+impl<'a> DiagnosticRenderer<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            origin: None,
+            style: Style::Plain,
+        }
+    }
+
+    pub fn with_origin(mut self, origin: &'a str) -> Self {
+        self.origin = Some(origin);
+        self
+    }
+
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn render(&self, error: &Error) -> String {
+        // A span in an included file points into text other than the source,
+        // so the message keeps its location and no snippet is shown.
+        let span = error.span().filter(|span| span.source == 0);
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "{}: {}",
+            self.style.paint(ERROR, "error"),
+            message(error, span)
+        );
+
+        if let Some(span) = span {
+            self.render_snippet(&mut out, span);
+        }
+
+        if let Some(help) = help(error) {
+            let indent = span.map_or(String::new(), |span| {
+                " ".repeat(span.line.to_string().len() + 1)
+            });
+            let _ = writeln!(
+                out,
+                "{}{} {}",
+                indent,
+                self.style.paint(HELP, "= help:"),
+                help
+            );
+        }
+
+        out
+    }
+
+    pub fn render_all(&self, errors: &Errors) -> String {
+        let rendered: Vec<String> = errors.iter().map(|error| self.render(error)).collect();
+        rendered.join("\n")
+    }
+
+    fn render_snippet(&self, out: &mut String, span: Span) {
+        let Some(line) = self.source.lines().nth(span.line.saturating_sub(1)) else {
+            return;
+        };
+
+        let line_number = span.line.to_string();
+        let padding = " ".repeat(line_number.len());
+        let gutter = self.style.paint(GUTTER, "|");
+        let origin = self.origin.unwrap_or("input");
+
+        let _ = writeln!(
+            out,
+            "{}{} {}:{}:{}",
+            padding,
+            self.style.paint(GUTTER, "-->"),
+            origin,
+            span.line,
+            span.column
+        );
+        let _ = writeln!(out, "{} {}", padding, gutter);
+        let _ = writeln!(
+            out,
+            "{} {} {}",
+            self.style.paint(GUTTER, &line_number),
+            gutter,
+            line
+        );
+
+        let prefix: String = line
+            .chars()
+            .take(span.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let underlined = line
+            .chars()
+            .skip(span.column.saturating_sub(1))
+            .scan(span.start, |pos, c| {
+                let start = *pos;
+                *pos += c.len_utf8();
+                (start < span.end).then_some(c)
+            })
+            .count()
+            .max(1);
+
+        let _ = writeln!(
+            out,
+            "{} {} {}{}",
+            padding,
+            gutter,
+            prefix,
+            self.style.paint(ERROR, &"^".repeat(underlined))
+        );
+        let _ = writeln!(out, "{} {}", padding, gutter);
+    }
+}
+
+fn message(error: &Error, span: Option<Span>) -> String {
+    let text = match error {
+        Error::Lexer(e) => e.to_string(),
+        Error::Parse(ParseError::Token(TokenValidationError::Key(e))) => e.to_string(),
+        Error::Parse(ParseError::Token(TokenValidationError::Value(e))) => e.to_string(),
+        Error::Parse(ParseError::Token(TokenValidationError::LineStructure(e))) => e.to_string(),
+        Error::Parse(ParseError::Syntax(e)) => e.to_string(),
+        _ => error.to_string(),
+    };
+
+    match span {
+        Some(span) => text
+            .strip_suffix(&format!(" at {}", span))
+            .map(str::to_string)
+            .unwrap_or(text),
+        None => text,
+    }
+}
+
+fn help(error: &Error) -> Option<&'static str> {
+    match error {
+        Error::Lexer(e) => token_help(e),
+        Error::Parse(ParseError::Token(TokenValidationError::Key(e))) => Some(key_help(e)),
+        Error::Parse(ParseError::Token(TokenValidationError::Value(e))) => Some(value_help(e)),
+        Error::Parse(ParseError::Token(TokenValidationError::LineStructure(e))) => {
+            Some(line_structure_help(e))
+        }
+        Error::Parse(ParseError::Syntax(e)) => Some(syntax_help(e)),
+        Error::UnknownKey { .. } => Some("remove the key or add it to the schema"),
+        Error::MissingKey { .. } => Some("add the key to the configuration"),
+        Error::TypeMismatch { .. } => Some("change the value to match the type in the schema"),
+        Error::IncludeCycle { .. } => Some("remove one of the `include` lines along the chain"),
+        Error::IncludeDepth { .. } => Some("include the deepest files directly from a parent"),
+        Error::InvalidOverride => Some("pass one key per override, e.g. `server.port=9000`"),
+        Error::Included { source, .. }
+        | Error::Override { source, .. }
+        | Error::InFile { source, .. } => help(source),
+        _ => None,
+    }
+}
+
+fn token_help(error: &TokenError) -> Option<&'static str> {
+    match error {
+        TokenError::InvalidChar { .. } => Some("remove the character or wrap the value in quotes"),
+        TokenError::UnterminatedString { .. } => Some("close the string on the same line"),
+        TokenError::UnterminatedReference { .. } => {
+            Some("close the reference with `}` on the same line, e.g. `${server.port}`")
+        }
+        TokenError::InvalidEscape { .. } => Some(
+            "use `\\n`, `\\t`, `\\r`, `\\\\`, `\\\"`, `\\'`, `\\0`, `\\uXXXX` or `\\u{...}`, \
+             or single quotes for text with literal backslashes",
+        ),
+        TokenError::Internal(_) => None,
+    }
+}
+
+fn key_help(error: &KeyError) -> &'static str {
+    match error {
+        KeyError::InvalidKeyStartsWithHyphen { .. } | KeyError::InvalidKeyEndsWithHyphen { .. } => {
+            "hyphens are only allowed between other characters of a key"
+        }
+        KeyError::UnexpectedTokenInKey { .. } => {
+            "keys are names separated by single dots, e.g. `server.port`"
+        }
+        KeyError::KeyCannotBeNumeric { .. } => {
+            "key parts must not start with a digit unless they are a list index like `servers.0`"
+        }
+        KeyError::InvalidQuotedKey { .. } => {
+            "quoted key parts must not be empty or contain only digits"
+        }
+        KeyError::ReferenceInKey { .. } => {
+            "references like `${server.port}` are only allowed in values"
+        }
+    }
+}
+
+fn value_help(error: &ValueError) -> &'static str {
+    match error {
+        ValueError::MultipleQuotedIdents { .. } | ValueError::MultipleMixedIdents { .. } => {
+            "a value is either one quoted string or one plain value"
+        }
+        ValueError::MultipleNonNumericIdents { .. }
+        | ValueError::MultipleDots { .. }
+        | ValueError::InvalidValueFormat { .. } => {
+            "wrap the value in quotes if it is meant to be a string"
+        }
+        ValueError::UnterminatedArray { .. } => "close the array with `]` on the same line",
+        ValueError::UnexpectedDelimiter { .. } => {
+            "brackets, braces and commas are only allowed in array and inline table values"
+        }
+        ValueError::EmptyArrayElement { .. } | ValueError::EmptyInlineTableEntry { .. } => {
+            "remove the extra comma"
+        }
+        ValueError::UnterminatedInlineTable { .. } => {
+            "close the inline table with `}` on the same line"
+        }
+        ValueError::InvalidDatetime { .. } => {
+            "dates and times use RFC 3339, e.g. `2024-01-15`, `10:30:00` or `2024-01-15T10:30:00Z`"
+        }
+        ValueError::InvalidHexLiteral { .. } => {
+            "hexadecimal literals use the digits 0-9 and a-f after `0x`, e.g. `0xFF`"
+        }
+        ValueError::InvalidOctalLiteral { .. } => {
+            "octal literals use the digits 0-7 after `0o`, e.g. `0o755`"
+        }
+        ValueError::InvalidBinaryLiteral { .. } => {
+            "binary literals use the digits 0 and 1 after `0b`, e.g. `0b1010`"
+        }
+        ValueError::InvalidDecimalLiteral { .. } => {
+            "decimal literals use digits with at most one `.` and an optional exponent, e.g. `1_000.5e-3`"
+        }
+        ValueError::MisplacedDigitSeparator { .. } => {
+            "`_` is only allowed between two digits, e.g. `1_000_000`"
+        }
+        ValueError::InvalidExponent { .. } => {
+            "write exponents as `e` followed by an optional sign and digits, e.g. `1e-3`"
+        }
+        ValueError::NumberOutOfRange { .. } => {
+            "integers must fit in 64 bits and decimals must be finite 64-bit floats"
+        }
+        ValueError::MisorderedDurationUnits { .. } => {
+            "write each unit once, from the largest to the smallest, e.g. `1h30m`"
+        }
+        ValueError::DurationOutOfRange { .. } => "durations must fit in 64 bits of seconds",
+        ValueError::SizeOutOfRange { .. } => "sizes must fit in 64 bits of bytes",
+    }
+}
+
+fn line_structure_help(error: &LineStructureError) -> &'static str {
+    match error {
+        LineStructureError::MissingSeparators { .. } => {
+            "each line must have the form `key = value`"
+        }
+        LineStructureError::MultipleSeparators { .. } => {
+            "wrap the value in quotes if it contains a separator"
+        }
+        LineStructureError::MissingLeftSide { .. } => "add a key before the separator",
+        LineStructureError::LeftSideMustBeIdent { .. } => "keys must not end with a dot",
+        LineStructureError::RightSideContainsInvalidTokens { .. } => {
+            "values must not end with a dot, a comma or an opening bracket or brace"
+        }
+        LineStructureError::InvalidSectionHeader { .. } => {
+            "write a section header as a key in brackets on its own line, e.g. `[server.tls]`"
+        }
+    }
+}
+
+fn syntax_help(error: &SyntaxValidationError) -> &'static str {
+    match error {
+        SyntaxValidationError::Duplicate { .. } => "each key may only be assigned once",
+        SyntaxValidationError::QuotedNotAllowed { .. } => "schema types must not be quoted",
+        SyntaxValidationError::OptionalNotAllowed { .. } => {
+            "only schema types can be marked optional with `?`"
+        }
+        SyntaxValidationError::KeyPathConflict { .. } => {
+            "a key cannot hold a value and nested keys at the same time"
+        }
+        SyntaxValidationError::InvalidArrayType { .. } => "write array types as `[type]`",
+        SyntaxValidationError::IndexOutOfSequence { .. } => {
+            "list indices must start at 0 and increase by one"
+        }
+        SyntaxValidationError::InvalidSchemaKey { .. } => {
+            "use `*` for list elements in schema keys, e.g. `servers.*.host`"
+        }
+        SyntaxValidationError::UnknownType { .. } => {
+            "use one of string, integer, unsigned_integer, float, bool, date, time, datetime, duration or size"
+        }
+        SyntaxValidationError::EmptyValue { .. } => {
+            "write `null` to unset the value or `''` for an empty string"
+        }
+        SyntaxValidationError::UnresolvedReference { .. } => {
+            "define the referenced key, or write `$${` for a literal `${`"
+        }
+        SyntaxValidationError::InvalidReference { .. } => {
+            "only strings, numbers and other single values can be embedded in text; \
+             reference arrays and tables as the whole value"
+        }
+        SyntaxValidationError::CircularReference { .. } => {
+            "break the chain so no key refers back to itself"
+        }
+        SyntaxValidationError::UndefinedVariable { .. } => {
+            "set the variable or give a default, e.g. `${env:PORT:-8080}`"
+        }
+        SyntaxValidationError::InvalidProfileKey { .. } => {
+            "put keys inside a named profile, e.g. `server.port` under `[profile.production]`"
+        }
+        SyntaxValidationError::InvalidEnvOverrideKey { .. } => {
+            "separate key parts with `__` and use only letters, digits, `_` and `-` in each, \
+             e.g. `MYAPP__SERVER__PORT`"
+        }
+        SyntaxValidationError::EnvOverrideConflict { .. } => {
+            "a variable may not turn a table into a value or a value into a table"
+        }
+        SyntaxValidationError::EnvOverrideIndexOutOfSequence { .. } => {
+            "a variable may set an existing list element or append the next index"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn duplicate_error() -> Error {
+        Error::Parse(ParseError::Syntax(SyntaxValidationError::Duplicate {
+            key: "server.host".into(),
+            span: Span::new(24, 35, 2, 1),
+        }))
+    }
+
+    #[test]
+    fn test_render_plain() {
+        let source = "server.host = localhost\nserver.host = other\n";
+        let rendered = DiagnosticRenderer::new(source)
+            .with_origin("app.conf")
+            .render(&duplicate_error());
+
+        let expected = [
+            "error: duplicate key found: 'server.host'",
+            " --> app.conf:2:1",
+            "  |",
+            "2 | server.host = other",
+            "  | ^^^^^^^^^^^",
+            "  |",
+            "  = help: each key may only be assigned once",
+            "",
+        ];
+        assert_eq!(rendered, expected.join("\n"));
+    }
+
+    #[test]
+    fn test_render_column_offset() {
+        let source = "key = 'value";
+        let error = Error::Lexer(TokenError::UnterminatedString {
+            span: Span::new(6, 12, 1, 7),
+        });
+        let rendered = DiagnosticRenderer::new(source).render(&error);
+
+        assert!(rendered.contains(" --> input:1:7\n"));
+        assert!(rendered.contains("1 | key = 'value\n  |       ^^^^^^\n"));
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let source = "server.host = localhost\nserver.host = other\n";
+        let rendered = DiagnosticRenderer::new(source)
+            .with_style(Style::Ansi)
+            .render(&duplicate_error());
+
+        assert!(rendered.starts_with("\x1b[1;31merror\x1b[0m: duplicate key found"));
+        assert!(rendered.contains("\x1b[1;31m^^^^^^^^^^^\x1b[0m"));
+    }
+
+    #[test]
+    fn test_render_included_span() {
+        let error = Error::Parse(ParseError::Syntax(SyntaxValidationError::Duplicate {
+            key: "server.host".into(),
+            span: Span {
+                source: 1,
+                ..Span::new(24, 35, 2, 1)
+            },
+        }));
+        let rendered = DiagnosticRenderer::new("server.host = localhost\nname = app\n")
+            .with_origin("app.conf")
+            .render(&error);
+
+        assert_eq!(
+            rendered,
+            "error: duplicate key found: 'server.host' at line 2, column 1\n\
+             = help: each key may only be assigned once\n"
+        );
+    }
+
+    #[test]
+    fn test_render_without_span() {
+        let error = Error::UnknownKey { key: "port".into() };
+        let rendered = DiagnosticRenderer::new("").render(&error);
+
+        assert_eq!(
+            rendered,
+            "error: validation error: unknown key 'port'\n\
+             = help: remove the key or add it to the schema\n"
+        );
+    }
+
+    #[test]
+    fn test_render_env_override() {
+        let error = Error::Parse(ParseError::Syntax(
+            SyntaxValidationError::EnvOverrideConflict {
+                name: "MYAPP__A__B".into(),
+                key: "a".into(),
+            },
+        ));
+        let rendered = DiagnosticRenderer::new("a = 1\n").render(&error);
+
+        assert_eq!(
+            rendered,
+            "error: environment variable 'MYAPP__A__B' conflicts with key 'a'\n\
+             = help: a variable may not turn a table into a value or a value into a table\n"
+        );
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::{BTreeMap, HashMap};
+
+/// Where `${env:NAME}` references and prefixed overrides in values are
+/// looked up.
+pub trait Environment {
+    fn var(&self, name: &str) -> Option<String>;
+
+    /// Every variable, sorted by name.
+    fn vars(&self) -> Vec<(String, String)>;
+}
+
+/// The environment of the running process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProcessEnvironment;
+
+impl Environment for ProcessEnvironment {
+    fn var(&self, name: &str) -> Option<String> {
+        std::env::var(name).ok()
+    }
+
+    /// Variables whose name or value is not valid unicode are skipped.
+    fn vars(&self) -> Vec<(String, String)> {
+        let mut vars: Vec<(String, String)> = std::env::vars_os()
+            .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+            .collect();
+        vars.sort();
+        vars
+    }
+}
+
+impl Environment for HashMap<String, String> {
+    fn var(&self, name: &str) -> Option<String> {
+        self.get(name).cloned()
+    }
+
+    fn vars(&self) -> Vec<(String, String)> {
+        let mut vars: Vec<(String, String)> = self
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        vars.sort();
+        vars
+    }
+}
+
+impl Environment for BTreeMap<String, String> {
+    fn var(&self, name: &str) -> Option<String> {
+        self.get(name).cloned()
+    }
+
+    fn vars(&self) -> Vec<(String, String)> {
+        self.iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+}
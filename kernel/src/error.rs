@@ -1,3 +1,6 @@
+use crate::de::DeserializeError;
+use crate::span::Span;
+use std::fmt::Display;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -12,22 +15,116 @@ pub enum Error {
     #[error("validation error: missing key '{key}'")]
     MissingKey { key: String },
 
-    #[error("validation error: type mismatch: expected '{expected}', found '{found}'")]
-    TypeMismatch { expected: String, found: String },
+    #[error("validation error: type mismatch at '{key}': expected '{expected}', found '{found}'")]
+    TypeMismatch {
+        key: String,
+        expected: String,
+        found: String,
+    },
 
     #[error("serde error: {0}")]
-    Serde(String),
+    Serde(#[from] DeserializeError),
 
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+
+    #[error("include error: cycle detected: {chain}")]
+    IncludeCycle { chain: String },
+    #[error("include error: includes nested deeper than {max}: {chain}")]
+    IncludeDepth { chain: String, max: usize },
+    #[error("in included file '{path}': {source}")]
+    Included { path: String, source: Box<Error> },
+
+    #[error("in override '{arg}': {source}")]
+    Override { arg: String, source: Box<Error> },
+    #[error("override error: expected a single key.path=value entry")]
+    InvalidOverride,
+    #[error("in file '{path}': {source}")]
+    InFile { path: String, source: Box<Error> },
+}
+
+#[derive(Debug, Default)]
+pub struct Errors(Vec<Error>);
+
+impl Errors {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn push(&mut self, error: impl Into<Error>) {
+        self.0.push(error.into());
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Error> {
+        self.0.iter()
+    }
+
+    pub fn into_result<T>(self, value: T) -> Result<T, Errors> {
+        if self.is_empty() {
+            Ok(value)
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl Display for Errors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let messages: Vec<String> = self.0.iter().map(|e| e.to_string()).collect();
+        write!(f, "{}", messages.join("\n"))
+    }
+}
+
+impl std::error::Error for Errors {}
+
+impl From<Error> for Errors {
+    fn from(error: Error) -> Self {
+        Self(vec![error])
+    }
+}
+
+impl<E: Into<Error>> Extend<E> for Errors {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        self.0.extend(iter.into_iter().map(Into::into));
+    }
+}
+
+impl IntoIterator for Errors {
+    type Item = Error;
+    type IntoIter = std::vec::IntoIter<Error>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Errors {
+    type Item = &'a Error;
+    type IntoIter = std::slice::Iter<'a, Error>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
 }
 
 #[derive(Debug, Error)]
 pub enum TokenError {
-    #[error("Invalid character: '{0}'")]
-    InvalidChar(char),
-    #[error("Unterminated string literal")]
-    UnterminatedString,
+    #[error("Invalid character: '{char}' at {span}")]
+    InvalidChar { char: char, span: Span },
+    #[error("Unterminated string literal at {span}")]
+    UnterminatedString { span: Span },
+    #[error("Invalid escape sequence at {span}")]
+    InvalidEscape { span: Span },
+    #[error("Unterminated reference at {span}")]
+    UnterminatedReference { span: Span },
     #[error("Internal lexer error: {0}")]
     Internal(String),
 }
@@ -57,63 +154,284 @@ pub enum TokenValidationError {
 
 #[derive(Debug, Error)]
 pub enum SyntaxValidationError {
-    #[error("duplicate key found")]
-    Duplicate { key: String },
+    #[error("duplicate key found: '{key}' at {span}")]
+    Duplicate { key: String, span: Span },
+
+    #[error("quoted value not allowed in this context at {span}")]
+    QuotedNotAllowed { span: Span },
+
+    #[error("optional marker not allowed in this context at {span}")]
+    OptionalNotAllowed { span: Span },
+
+    #[error("key path conflict: '{key}' at {span}")]
+    KeyPathConflict { key: String, span: Span },
+
+    #[error("array type must name exactly one element type at {span}")]
+    InvalidArrayType { span: Span },
+
+    #[error("array index out of sequence: '{key}' at {span}")]
+    IndexOutOfSequence { key: String, span: Span },
+
+    #[error("invalid schema key: '{key}' at {span}")]
+    InvalidSchemaKey { key: String, span: Span },
+
+    #[error("unknown schema type: '{name}' at {span}")]
+    UnknownType { name: String, span: Span },
+
+    #[error("empty value: '{key}' at {span}")]
+    EmptyValue { key: String, span: Span },
+
+    #[error("unresolved reference: '{reference}' at {span}")]
+    UnresolvedReference { reference: String, span: Span },
+
+    #[error("invalid reference: '{reference}' at {span}")]
+    InvalidReference { reference: String, span: Span },
+
+    #[error("circular reference: {chain} at {span}")]
+    CircularReference { chain: String, span: Span },
+
+    #[error("undefined environment variable: '{name}' at {span}")]
+    UndefinedVariable { name: String, span: Span },
+
+    #[error("invalid profile key: '{key}' at {span}")]
+    InvalidProfileKey { key: String, span: Span },
 
-    #[error("quoted value not allowed in this context")]
-    QuotedNotAllowed,
+    #[error("environment variable '{name}' does not name a valid key")]
+    InvalidEnvOverrideKey { name: String },
 
-    #[error("key path conflict: '{key}'")]
-    KeyPathConflict { key: String },
+    #[error("environment variable '{name}' conflicts with key '{key}'")]
+    EnvOverrideConflict { name: String, key: String },
+
+    #[error("environment variable '{name}' sets array index out of sequence: '{key}'")]
+    EnvOverrideIndexOutOfSequence { name: String, key: String },
 }
 
 #[derive(Debug, Error)]
 pub enum KeyError {
-    #[error("Key cannot start with hyphen: '{key_part}'")]
-    InvalidKeyStartsWithHyphen { key_part: String },
+    #[error("Key cannot start with hyphen: '{key_part}' at {span}")]
+    InvalidKeyStartsWithHyphen { key_part: String, span: Span },
+
+    #[error("Key cannot end with hyphen: '{key_part}' at {span}")]
+    InvalidKeyEndsWithHyphen { key_part: String, span: Span },
+
+    #[error("Unexpected token in key at {span}")]
+    UnexpectedTokenInKey { span: Span },
 
-    #[error("Key cannot end with hyphen: '{key_part}'")]
-    InvalidKeyEndsWithHyphen { key_part: String },
+    #[error("Key cannot be numeric: '{key_part}' at {span}")]
+    KeyCannotBeNumeric { key_part: String, span: Span },
 
-    #[error("Unexpected token in key")]
-    UnexpectedTokenInKey,
+    #[error("Invalid quoted key: '{key_part}' at {span}")]
+    InvalidQuotedKey { key_part: String, span: Span },
 
-    #[error("Key cannot be numeric: '{key_part}'")]
-    KeyCannotBeNumeric { key_part: String },
+    #[error("Reference not allowed in key: '{key_part}' at {span}")]
+    ReferenceInKey { key_part: String, span: Span },
 }
 
 #[derive(Debug, Error)]
 pub enum ValueError {
-    #[error("Value cannot contain multiple non-numeric identifiers")]
-    MultipleNonNumericIdents,
+    #[error("Value cannot contain multiple non-numeric identifiers at {span}")]
+    MultipleNonNumericIdents { span: Span },
+
+    #[error("Value cannot contain multiple quoted identifiers at {span}")]
+    MultipleQuotedIdents { span: Span },
+
+    #[error("Value cannot contain multiple mixed identifiers at {span}")]
+    MultipleMixedIdents { span: Span },
+
+    #[error("Value cannot contain multiple dots at {span}")]
+    MultipleDots { span: Span },
+
+    #[error("Invalid value format at {span}")]
+    InvalidValueFormat { span: Span },
+
+    #[error("Unterminated array at {span}")]
+    UnterminatedArray { span: Span },
+
+    #[error("Unexpected delimiter at {span}")]
+    UnexpectedDelimiter { span: Span },
+
+    #[error("Empty array element at {span}")]
+    EmptyArrayElement { span: Span },
+
+    #[error("Unterminated inline table at {span}")]
+    UnterminatedInlineTable { span: Span },
+
+    #[error("Empty inline table entry at {span}")]
+    EmptyInlineTableEntry { span: Span },
+
+    #[error("Invalid date or time at {span}")]
+    InvalidDatetime { span: Span },
+
+    #[error("Invalid hexadecimal literal at {span}")]
+    InvalidHexLiteral { span: Span },
+
+    #[error("Invalid octal literal at {span}")]
+    InvalidOctalLiteral { span: Span },
+
+    #[error("Invalid binary literal at {span}")]
+    InvalidBinaryLiteral { span: Span },
+
+    #[error("Invalid decimal literal at {span}")]
+    InvalidDecimalLiteral { span: Span },
+
+    #[error("Misplaced digit separator at {span}")]
+    MisplacedDigitSeparator { span: Span },
 
-    #[error("Value cannot contain multiple quoted identifiers")]
-    MultipleQuotedIdents,
+    #[error("Invalid exponent at {span}")]
+    InvalidExponent { span: Span },
 
-    #[error("Value cannot contain multiple mixed identifiers")]
-    MultipleMixedIdents,
+    #[error("Number out of range at {span}")]
+    NumberOutOfRange { span: Span },
 
-    #[error("Value cannot contain multiple dots")]
-    MultipleDots,
+    #[error("Duration units out of order at {span}")]
+    MisorderedDurationUnits { span: Span },
 
-    #[error("Invalid value format")]
-    InvalidValueFormat,
+    #[error("Duration out of range at {span}")]
+    DurationOutOfRange { span: Span },
+
+    #[error("Size out of range at {span}")]
+    SizeOutOfRange { span: Span },
 }
 
 #[derive(Debug, Error)]
 pub enum LineStructureError {
-    #[error("Missing separator in line")]
-    MissingSeparators,
+    #[error("Missing separator in line at {span}")]
+    MissingSeparators { span: Span },
+
+    #[error("Multiple separator found, expected exactly one at {span}")]
+    MultipleSeparators { span: Span },
+
+    #[error("Missing key before '=' at {span}")]
+    MissingLeftSide { span: Span },
+
+    #[error("Token before '=' must be an identifier at {span}")]
+    LeftSideMustBeIdent { span: Span },
+
+    #[error("Value contains invalid tokens at {span}")]
+    RightSideContainsInvalidTokens { span: Span },
+
+    #[error("Invalid section header at {span}")]
+    InvalidSectionHeader { span: Span },
+}
+
+impl Error {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Error::Lexer(e) => e.span(),
+            Error::Parse(e) => e.span(),
+            _ => None,
+        }
+    }
+}
 
-    #[error("Multiple separator found, expected exactly one")]
-    MultipleSeparators,
+impl TokenError {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            TokenError::InvalidChar { span, .. }
+            | TokenError::UnterminatedString { span }
+            | TokenError::InvalidEscape { span }
+            | TokenError::UnterminatedReference { span } => Some(*span),
+            TokenError::Internal(_) => None,
+        }
+    }
+}
 
-    #[error("Missing key before '='")]
-    MissingLeftSide,
+impl ParseError {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ParseError::Token(e) => e.span(),
+            ParseError::Syntax(e) => e.span(),
+        }
+    }
+}
 
-    #[error("Token before '=' must be an identifier")]
-    LeftSideMustBeIdent,
+impl TokenValidationError {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            TokenValidationError::Key(e) => Some(e.span()),
+            TokenValidationError::Value(e) => Some(e.span()),
+            TokenValidationError::LineStructure(e) => Some(e.span()),
+            TokenValidationError::Internal(_) => None,
+        }
+    }
+}
+
+impl SyntaxValidationError {
+    /// Overrides taken from the environment were not written in any source,
+    /// so they have no span.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            SyntaxValidationError::Duplicate { span, .. }
+            | SyntaxValidationError::QuotedNotAllowed { span }
+            | SyntaxValidationError::OptionalNotAllowed { span }
+            | SyntaxValidationError::KeyPathConflict { span, .. }
+            | SyntaxValidationError::InvalidArrayType { span }
+            | SyntaxValidationError::IndexOutOfSequence { span, .. }
+            | SyntaxValidationError::InvalidSchemaKey { span, .. }
+            | SyntaxValidationError::UnknownType { span, .. }
+            | SyntaxValidationError::EmptyValue { span, .. }
+            | SyntaxValidationError::UnresolvedReference { span, .. }
+            | SyntaxValidationError::InvalidReference { span, .. }
+            | SyntaxValidationError::CircularReference { span, .. }
+            | SyntaxValidationError::UndefinedVariable { span, .. }
+            | SyntaxValidationError::InvalidProfileKey { span, .. } => Some(*span),
+            SyntaxValidationError::InvalidEnvOverrideKey { .. }
+            | SyntaxValidationError::EnvOverrideConflict { .. }
+            | SyntaxValidationError::EnvOverrideIndexOutOfSequence { .. } => None,
+        }
+    }
+}
+
+impl KeyError {
+    pub fn span(&self) -> Span {
+        match self {
+            KeyError::InvalidKeyStartsWithHyphen { span, .. }
+            | KeyError::InvalidKeyEndsWithHyphen { span, .. }
+            | KeyError::UnexpectedTokenInKey { span }
+            | KeyError::KeyCannotBeNumeric { span, .. }
+            | KeyError::InvalidQuotedKey { span, .. }
+            | KeyError::ReferenceInKey { span, .. } => *span,
+        }
+    }
+}
+
+impl ValueError {
+    pub fn span(&self) -> Span {
+        match self {
+            ValueError::MultipleNonNumericIdents { span }
+            | ValueError::MultipleQuotedIdents { span }
+            | ValueError::MultipleMixedIdents { span }
+            | ValueError::MultipleDots { span }
+            | ValueError::InvalidValueFormat { span }
+            | ValueError::UnterminatedArray { span }
+            | ValueError::UnexpectedDelimiter { span }
+            | ValueError::EmptyArrayElement { span }
+            | ValueError::UnterminatedInlineTable { span }
+            | ValueError::EmptyInlineTableEntry { span }
+            | ValueError::InvalidDatetime { span }
+            | ValueError::InvalidHexLiteral { span }
+            | ValueError::InvalidOctalLiteral { span }
+            | ValueError::InvalidBinaryLiteral { span }
+            | ValueError::InvalidDecimalLiteral { span }
+            | ValueError::MisplacedDigitSeparator { span }
+            | ValueError::InvalidExponent { span }
+            | ValueError::NumberOutOfRange { span }
+            | ValueError::MisorderedDurationUnits { span }
+            | ValueError::DurationOutOfRange { span }
+            | ValueError::SizeOutOfRange { span } => *span,
+        }
+    }
+}
 
-    #[error("Value contains invalid tokens")]
-    RightSideContainsInvalidTokens,
+impl LineStructureError {
+    pub fn span(&self) -> Span {
+        match self {
+            LineStructureError::MissingSeparators { span }
+            | LineStructureError::MultipleSeparators { span }
+            | LineStructureError::MissingLeftSide { span }
+            | LineStructureError::LeftSideMustBeIdent { span }
+            | LineStructureError::RightSideContainsInvalidTokens { span }
+            | LineStructureError::InvalidSectionHeader { span } => *span,
+        }
+    }
 }
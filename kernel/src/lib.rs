@@ -1,9 +1,15 @@
+pub mod datetime;
 pub mod de;
+pub mod diagnostic;
+pub mod env;
 pub mod error;
+pub mod options;
 pub mod parse;
 pub mod schema;
 pub mod shared;
 pub mod source;
+pub mod span;
 pub mod token;
 pub mod tokenize;
+pub mod units;
 pub mod value;
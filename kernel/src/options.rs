@@ -0,0 +1,63 @@
+/// How a line with nothing after the separator, such as `key =`, is read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmptyValue {
+    #[default]
+    EmptyString,
+    Null,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ParseOptions {
+    /// Accept non-ASCII letters and digits in unquoted keys and values.
+    pub unicode_idents: bool,
+    /// What an empty right-hand side means.
+    pub empty_value: EmptyValue,
+    /// Remove the indentation shared by all lines of triple-quoted strings.
+    pub strip_indent: bool,
+    /// Reject unknown escapes such as `\q` instead of keeping them as written.
+    pub strict_escapes: bool,
+    /// Let environment variables such as `MYAPP__SERVER__PORT` override keys
+    /// when this is `Some("MYAPP")`.
+    pub env_prefix: Option<String>,
+    /// The profile whose `[profile.<name>]` keys replace the base keys.
+    /// Every other profile is dropped. Without one, `profile` is an ordinary
+    /// key.
+    pub profile: Option<String>,
+}
+
+impl ParseOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_unicode_idents(mut self, enabled: bool) -> Self {
+        self.unicode_idents = enabled;
+        self
+    }
+
+    pub fn with_empty_value(mut self, policy: EmptyValue) -> Self {
+        self.empty_value = policy;
+        self
+    }
+
+    pub fn with_strip_indent(mut self, enabled: bool) -> Self {
+        self.strip_indent = enabled;
+        self
+    }
+
+    pub fn with_strict_escapes(mut self, enabled: bool) -> Self {
+        self.strict_escapes = enabled;
+        self
+    }
+
+    pub fn with_env_prefix(mut self, prefix: &str) -> Self {
+        self.env_prefix = Some(prefix.to_string());
+        self
+    }
+
+    pub fn with_profile(mut self, profile: &str) -> Self {
+        self.profile = Some(profile.to_string());
+        self
+    }
+}
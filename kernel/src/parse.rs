@@ -1,6 +1,46 @@
+use crate::env::{Environment, ProcessEnvironment};
 use crate::error::ParseError;
-use crate::token::Token;
+use crate::options::ParseOptions;
+use crate::token::SpannedToken;
 
 pub trait Parse<T> {
-    fn parse(tokens: Vec<Token>) -> Result<T, ParseError>;
+    fn parse_all_with_env(
+        tokens: Vec<SpannedToken>,
+        options: ParseOptions,
+        env: &dyn Environment,
+    ) -> (T, Vec<ParseError>);
+
+    fn parse_all_with_options(
+        tokens: Vec<SpannedToken>,
+        options: ParseOptions,
+    ) -> (T, Vec<ParseError>) {
+        Self::parse_all_with_env(tokens, options, &ProcessEnvironment)
+    }
+
+    fn parse_all(tokens: Vec<SpannedToken>) -> (T, Vec<ParseError>) {
+        Self::parse_all_with_options(tokens, ParseOptions::default())
+    }
+
+    fn parse_with_env(
+        tokens: Vec<SpannedToken>,
+        options: ParseOptions,
+        env: &dyn Environment,
+    ) -> Result<T, ParseError> {
+        let (value, errors) = Self::parse_all_with_env(tokens, options, env);
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(value),
+        }
+    }
+
+    fn parse_with_options(
+        tokens: Vec<SpannedToken>,
+        options: ParseOptions,
+    ) -> Result<T, ParseError> {
+        Self::parse_with_env(tokens, options, &ProcessEnvironment)
+    }
+
+    fn parse(tokens: Vec<SpannedToken>) -> Result<T, ParseError> {
+        Self::parse_with_options(tokens, ParseOptions::default())
+    }
 }
@@ -9,7 +9,13 @@ pub enum Schema {
     Integer,
     UnsignedInteger,
     Float,
+    Date,
+    Time,
+    DateTime,
+    Duration,
+    Size,
     Optional(Box<Schema>),
+    Array(Box<Schema>),
     Dict(Map<String, Schema>),
 }
 
@@ -21,7 +27,13 @@ impl Display for Schema {
             Schema::Integer => write!(f, "integer"),
             Schema::UnsignedInteger => write!(f, "unsigned_integer"),
             Schema::Float => write!(f, "float"),
+            Schema::Date => write!(f, "date"),
+            Schema::Time => write!(f, "time"),
+            Schema::DateTime => write!(f, "datetime"),
+            Schema::Duration => write!(f, "duration"),
+            Schema::Size => write!(f, "size"),
             Schema::Optional(inner) => write!(f, "{}?", inner),
+            Schema::Array(inner) => write!(f, "[{}]", inner),
             Schema::Dict(map) => {
                 let dict_str: Vec<String> =
                     map.iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
@@ -1,22 +1,25 @@
 use crate::error::TokenError;
+use crate::span::Span;
 use crate::token::Token;
 use std::char;
 
 use std::iter::Peekable;
+use std::str::CharIndices;
 
 #[derive(Debug, PartialEq)]
 pub enum TokenResult {
     Single(Token),
     NeedsBlock(char),
     Comment,
+    Invalid,
 }
 
-pub trait TryFromSource {
+pub trait TryFromSource: AsRef<str> {
     fn classify_char(&self, c: char) -> TokenResult;
     fn process_block_token(
         &self,
-        char: char,
-        chars: &mut Peekable<std::str::Chars>,
+        start: (usize, char),
+        chars: &mut Peekable<CharIndices>,
     ) -> Result<Token, TokenError>;
 
     fn is_skip_char(&self, c: char) -> bool {
@@ -24,16 +27,29 @@ pub trait TryFromSource {
     }
 
     fn is_invalid_chars(&self, c: char) -> bool {
-        const DEFAULT_INVALID: &[char] = &[
-            '[', ']', '{', '}', ',', ';', '!', '@', '$', '%', '^', '&', '*', '(', ')', '+',
-        ];
+        const DEFAULT_INVALID: &[char] = &[';', '!', '@', '%', '^', '&', '(', ')'];
 
         DEFAULT_INVALID.contains(&c)
             || self.additional_invalid_chars().contains(&c)
-            || !c.is_ascii()
+            || !(c.is_ascii() || self.allows_unicode_idents() && c.is_alphanumeric())
     }
 
     fn additional_invalid_chars(&self) -> &[char] {
         &[]
     }
+
+    fn allows_unicode_idents(&self) -> bool {
+        false
+    }
+
+    fn span(&self, start: usize, end: usize) -> Span {
+        Span::locate(self.as_ref(), start, end)
+    }
+
+    fn invalid_char(&self, pos: usize, c: char) -> TokenError {
+        TokenError::InvalidChar {
+            char: c,
+            span: self.span(pos, pos + c.len_utf8()),
+        }
+    }
 }
@@ -0,0 +1,87 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    /// The input the span is in: 0 for the text being parsed, `n` for the
+    /// `n`-th file it included.
+    pub source: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Self {
+            start,
+            end,
+            line,
+            column,
+            source: 0,
+        }
+    }
+
+    pub fn locate(input: &str, start: usize, end: usize) -> Self {
+        let before = &input[..start];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |pos| pos + 1);
+        let column = input[line_start..start].chars().count() + 1;
+
+        Self::new(start, end, line, column)
+    }
+
+    pub fn to(self, other: Span) -> Self {
+        if other.start < self.start {
+            return other.to(self);
+        }
+
+        Self {
+            end: self.end.max(other.end),
+            ..self
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locate() {
+        let input = "a = 1\nkey = value\n";
+
+        assert_eq!(Span::locate(input, 0, 1), Span::new(0, 1, 1, 1));
+        assert_eq!(Span::locate(input, 6, 9), Span::new(6, 9, 2, 1));
+        assert_eq!(Span::locate(input, 12, 17), Span::new(12, 17, 2, 7));
+    }
+
+    #[test]
+    fn test_locate_multibyte() {
+        let input = "あ = 'い'";
+        assert_eq!(Span::locate(input, 6, 13), Span::new(6, 13, 1, 5));
+    }
+
+    #[test]
+    fn test_to() {
+        let first = Span::new(0, 3, 1, 1);
+        let second = Span::new(6, 9, 1, 7);
+
+        assert_eq!(first.to(second), Span::new(0, 9, 1, 1));
+        assert_eq!(second.to(first), Span::new(0, 9, 1, 1));
+    }
+}
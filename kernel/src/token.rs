@@ -1,9 +1,40 @@
+use crate::span::Span;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     Ident(String),
     QuotedIdent(String),
     Separator,
     Dot,
+    LeftBracket,
+    RightBracket,
+    LeftBrace,
+    RightBrace,
+    Comma,
     Newline,
     Eof,
 }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
+impl SpannedToken {
+    pub fn new(token: Token, span: Span) -> Self {
+        Self { token, span }
+    }
+}
+
+impl From<Token> for SpannedToken {
+    fn from(token: Token) -> Self {
+        Self::new(token, Span::default())
+    }
+}
+
+impl PartialEq<Token> for SpannedToken {
+    fn eq(&self, other: &Token) -> bool {
+        self.token == *other
+    }
+}
@@ -1,10 +1,20 @@
 use crate::error::TokenError;
 use crate::source::TryFromSource;
-use crate::token::Token;
-use std::convert::AsRef;
+use crate::token::SpannedToken;
 
 pub trait Tokenize {
-    fn tokenize<T>(source: T) -> Result<Vec<Token>, TokenError>
+    fn tokenize_all<T>(source: T) -> (Vec<SpannedToken>, Vec<TokenError>)
     where
-        T: TryFromSource + AsRef<str>;
+        T: TryFromSource;
+
+    fn tokenize<T>(source: T) -> Result<Vec<SpannedToken>, TokenError>
+    where
+        T: TryFromSource,
+    {
+        let (tokens, errors) = Self::tokenize_all(source);
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(tokens),
+        }
+    }
 }
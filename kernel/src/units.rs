@@ -0,0 +1,284 @@
+use crate::error::ValueError;
+use crate::span::Span;
+use std::time::Duration;
+
+const DURATION_UNITS: &[(&str, u128)] = &[
+    ("d", 86_400_000_000_000),
+    ("h", 3_600_000_000_000),
+    ("m", 60_000_000_000),
+    ("s", 1_000_000_000),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+const SIZE_UNITS: &[(&str, u128)] = &[
+    ("B", 1),
+    ("KB", 1_000),
+    ("MB", 1_000_000),
+    ("GB", 1_000_000_000),
+    ("TB", 1_000_000_000_000),
+    ("PB", 1_000_000_000_000_000),
+    ("KiB", 1 << 10),
+    ("MiB", 1 << 20),
+    ("GiB", 1 << 30),
+    ("TiB", 1 << 40),
+    ("PiB", 1 << 50),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnitError {
+    MisorderedUnits,
+    DurationOutOfRange,
+    SizeOutOfRange,
+}
+
+impl UnitError {
+    pub fn at(self, span: Span) -> ValueError {
+        match self {
+            UnitError::MisorderedUnits => ValueError::MisorderedDurationUnits { span },
+            UnitError::DurationOutOfRange => ValueError::DurationOutOfRange { span },
+            UnitError::SizeOutOfRange => ValueError::SizeOutOfRange { span },
+        }
+    }
+}
+
+/// Parses a duration such as `30s`, `1.5h` or `1h30m`. Each component is a
+/// number followed by one of `d`, `h`, `m`, `s`, `ms`, `us` or `ns`, from
+/// the largest unit to the smallest. Returns `None` when the text is not
+/// shaped like a duration at all, so the caller can treat it as a string.
+pub fn parse_duration(s: &str) -> Option<Result<Duration, UnitError>> {
+    if s.is_empty() {
+        return None;
+    }
+
+    let mut components = Vec::new();
+    let mut rest = s;
+    while !rest.is_empty() {
+        let (amount, unit, remaining) = split_component(rest)?;
+        components.push((amount, lookup(DURATION_UNITS, unit)?));
+        rest = remaining;
+    }
+
+    let misordered = components.windows(2).any(|pair| pair[0].1 <= pair[1].1);
+    if misordered {
+        return Some(Err(UnitError::MisorderedUnits));
+    }
+
+    let total = components.iter().try_fold(0u128, |total, (amount, scale)| {
+        total.checked_add(amount.scale(*scale)?)
+    });
+    let duration = total.and_then(|total| {
+        let secs = u64::try_from(total / 1_000_000_000).ok()?;
+        Some(Duration::new(secs, (total % 1_000_000_000) as u32))
+    });
+    Some(duration.ok_or(UnitError::DurationOutOfRange))
+}
+
+/// Parses a byte size such as `512KiB` or `10MB` into a number of bytes.
+/// `KB`, `MB`, ... are powers of 1000 and `KiB`, `MiB`, ... powers of 1024.
+/// Returns `None` when the text is not shaped like a size, including a
+/// fraction that does not come out to whole bytes.
+pub fn parse_size(s: &str) -> Option<Result<u64, UnitError>> {
+    let (amount, unit, rest) = split_component(s)?;
+    if !rest.is_empty() {
+        return None;
+    }
+
+    let scale = lookup(SIZE_UNITS, unit)?;
+    if !amount.is_exact(scale) {
+        return None;
+    }
+    let bytes = amount
+        .scale(scale)
+        .and_then(|bytes| u64::try_from(bytes).ok());
+    Some(bytes.ok_or(UnitError::SizeOutOfRange))
+}
+
+/// Formats a duration the way [`parse_duration`] reads it, e.g. `1h30m`.
+pub fn format_duration(duration: &Duration) -> String {
+    let mut remaining = duration.as_nanos();
+    if remaining == 0 {
+        return "0s".to_string();
+    }
+
+    let mut out = String::new();
+    for (unit, scale) in DURATION_UNITS {
+        let count = remaining / scale;
+        if count > 0 {
+            out.push_str(&format!("{}{}", count, unit));
+            remaining %= scale;
+        }
+    }
+    out
+}
+
+pub fn format_size(bytes: u64) -> String {
+    format!("{}B", bytes)
+}
+
+/// The digits of one component. They are kept as text until the unit is
+/// known, so that a number too large to scale is reported as out of range
+/// rather than as something other than a duration or size.
+struct Amount<'a> {
+    whole: &'a str,
+    fraction: &'a str,
+}
+
+impl Amount<'_> {
+    /// Truncates whatever falls below the smallest unit.
+    fn scale(&self, scale: u128) -> Option<u128> {
+        let whole = self.whole.parse::<u128>().ok()?.checked_mul(scale)?;
+        let (fraction, denominator) = self.fraction_parts()?;
+        whole.checked_add(fraction.checked_mul(scale)? / denominator)
+    }
+
+    /// Whether the amount comes out to a whole number of the smallest unit.
+    fn is_exact(&self, scale: u128) -> bool {
+        match self.fraction_parts() {
+            Some((fraction, denominator)) => fraction
+                .checked_mul(scale)
+                .is_some_and(|fraction| fraction % denominator == 0),
+            None => false,
+        }
+    }
+
+    fn fraction_parts(&self) -> Option<(u128, u128)> {
+        let fraction = self.fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            return Some((0, 1));
+        }
+        let denominator = 10u128.checked_pow(fraction.len() as u32)?;
+        Some((fraction.parse().ok()?, denominator))
+    }
+}
+
+fn split_component(s: &str) -> Option<(Amount<'_>, &str, &str)> {
+    let number_end = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let unit_end = s[number_end..]
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .map_or(s.len(), |i| number_end + i);
+
+    let (whole, fraction) = match s[..number_end].split_once('.') {
+        Some((whole, fraction)) => (whole, fraction),
+        None => (&s[..number_end], ""),
+    };
+    if whole.is_empty() || fraction.contains('.') || s[..number_end].ends_with('.') {
+        return None;
+    }
+
+    Some((
+        Amount { whole, fraction },
+        &s[number_end..unit_end],
+        &s[unit_end..],
+    ))
+}
+
+fn lookup(units: &[(&str, u128)], unit: &str) -> Option<u128> {
+    units
+        .iter()
+        .find(|(name, _)| *name == unit)
+        .map(|(_, scale)| *scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod valid {
+        use super::*;
+
+        #[test]
+        fn test_parse_duration() {
+            let parse = |s| parse_duration(s).unwrap().unwrap();
+            assert_eq!(parse("30s"), Duration::from_secs(30));
+            assert_eq!(parse("5m"), Duration::from_secs(300));
+            assert_eq!(parse("1h30m"), Duration::from_secs(5400));
+            assert_eq!(parse("1.5h"), Duration::from_secs(5400));
+            assert_eq!(parse("250ms"), Duration::from_millis(250));
+            assert_eq!(parse("2d"), Duration::from_secs(172_800));
+            assert_eq!(parse("0s"), Duration::ZERO);
+            assert_eq!(parse("1d2h3m4s5ms"), Duration::new(93_784, 5_000_000));
+        }
+
+        #[test]
+        fn test_parse_size() {
+            let parse = |s| parse_size(s).unwrap().unwrap();
+            assert_eq!(parse("512KiB"), 512 * 1024);
+            assert_eq!(parse("10MB"), 10_000_000);
+            assert_eq!(parse("1.5KiB"), 1536);
+            assert_eq!(parse("64B"), 64);
+            assert_eq!(parse("16383PiB"), 16383 << 50);
+        }
+
+        #[test]
+        fn test_format_duration() {
+            assert_eq!(format_duration(&Duration::from_secs(5400)), "1h30m");
+            assert_eq!(format_duration(&Duration::from_millis(1500)), "1s500ms");
+            assert_eq!(format_duration(&Duration::ZERO), "0s");
+        }
+    }
+
+    mod invalid {
+        use super::*;
+
+        #[test]
+        fn test_invalid_durations() {
+            for input in ["", "30", "s", "30x", "1.h", "1..5s", "30s5", "-5s", "5 m"] {
+                assert_eq!(parse_duration(input), None, "{}", input);
+            }
+        }
+
+        #[test]
+        fn test_misordered_duration_units() {
+            for input in ["1h1h", "1ms5s", "30m1h", "0s0s"] {
+                assert_eq!(
+                    parse_duration(input),
+                    Some(Err(UnitError::MisorderedUnits)),
+                    "{}",
+                    input
+                );
+            }
+        }
+
+        #[test]
+        fn test_duration_out_of_range() {
+            for input in [
+                "99999999999999999999d",
+                "999999999999999999999999999999999999999s",
+            ] {
+                assert_eq!(
+                    parse_duration(input),
+                    Some(Err(UnitError::DurationOutOfRange)),
+                    "{}",
+                    input
+                );
+            }
+        }
+
+        #[test]
+        fn test_invalid_sizes() {
+            for input in ["10", "10mb", "10MB5", "1.5B", "10MBKiB", "1MB1KB"] {
+                assert_eq!(parse_size(input), None, "{}", input);
+            }
+        }
+
+        #[test]
+        fn test_size_out_of_range() {
+            for input in [
+                "99999999999PiB",
+                "18446744073709551616B",
+                "18446744073709551616PiB",
+            ] {
+                assert_eq!(
+                    parse_size(input),
+                    Some(Err(UnitError::SizeOutOfRange)),
+                    "{}",
+                    input
+                );
+            }
+        }
+    }
+}
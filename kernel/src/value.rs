@@ -1,6 +1,9 @@
+use crate::datetime::Datetime;
 use crate::shared::Map;
+use crate::units::{format_duration, format_size};
 
 use std::fmt::Display;
+use std::time::Duration;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Value {
@@ -8,9 +11,31 @@ pub enum Value {
     Bool(bool),
     Number(Number),
     String(String),
+    /// Datetimes, durations and sizes keep the text they were written as,
+    /// which is what string targets read.
+    Datetime(Datetime, String),
+    Duration(Duration, String),
+    Size(u64, String),
+    Array(Vec<Value>),
     Dict(Map<String, Value>),
 }
 
+impl Value {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "bool",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Datetime(dt, _) => dt.kind(),
+            Value::Duration(..) => "duration",
+            Value::Size(..) => "size",
+            Value::Array(_) => "array",
+            Value::Dict(_) => "dict",
+        }
+    }
+}
+
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -18,6 +43,13 @@ impl Display for Value {
             Value::Bool(b) => write!(f, "{}", b),
             Value::Number(n) => write!(f, "{}", n),
             Value::String(s) => write!(f, "{}", s),
+            Value::Datetime(dt, _) => write!(f, "{}", dt),
+            Value::Duration(d, _) => write!(f, "{}", format_duration(d)),
+            Value::Size(bytes, _) => write!(f, "{}", format_size(*bytes)),
+            Value::Array(items) => {
+                let items_str: Vec<String> = items.iter().map(|v| v.to_string()).collect();
+                write!(f, "[{}]", items_str.join(", "))
+            }
             Value::Dict(d) => {
                 let dict_str: Vec<String> =
                     d.iter().map(|(k, v)| format!("{}: {}", k, v)).collect();
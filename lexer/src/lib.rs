@@ -5,45 +5,102 @@ use crate::peekable_extend::PeekableExt;
 use kernel::{
     error::TokenError,
     source::{TokenResult, TryFromSource},
-    token::Token,
+    span::Span,
+    token::{SpannedToken, Token},
     tokenize::Tokenize,
 };
 
 pub use sources::{config::ConfigSource, schema::SchemaSource};
 
+use std::iter::Peekable;
+
 pub struct Lexer;
 
 impl Tokenize for Lexer {
-    fn tokenize<T>(source: T) -> Result<Vec<Token>, TokenError>
+    fn tokenize_all<T>(source: T) -> (Vec<SpannedToken>, Vec<TokenError>)
     where
-        T: TryFromSource + AsRef<str>,
+        T: TryFromSource,
     {
         let input = source.as_ref();
         let mut tokens = Vec::new();
-        let mut chars = input.chars().peekable();
-        while let Some(c) = chars.next() {
+        let mut errors = Vec::new();
+        let mut chars = input.char_indices().peekable();
+        let mut line = 1;
+        let mut line_start = 0;
+
+        while let Some((start, c)) = chars.next() {
             if source.is_skip_char(c) {
                 continue;
             }
 
             if source.is_invalid_chars(c) {
-                return Err(TokenError::InvalidChar(c));
+                errors.push(source.invalid_char(start, c));
+                Self::skip_line(&mut tokens, &mut chars);
+                continue;
             }
 
-            match source.classify_char(c) {
-                TokenResult::Single(token) => tokens.push(token),
+            let token = match source.classify_char(c) {
+                TokenResult::Single(token) => token,
                 TokenResult::NeedsBlock(start_char) => {
-                    let token = source.process_block_token(start_char, &mut chars)?;
-                    tokens.push(token);
+                    match source.process_block_token((start, start_char), &mut chars) {
+                        Ok(token) => token,
+                        Err(error) => {
+                            errors.push(error);
+                            Self::skip_line(&mut tokens, &mut chars);
+                            continue;
+                        }
+                    }
                 }
                 TokenResult::Comment => {
                     let _ = chars.read_until_delimiter(|c| c == '\n', |_| None);
                     continue;
                 }
+                TokenResult::Invalid => {
+                    errors.push(source.invalid_char(start, c));
+                    Self::skip_line(&mut tokens, &mut chars);
+                    continue;
+                }
+            };
+
+            let end = chars.peek().map_or(input.len(), |&(pos, _)| pos);
+            let column = input[line_start..start].chars().count() + 1;
+            let is_newline = token == Token::Newline;
+
+            tokens.push(SpannedToken::new(
+                token,
+                Span::new(start, end, line, column),
+            ));
+
+            if is_newline {
+                line += 1;
+                line_start = end;
+            } else if let Some(offset) = input[start..end].rfind('\n') {
+                // Multi-line strings span several lines within one token.
+                line += input[start..end].matches('\n').count();
+                line_start = start + offset + 1;
             }
         }
 
-        tokens.push(Token::Eof);
-        Ok(tokens)
+        let column = input[line_start..].chars().count() + 1;
+        tokens.push(SpannedToken::new(
+            Token::Eof,
+            Span::new(input.len(), input.len(), line, column),
+        ));
+        (tokens, errors)
+    }
+}
+
+impl Lexer {
+    fn skip_line<I>(tokens: &mut Vec<SpannedToken>, chars: &mut Peekable<I>)
+    where
+        I: Iterator<Item = (usize, char)>,
+    {
+        let line_start = tokens
+            .iter()
+            .rposition(|t| t.token == Token::Newline)
+            .map_or(0, |pos| pos + 1);
+        tokens.truncate(line_start);
+
+        let _ = chars.read_until_delimiter(|c| c == '\n', |_| None);
     }
 }
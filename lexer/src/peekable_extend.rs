@@ -1,11 +1,7 @@
 use std::iter::Peekable;
 
 pub trait PeekableExt<E> {
-    fn read_until_delimiter<F, G>(
-        &mut self,
-        predicate: F,
-        error_predicate: G,
-    ) -> Result<String, char>
+    fn read_until_delimiter<F, G>(&mut self, predicate: F, error_predicate: G) -> Result<String, E>
     where
         F: Fn(char) -> bool,
         G: Fn(char) -> Option<bool>;
@@ -14,21 +10,21 @@ pub trait PeekableExt<E> {
         &mut self,
         predicate: F,
         error_predicate: G,
-    ) -> Result<String, char>
+    ) -> Result<String, Option<E>>
     where
         F: Fn(char) -> bool,
         G: Fn(char) -> Option<bool>;
 }
 
-impl<I> PeekableExt<char> for Peekable<I>
+impl<I> PeekableExt<(usize, char)> for Peekable<I>
 where
-    I: Iterator<Item = char>,
+    I: Iterator<Item = (usize, char)>,
 {
     fn read_until_delimiter<F, G>(
         &mut self,
         predicate: F,
         error_predicate: G,
-    ) -> Result<String, char>
+    ) -> Result<String, (usize, char)>
     where
         F: Fn(char) -> bool,
         G: Fn(char) -> Option<bool>,
@@ -37,16 +33,16 @@ where
 
         loop {
             match self.peek() {
-                Some(&c) => {
+                Some(&(pos, c)) => {
                     if let Some(true) = error_predicate(c) {
-                        return Err(c);
+                        return Err((pos, c));
                     }
 
                     if predicate(c) {
                         return Ok(result);
                     }
 
-                    let actual_c = self
+                    let (_, actual_c) = self
                         .next()
                         .expect("Iterator inconsistency: peek() succeeded but next() failed");
                     result.push(actual_c);
@@ -62,7 +58,7 @@ where
         &mut self,
         predicate: F,
         error_predicate: G,
-    ) -> Result<String, char>
+    ) -> Result<String, Option<(usize, char)>>
     where
         F: Fn(char) -> bool,
         G: Fn(char) -> Option<bool>,
@@ -71,9 +67,9 @@ where
 
         loop {
             match self.peek() {
-                Some(&c) => {
+                Some(&(pos, c)) => {
                     if let Some(true) = error_predicate(c) {
-                        return Err(c);
+                        return Err(Some((pos, c)));
                     }
 
                     if predicate(c) {
@@ -81,13 +77,13 @@ where
                         return Ok(result);
                     }
 
-                    let actual_c = self
+                    let (_, actual_c) = self
                         .next()
                         .expect("Iterator inconsistency: peek() succeeded but next() failed");
                     result.push(actual_c);
                 }
                 None => {
-                    return Err('\0');
+                    return Err(None);
                 }
             }
         }
@@ -1,5 +1,6 @@
 use kernel::{
     error::TokenError,
+    options::ParseOptions,
     source::{TokenResult, TryFromSource},
     token::Token,
 };
@@ -8,9 +9,14 @@ use crate::peekable_extend::PeekableExt;
 
 use std::convert::AsRef;
 use std::iter::Peekable;
+use std::str::CharIndices;
+
+/// The longest escape after the backslash, `u{10FFFF}`.
+const MAX_ESCAPE_LEN: usize = 9;
 
 pub struct ConfigSource {
     pub input: String,
+    pub options: ParseOptions,
 }
 
 impl AsRef<str> for ConfigSource {
@@ -21,37 +27,257 @@ impl AsRef<str> for ConfigSource {
 
 impl ConfigSource {
     pub fn new(input: String) -> Self {
-        Self { input }
+        Self {
+            input,
+            options: ParseOptions::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
     }
 
     fn process_escape_sequences(&self, input: String) -> String {
-        let mut result = String::new();
-        let mut chars = input.chars().peekable();
-
-        while let Some(c) = chars.next() {
-            if c == '\\' {
-                match chars.next() {
-                    Some('n') => result.push('\n'),
-                    Some('t') => result.push('\t'),
-                    Some('r') => result.push('\r'),
-                    Some('\\') => result.push('\\'),
-                    Some('"') => result.push('"'),
-                    Some('\'') => result.push('\''),
-                    Some('0') => result.push('\0'),
-                    Some(other) => {
-                        result.push('\\');
-                        result.push(other);
+        let mut result = String::with_capacity(input.len());
+        let mut rest = input.as_str();
+
+        while let Some(pos) = rest.find('\\') {
+            result.push_str(&rest[..pos]);
+            rest = &rest[pos + 1..];
+            match Self::decode_escape(rest) {
+                Some((c, len)) => {
+                    result.push(c);
+                    rest = &rest[len..];
+                }
+                None => result.push('\\'),
+            }
+        }
+
+        result.push_str(rest);
+        result
+    }
+
+    /// Decodes the escape that follows a backslash, returning the character
+    /// and how many bytes of `rest` it used.
+    fn decode_escape(rest: &str) -> Option<(char, usize)> {
+        let c = match rest.chars().next()? {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '\\' => '\\',
+            '"' => '"',
+            '\'' => '\'',
+            '0' => '\0',
+            'u' => return Self::decode_unicode_escape(&rest[1..]).map(|(c, len)| (c, len + 1)),
+            _ => return None,
+        };
+        Some((c, 1))
+    }
+
+    /// Either `{1F600}` with one to six hex digits or exactly four, as in `00E9`.
+    fn decode_unicode_escape(rest: &str) -> Option<(char, usize)> {
+        let (digits, len) = match rest.strip_prefix('{') {
+            Some(braced) => {
+                let end = braced.find('}')?;
+                (&braced[..end], end + 2)
+            }
+            None => (rest.get(..4)?, 4),
+        };
+
+        let is_hex =
+            (1..=6).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_hexdigit());
+        if !is_hex {
+            return None;
+        }
+        let c = char::from_u32(u32::from_str_radix(digits, 16).ok()?)?;
+        Some((c, len))
+    }
+
+    /// Copies a backslash and the character after it into `content`, leaving
+    /// the decoding to `process_escape_sequences`. When escapes are strict, one
+    /// that would not decode is rejected here, while its position is known.
+    fn read_escape(
+        &self,
+        pos: usize,
+        chars: &mut Peekable<CharIndices>,
+        content: &mut String,
+    ) -> Result<(), TokenError> {
+        content.push('\\');
+        let Some(&(next_pos, next)) = chars.peek() else {
+            return Ok(());
+        };
+
+        if self.options.strict_escapes {
+            let rest: String = chars.clone().map(|(_, c)| c).take(MAX_ESCAPE_LEN).collect();
+            if Self::decode_escape(&rest).is_none() {
+                return Err(TokenError::InvalidEscape {
+                    span: self.span(pos, next_pos + next.len_utf8()),
+                });
+            }
+        }
+
+        // A control character is left for the caller, which ends the string on it.
+        if !next.is_control() {
+            content.push(next);
+            chars.next();
+        }
+        Ok(())
+    }
+
+    /// Reads the rest of a bare value or key part, including any `${key}`
+    /// references inside it.
+    fn read_ident(&self, chars: &mut Peekable<CharIndices>) -> Result<String, TokenError> {
+        let mut content = String::new();
+        loop {
+            let part = chars
+                .read_until_delimiter(
+                    |c| {
+                        matches!(
+                            c,
+                            ' ' | '.' | '=' | '[' | ']' | '{' | '}' | ',' | '\n' | '$'
+                        )
+                    },
+                    |c| Some(self.is_invalid_chars(c)),
+                )
+                .map_err(|(pos, c)| self.invalid_char(pos, c))?;
+            content.push_str(&part);
+
+            match chars.next_if(|&(_, c)| c == '$') {
+                Some((pos, _)) => content.push_str(&self.read_reference(pos, chars)?),
+                None => return Ok(content),
+            }
+        }
+    }
+
+    /// Reads a `${key}` reference, or an escaped `$${key}`, after its `$`,
+    /// keeping it as written; the parser resolves it once every key is known.
+    fn read_reference(
+        &self,
+        start: usize,
+        chars: &mut Peekable<CharIndices>,
+    ) -> Result<String, TokenError> {
+        let escape = if chars.next_if(|&(_, c)| c == '$').is_some() {
+            "$"
+        } else {
+            ""
+        };
+        if chars.next_if(|&(_, c)| c == '{').is_none() {
+            return Err(self.invalid_char(start, '$'));
+        }
+
+        let key = chars
+            .read_until_terminator(|c| c == '}', |c| Some(c.is_control()))
+            .map_err(|stopped_at| {
+                let end = stopped_at.map_or(self.input.len(), |(pos, _)| pos);
+                TokenError::UnterminatedReference {
+                    span: self.span(start, end),
+                }
+            })?;
+        Ok(format!("{}${{{}}}", escape, key))
+    }
+
+    /// Reads a double-quoted string after its opening quote, where `\"` does
+    /// not end the string.
+    fn read_escaped_string(
+        &self,
+        start: usize,
+        chars: &mut Peekable<CharIndices>,
+    ) -> Result<String, TokenError> {
+        let mut content = String::new();
+        loop {
+            let Some(&(pos, c)) = chars.peek() else {
+                return Err(self.unterminated_string(start, None));
+            };
+            if c.is_control() {
+                return Err(self.unterminated_string(start, Some((pos, c))));
+            }
+
+            chars.next();
+            match c {
+                '"' => return Ok(content),
+                '\\' => self.read_escape(pos, chars, &mut content)?,
+                _ => content.push(c),
+            }
+        }
+    }
+
+    /// Whether the opening quote is followed by two more, e.g. `"""`.
+    fn is_triple_quote(quote: char, chars: &Peekable<CharIndices>) -> bool {
+        let mut lookahead = chars.clone();
+        lookahead.next().is_some_and(|(_, c)| c == quote)
+            && lookahead.next().is_some_and(|(_, c)| c == quote)
+    }
+
+    /// Reads a triple-quoted string after its opening delimiter. Newlines and
+    /// tabs are kept; a newline right after the delimiter is dropped. Up to
+    /// two quotes directly before the closing delimiter belong to the content.
+    fn read_multiline(
+        &self,
+        start: usize,
+        quote: char,
+        chars: &mut Peekable<CharIndices>,
+    ) -> Result<String, TokenError> {
+        chars.next();
+        chars.next();
+        if chars.peek().is_some_and(|&(_, c)| c == '\n') {
+            chars.next();
+        }
+
+        let mut content = String::new();
+        loop {
+            let Some((pos, c)) = chars.next() else {
+                return Err(self.unterminated_string(start, None));
+            };
+
+            match c {
+                '\\' if quote == '"' => self.read_escape(pos, chars, &mut content)?,
+                _ if c == quote => {
+                    let mut run = 1;
+                    while run < 5 && chars.peek().is_some_and(|&(_, next)| next == quote) {
+                        chars.next();
+                        run += 1;
                     }
-                    None => {
-                        result.push('\\');
+                    if run >= 3 {
+                        content.extend(std::iter::repeat_n(quote, run - 3));
+                        return Ok(content.replace("\r\n", "\n"));
                     }
+                    content.extend(std::iter::repeat_n(quote, run));
                 }
-            } else {
-                result.push(c);
+                '\n' | '\r' | '\t' => content.push(c),
+                _ if c.is_control() => return Err(self.unterminated_string(start, Some((pos, c)))),
+                _ => content.push(c),
             }
         }
+    }
 
-        result
+    /// Removes the indentation shared by all non-blank lines. A last line
+    /// holding only the indentation of the closing delimiter is dropped.
+    fn strip_indent(content: &str) -> String {
+        let content = match content.rsplit_once('\n') {
+            Some((body, last)) if last.trim().is_empty() => format!("{}\n", body),
+            _ => content.to_string(),
+        };
+
+        let indent = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start_matches([' ', '\t']).len())
+            .min()
+            .unwrap_or(0);
+
+        content
+            .split('\n')
+            .map(|line| line.get(indent..).unwrap_or_else(|| line.trim_start()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn unterminated_string(&self, start: usize, stopped_at: Option<(usize, char)>) -> TokenError {
+        let end = stopped_at.map_or(self.input.len(), |(pos, _)| pos);
+        TokenError::UnterminatedString {
+            span: self.span(start, end),
+        }
     }
 }
 
@@ -60,45 +286,57 @@ impl TryFromSource for ConfigSource {
         match c {
             '=' => TokenResult::Single(Token::Separator),
             '.' => TokenResult::Single(Token::Dot),
+            '[' => TokenResult::Single(Token::LeftBracket),
+            ']' => TokenResult::Single(Token::RightBracket),
+            '{' => TokenResult::Single(Token::LeftBrace),
+            '}' => TokenResult::Single(Token::RightBrace),
+            ',' => TokenResult::Single(Token::Comma),
             '\n' => TokenResult::Single(Token::Newline),
 
             '"' => TokenResult::NeedsBlock(c),
             '\'' => TokenResult::NeedsBlock(c),
             '#' => TokenResult::Comment,
-            _ if c.is_ascii() || c == '_' => TokenResult::NeedsBlock(c),
-            _ => unreachable!("Unexpected character: {}", c),
+            _ if c.is_ascii() => TokenResult::NeedsBlock(c),
+            _ if self.allows_unicode_idents() && c.is_alphanumeric() => TokenResult::NeedsBlock(c),
+            _ => TokenResult::Invalid,
         }
     }
 
     fn process_block_token(
         &self,
-        start_char: char,
-        chars: &mut Peekable<std::str::Chars>,
+        (start, start_char): (usize, char),
+        chars: &mut Peekable<CharIndices>,
     ) -> Result<Token, TokenError> {
         match start_char {
+            '"' | '\'' if Self::is_triple_quote(start_char, chars) => {
+                let mut content = self.read_multiline(start, start_char, chars)?;
+                if self.options.strip_indent {
+                    content = Self::strip_indent(&content);
+                }
+                if start_char == '"' {
+                    content = self.process_escape_sequences(content);
+                }
+                Ok(Token::QuotedIdent(content))
+            }
             '"' => {
-                let content = chars
-                    .read_until_terminator(|c| c == '"', |c| Some(c.is_control()))
-                    .map_err(|_| TokenError::UnterminatedString)?;
-
+                let content = self.read_escaped_string(start, chars)?;
                 let escaped = self.process_escape_sequences(content);
                 Ok(Token::QuotedIdent(escaped))
             }
             '\'' => {
                 let content = chars
                     .read_until_terminator(|c| c == '\'', |c| Some(c.is_control()))
-                    .map_err(|_| TokenError::UnterminatedString)?;
+                    .map_err(|stopped_at| self.unterminated_string(start, stopped_at))?;
 
                 Ok(Token::QuotedIdent(content))
             }
+            '$' => {
+                let reference = self.read_reference(start, chars)?;
+                let rest = self.read_ident(chars)?;
+                Ok(Token::Ident(format!("{}{}", reference, rest)))
+            }
             _ => {
-                let content = chars
-                    .read_until_delimiter(
-                        |c| c == ' ' || c == '.' || c == '=' || c == '\n',
-                        |c| Some(self.is_invalid_chars(c)),
-                    )
-                    .map_err(TokenError::InvalidChar)?;
-
+                let content = self.read_ident(chars)?;
                 let with_start_char = format!("{}{}", start_char, content);
 
                 Ok(Token::Ident(with_start_char))
@@ -107,7 +345,11 @@ impl TryFromSource for ConfigSource {
     }
 
     fn additional_invalid_chars(&self) -> &[char] {
-        &[';']
+        &[';', '*']
+    }
+
+    fn allows_unicode_idents(&self) -> bool {
+        self.options.unicode_idents
     }
 }
 
@@ -129,6 +371,23 @@ mod tests {
                 TokenResult::Single(Token::Separator)
             );
             assert_eq!(source.classify_char('.'), TokenResult::Single(Token::Dot));
+            assert_eq!(
+                source.classify_char('['),
+                TokenResult::Single(Token::LeftBracket)
+            );
+            assert_eq!(
+                source.classify_char(']'),
+                TokenResult::Single(Token::RightBracket)
+            );
+            assert_eq!(
+                source.classify_char('{'),
+                TokenResult::Single(Token::LeftBrace)
+            );
+            assert_eq!(
+                source.classify_char('}'),
+                TokenResult::Single(Token::RightBrace)
+            );
+            assert_eq!(source.classify_char(','), TokenResult::Single(Token::Comma));
             assert_eq!(
                 source.classify_char('\n'),
                 TokenResult::Single(Token::Newline)
@@ -141,10 +400,14 @@ mod tests {
         }
 
         #[test]
-        #[should_panic]
         fn test_classify_char_ja() {
             let source = ConfigSource::new("".to_string());
-            source.classify_char('あ');
+            assert_eq!(source.classify_char('あ'), TokenResult::Invalid);
+
+            let source = ConfigSource::new("".to_string())
+                .with_options(ParseOptions::new().with_unicode_idents(true));
+            assert_eq!(source.classify_char('あ'), TokenResult::NeedsBlock('あ'));
+            assert_eq!(source.classify_char('、'), TokenResult::Invalid);
         }
     }
 
@@ -154,49 +417,201 @@ mod tests {
         #[test]
         fn test_process_block_token() {
             let source = ConfigSource::new("".to_string());
-            let mut chars = "\"Hello World\"".chars().peekable();
+            let mut chars = "\"Hello World\"".char_indices().peekable();
             chars.next();
 
-            let token = source.process_block_token('"', &mut chars).unwrap();
+            let token = source.process_block_token((0, '"'), &mut chars).unwrap();
             assert_eq!(token, Token::QuotedIdent("Hello World".to_string()));
 
-            let mut chars = r#""Hello\nWorld""#.chars().peekable();
+            let mut chars = r#""Hello\nWorld""#.char_indices().peekable();
             chars.next();
-            let token = source.process_block_token('"', &mut chars).unwrap();
+            let token = source.process_block_token((0, '"'), &mut chars).unwrap();
             assert_eq!(token, Token::QuotedIdent("Hello\nWorld".to_string()));
 
-            let mut chars = "'Hello World'".chars().peekable();
+            let mut chars = "'Hello World'".char_indices().peekable();
             chars.next();
-            let token = source.process_block_token('\'', &mut chars).unwrap();
+            let token = source.process_block_token((0, '\''), &mut chars).unwrap();
             assert_eq!(token, Token::QuotedIdent("Hello World".to_string()));
 
-            let mut chars = r#"'Hello\nWorld'"#.chars().peekable();
+            let mut chars = r#"'Hello\nWorld'"#.char_indices().peekable();
             chars.next();
-            let token = source.process_block_token('\'', &mut chars).unwrap();
+            let token = source.process_block_token((0, '\''), &mut chars).unwrap();
             assert_eq!(token, Token::QuotedIdent("Hello\\nWorld".to_string()));
 
-            let mut chars = "Identifier".chars().peekable();
+            let mut chars = "Identifier".char_indices().peekable();
+            chars.next();
+            let token = source.process_block_token((0, 'I'), &mut chars).unwrap();
+            assert_eq!(token, Token::Ident("Identifier".to_string()));
+
+            let mut chars = "Identifier with space".char_indices().peekable();
+            chars.next();
+            let token = source.process_block_token((0, 'I'), &mut chars).unwrap();
+            assert_eq!(token, Token::Ident("Identifier".to_string()));
+
+            let mut chars = "Identifier.with.dot".char_indices().peekable();
             chars.next();
-            let token = source.process_block_token('I', &mut chars).unwrap();
+            let token = source.process_block_token((0, 'I'), &mut chars).unwrap();
             assert_eq!(token, Token::Ident("Identifier".to_string()));
 
-            let mut chars = "Identifier with space".chars().peekable();
+            let mut chars = "Identifier=with=equals".char_indices().peekable();
             chars.next();
-            let token = source.process_block_token('I', &mut chars).unwrap();
+            let token = source.process_block_token((0, 'I'), &mut chars).unwrap();
             assert_eq!(token, Token::Ident("Identifier".to_string()));
 
-            let mut chars = "Identifier.with.dot".chars().peekable();
+            let mut chars = "Identifier, next".char_indices().peekable();
             chars.next();
-            let token = source.process_block_token('I', &mut chars).unwrap();
+            let token = source.process_block_token((0, 'I'), &mut chars).unwrap();
             assert_eq!(token, Token::Ident("Identifier".to_string()));
 
-            let mut chars = "Identifier=with=equals".chars().peekable();
+            let mut chars = "Identifier]".char_indices().peekable();
             chars.next();
-            let token = source.process_block_token('I', &mut chars).unwrap();
+            let token = source.process_block_token((0, 'I'), &mut chars).unwrap();
             assert_eq!(token, Token::Ident("Identifier".to_string()));
 
-            let mut chars = "# This is a comment".chars().peekable();
+            let mut chars = "# This is a comment".char_indices().peekable();
+            chars.next();
+        }
+
+        #[test]
+        fn test_process_escape_sequences() {
+            let source = ConfigSource::new("".to_string());
+
+            let mut chars = r#""say \"hi\" \u00E9 \u{1F600}\\""#.char_indices().peekable();
+            chars.next();
+            let token = source.process_block_token((0, '"'), &mut chars).unwrap();
+            assert_eq!(token, Token::QuotedIdent("say \"hi\" é 😀\\".to_string()));
+            assert_eq!(chars.next(), None);
+
+            let source = ConfigSource::new("".to_string());
+            let mut chars = r#""\q \u{zz} \uD800""#.char_indices().peekable();
+            chars.next();
+            let token = source.process_block_token((0, '"'), &mut chars).unwrap();
+            assert_eq!(token, Token::QuotedIdent(r"\q \u{zz} \uD800".to_string()));
+        }
+
+        #[test]
+        fn test_process_invalid_escape_sequences() {
+            for input in [
+                r#""a\qb""#,
+                r#""\u{}""#,
+                r#""\u{1234567}""#,
+                r#""\u{110000}""#,
+                r#""\uD800""#,
+                r#""\u12""#,
+                "\"\"\"\n\\x\n\"\"\"",
+            ] {
+                let source = ConfigSource::new(input.to_string())
+                    .with_options(ParseOptions::new().with_strict_escapes(true));
+                let mut chars = source.input.char_indices().peekable();
+                chars.next();
+                let result = source.process_block_token((0, '"'), &mut chars);
+                assert!(
+                    matches!(result, Err(TokenError::InvalidEscape { .. })),
+                    "{}",
+                    input
+                );
+            }
+        }
+
+        #[test]
+        fn test_process_reference_token() {
+            let source = ConfigSource::new("".to_string());
+            let cases = [
+                ("${primary.port}", "${primary.port}"),
+                ("${app.home}/logs, next", "${app.home}/logs"),
+                ("${a}-${b} = c", "${a}-${b}"),
+                ("v${major}.0", "v${major}"),
+                ("$${home}", "$${home}"),
+                ("$${a}-${b} = c", "$${a}-${b}"),
+            ];
+            for (input, expected) in cases {
+                let mut chars = input.char_indices().peekable();
+                let start = chars.next().unwrap();
+                let token = source.process_block_token(start, &mut chars).unwrap();
+                assert_eq!(token, Token::Ident(expected.to_string()), "{}", input);
+            }
+
+            for input in ["$home", "$$home"] {
+                let mut chars = input.char_indices().peekable();
+                let start = chars.next().unwrap();
+                let result = source.process_block_token(start, &mut chars);
+                assert!(
+                    matches!(result, Err(TokenError::InvalidChar { char: '$', .. })),
+                    "{}",
+                    input
+                );
+            }
+
+            let source = ConfigSource::new("${app.home\n".to_string());
+            let mut chars = source.input.char_indices().peekable();
+            let start = chars.next().unwrap();
+            let result = source.process_block_token(start, &mut chars);
+            assert!(matches!(
+                result,
+                Err(TokenError::UnterminatedReference { .. })
+            ));
+        }
+
+        #[test]
+        fn test_process_multiline_token() {
+            let source = ConfigSource::new("".to_string());
+
+            let mut chars = "\"\"\"\nline 1\n\\tline \"2\"\n\"\"\""
+                .char_indices()
+                .peekable();
+            chars.next();
+            let token = source.process_block_token((0, '"'), &mut chars).unwrap();
+            assert_eq!(
+                token,
+                Token::QuotedIdent("line 1\n\tline \"2\"\n".to_string())
+            );
+            assert_eq!(chars.next(), None);
+
+            let mut chars = r"'''C:\temp
+''quoted'''''"
+                .char_indices()
+                .peekable();
+            chars.next();
+            let token = source.process_block_token((0, '\''), &mut chars).unwrap();
+            assert_eq!(
+                token,
+                Token::QuotedIdent("C:\\temp\n''quoted''".to_string())
+            );
+
+            let mut chars = r#""""a \""" b""""#.char_indices().peekable();
+            chars.next();
+            let token = source.process_block_token((0, '"'), &mut chars).unwrap();
+            assert_eq!(token, Token::QuotedIdent("a \"\"\" b".to_string()));
+
+            let mut chars = "\"\"".char_indices().peekable();
+            chars.next();
+            let token = source.process_block_token((0, '"'), &mut chars).unwrap();
+            assert_eq!(token, Token::QuotedIdent("".to_string()));
+        }
+
+        #[test]
+        fn test_process_multiline_token_with_strip_indent() {
+            let source = ConfigSource::new("".to_string())
+                .with_options(ParseOptions::new().with_strip_indent(true));
+
+            let mut chars = "'''\n    SELECT *\n      FROM users\n\n    '''"
+                .char_indices()
+                .peekable();
+            chars.next();
+            let token = source.process_block_token((0, '\''), &mut chars).unwrap();
+            assert_eq!(
+                token,
+                Token::QuotedIdent("SELECT *\n  FROM users\n\n".to_string())
+            );
+        }
+
+        #[test]
+        fn test_process_unterminated_multiline_token() {
+            let source = ConfigSource::new("'''abc\n''".to_string());
+            let mut chars = source.input.char_indices().peekable();
             chars.next();
+            let result = source.process_block_token((0, '\''), &mut chars);
+            assert!(matches!(result, Err(TokenError::UnterminatedString { .. })));
         }
     }
 }
@@ -8,6 +8,7 @@ use crate::peekable_extend::PeekableExt;
 
 use std::convert::AsRef;
 use std::iter::Peekable;
+use std::str::CharIndices;
 
 pub struct SchemaSource {
     pub input: String,
@@ -23,6 +24,13 @@ impl SchemaSource {
     pub fn new(input: String) -> Self {
         Self { input }
     }
+
+    fn unterminated_string(&self, start: usize, stopped_at: Option<(usize, char)>) -> TokenError {
+        let end = stopped_at.map_or(self.input.len(), |(pos, _)| pos);
+        TokenError::UnterminatedString {
+            span: self.span(start, end),
+        }
+    }
 }
 
 impl TryFromSource for SchemaSource {
@@ -30,31 +38,45 @@ impl TryFromSource for SchemaSource {
         match c {
             ':' => TokenResult::Single(Token::Separator),
             '.' => TokenResult::Single(Token::Dot),
+            '[' => TokenResult::Single(Token::LeftBracket),
+            ']' => TokenResult::Single(Token::RightBracket),
+            '{' => TokenResult::Single(Token::LeftBrace),
+            '}' => TokenResult::Single(Token::RightBrace),
+            ',' => TokenResult::Single(Token::Comma),
             '\n' => TokenResult::Single(Token::Newline),
 
             '#' => TokenResult::Comment,
-            _ if c.is_ascii() || c == '_' => TokenResult::NeedsBlock(c),
-            _ => unreachable!("Unexpected character: {}", c),
+            _ if c.is_ascii() => TokenResult::NeedsBlock(c),
+            _ => TokenResult::Invalid,
         }
     }
 
     fn process_block_token(
         &self,
-        start_char: char,
-        chars: &mut Peekable<std::str::Chars>,
+        (start, start_char): (usize, char),
+        chars: &mut Peekable<CharIndices>,
     ) -> Result<Token, TokenError> {
+        // Quoted text is taken as written; it is only meaningful in keys and
+        // `include` paths, so the parser rejects it as a type.
+        if matches!(start_char, '"' | '\'') {
+            let content = chars
+                .read_until_terminator(|c| c == start_char, |c| Some(c.is_control()))
+                .map_err(|stopped_at| self.unterminated_string(start, stopped_at))?;
+            return Ok(Token::QuotedIdent(content));
+        }
+
         let content = chars
             .read_until_delimiter(
-                |c| c == ' ' || c == '.' || c == ':' || c == '\n',
+                |c| matches!(c, ' ' | '.' | ':' | '[' | ']' | '{' | '}' | ',' | '\n'),
                 |c| Some(self.is_invalid_chars(c)),
             )
-            .map_err(TokenError::InvalidChar)?;
+            .map_err(|(pos, c)| self.invalid_char(pos, c))?;
 
         let with_start_char = format!("{}{}", start_char, content);
         Ok(Token::Ident(with_start_char))
     }
 
     fn additional_invalid_chars(&self) -> &[char] {
-        &['=', '"', '\'']
+        &['=', '+', '$']
     }
 }
@@ -1,3 +1,4 @@
+mod number;
 mod parse_impl;
 mod schema_impl;
 mod syntax;
@@ -5,28 +6,135 @@ mod token_analyzer;
 mod validator;
 mod value_impl;
 
-use kernel::{error::ParseError, parse::Parse, schema::Schema, token::Token, value::Value};
+use kernel::{
+    env::Environment, error::ParseError, options::ParseOptions, parse::Parse, schema::Schema,
+    shared::Map, span::Span, token::SpannedToken, value::Value,
+};
 
-use validator::syntax::{ConfigRule, SchemaRule, SyntaxValidator};
+use parse_impl::DictLike;
+use syntax::{Syntax, SyntaxValue};
+use validator::syntax::{ConfigRule, SchemaRule, Scope, SyntaxValidator, ValidationRule};
 
 use token_analyzer::TokenAnalyzer;
 
+pub use syntax::display_key;
+
 pub struct Parser;
 
 impl Parse<Value> for Parser {
-    fn parse(tokens: Vec<Token>) -> Result<Value, ParseError> {
-        let syntax = TokenAnalyzer::analyze(tokens)?;
-        SyntaxValidator::validate::<ConfigRule>(&syntax)?;
-        Self::convert_to(syntax)
+    fn parse_all_with_env(
+        tokens: Vec<SpannedToken>,
+        options: ParseOptions,
+        env: &dyn Environment,
+    ) -> (Value, Vec<ParseError>) {
+        Self::parse_with_rule::<ConfigRule, Value>(tokens, options, env)
     }
 }
 
 pub struct SchemaParser;
 
 impl Parse<Schema> for Parser {
-    fn parse(tokens: Vec<Token>) -> Result<Schema, ParseError> {
-        let syntax = TokenAnalyzer::analyze(tokens)?;
-        SyntaxValidator::validate::<SchemaRule>(&syntax)?;
-        Self::convert_to(syntax)
+    fn parse_all_with_env(
+        tokens: Vec<SpannedToken>,
+        options: ParseOptions,
+        env: &dyn Environment,
+    ) -> (Schema, Vec<ParseError>) {
+        Self::parse_with_rule::<SchemaRule, Schema>(tokens, options, env)
+    }
+}
+
+/// A single `key = value` assignment, for layering onto a value that was
+/// parsed on its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub key_parts: Vec<String>,
+    pub value: Value,
+    /// Where the key was written.
+    pub span: Span,
+}
+
+impl Parser {
+    /// Parses config lines into one entry per key instead of a tree, with
+    /// inline tables flattened into their keys. The entries are a layer for
+    /// another value, so their list indices are only checked once they are
+    /// set onto it.
+    pub fn parse_entries(
+        tokens: Vec<SpannedToken>,
+        options: ParseOptions,
+        env: &dyn Environment,
+    ) -> Result<Vec<Entry>, ParseError> {
+        let (syntax, errors) =
+            Self::analyze_with_rule::<ConfigRule>(tokens, options, env, Scope::Layer);
+        if let Some(error) = errors.into_iter().next() {
+            return Err(error);
+        }
+
+        Ok(syntax
+            .patterns
+            .into_iter()
+            .map(|pattern| Entry {
+                key_parts: pattern.key_parts,
+                value: pattern.value.into(),
+                span: pattern.key_span,
+            })
+            .collect())
+    }
+
+    /// The key that the environment variable `name` overrides when the
+    /// prefix is `prefix`, e.g. `server.port` for `MYAPP__SERVER__PORT`.
+    pub fn env_override_key(name: &str, prefix: &str) -> Option<Vec<String>> {
+        ConfigRule::override_key(name, prefix)
+    }
+
+    fn parse_with_rule<R, T>(
+        tokens: Vec<SpannedToken>,
+        options: ParseOptions,
+        env: &dyn Environment,
+    ) -> (T, Vec<ParseError>)
+    where
+        R: ValidationRule,
+        T: DictLike + From<SyntaxValue>,
+    {
+        let (syntax, mut errors) =
+            Self::analyze_with_rule::<R>(tokens, options, env, Scope::Document);
+
+        let value = Self::convert_to(syntax).unwrap_or_else(|error| {
+            errors.push(error);
+            T::from_map(Map::new())
+        });
+
+        (value, errors)
+    }
+
+    fn analyze_with_rule<R: ValidationRule>(
+        tokens: Vec<SpannedToken>,
+        options: ParseOptions,
+        env: &dyn Environment,
+        scope: Scope,
+    ) -> (Syntax, Vec<ParseError>) {
+        let (mut syntax, token_errors) = TokenAnalyzer::analyze(tokens);
+        let mut syntax_errors = R::apply_options(&mut syntax, &options);
+        syntax_errors.extend(SyntaxValidator::validate::<R>(&syntax));
+
+        // Patterns rejected by the rule cannot be converted; they are already reported.
+        syntax.patterns.retain(|pattern| {
+            R::validate_key(&pattern.key_parts, pattern.key_span).is_ok()
+                && R::validate_value(&pattern.value, pattern.value_span).is_ok()
+        });
+        syntax_errors.extend(R::apply_profile(&mut syntax, &options));
+        // Profile keys only index into lists once they replace the base keys.
+        if scope == Scope::Document {
+            syntax_errors.extend(SyntaxValidator::validate_array_indices(&syntax));
+        }
+        syntax_errors.extend(R::apply_env_overrides(&mut syntax, &options, env, scope));
+        syntax_errors.extend(R::resolve_references(&mut syntax, env));
+
+        let errors = token_errors
+            .into_iter()
+            .map(ParseError::from)
+            .chain(syntax_errors.into_iter().map(ParseError::from))
+            .collect();
+
+        (syntax, errors)
     }
 }
@@ -0,0 +1,333 @@
+use kernel::error::ValueError;
+use kernel::span::Span;
+use kernel::value::Number;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Radix {
+    Hex,
+    Octal,
+    Binary,
+}
+
+impl Radix {
+    fn from_prefix(s: &str) -> Option<(Self, &str)> {
+        if let Some(digits) = s.strip_prefix("0x") {
+            Some((Radix::Hex, digits))
+        } else if let Some(digits) = s.strip_prefix("0o") {
+            Some((Radix::Octal, digits))
+        } else {
+            s.strip_prefix("0b").map(|digits| (Radix::Binary, digits))
+        }
+    }
+
+    fn base(&self) -> u32 {
+        match self {
+            Radix::Hex => 16,
+            Radix::Octal => 8,
+            Radix::Binary => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum NumberError {
+    InvalidDigits(Radix),
+    InvalidDecimal,
+    MisplacedSeparator,
+    InvalidExponent,
+    OutOfRange,
+}
+
+impl NumberError {
+    pub(crate) fn at(self, span: Span) -> ValueError {
+        match self {
+            NumberError::InvalidDigits(Radix::Hex) => ValueError::InvalidHexLiteral { span },
+            NumberError::InvalidDigits(Radix::Octal) => ValueError::InvalidOctalLiteral { span },
+            NumberError::InvalidDigits(Radix::Binary) => ValueError::InvalidBinaryLiteral { span },
+            NumberError::InvalidDecimal => ValueError::InvalidDecimalLiteral { span },
+            NumberError::MisplacedSeparator => ValueError::MisplacedDigitSeparator { span },
+            NumberError::InvalidExponent => ValueError::InvalidExponent { span },
+            NumberError::OutOfRange => ValueError::NumberOutOfRange { span },
+        }
+    }
+}
+
+/// Parses a plain value as a number. Returns `None` when the text is not
+/// shaped like a number at all, so the caller can treat it as a string.
+///
+/// Besides what `str::parse` accepts, this understands `0x`, `0o` and `0b`
+/// prefixes, `_` between digits, exponents such as `1e-3`, `inf` and `nan`.
+pub(crate) fn parse_number(s: &str) -> Option<Result<Number, NumberError>> {
+    let (negative, body) = match s.as_bytes().first() {
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        _ => (false, s),
+    };
+
+    match body {
+        "inf" if negative => return Some(Ok(Number::Float(f64::NEG_INFINITY))),
+        "inf" => return Some(Ok(Number::Float(f64::INFINITY))),
+        "nan" => return Some(Ok(Number::Float(f64::NAN))),
+        _ => {}
+    }
+
+    if let Some((radix, digits)) = Radix::from_prefix(body) {
+        return Some(parse_radix(negative, radix, digits));
+    }
+
+    let is_decimal = body.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+        && body.contains(['_', 'e', 'E'])
+        && body
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '_' | '.' | 'e' | 'E' | '+' | '-'));
+    if is_decimal {
+        return Some(parse_decimal(negative, body));
+    }
+
+    let is_integer = !body.is_empty() && body.chars().all(|c| c.is_ascii_digit());
+    parse_plain(s).map(Ok).or_else(|| {
+        // Digits alone are a number even when they do not fit.
+        is_integer.then_some(Err(NumberError::OutOfRange))
+    })
+}
+
+fn parse_plain(s: &str) -> Option<Number> {
+    if s.contains('.')
+        && !s.ends_with('.')
+        && let Ok(float) = s.parse::<f64>()
+    {
+        return Some(Number::Float(float));
+    }
+
+    if let Ok(uint) = s.parse::<u64>() {
+        Some(Number::UInt(uint))
+    } else if let Ok(int) = s.parse::<i64>() {
+        Some(Number::Int(int))
+    } else {
+        None
+    }
+}
+
+fn parse_radix(negative: bool, radix: Radix, digits: &str) -> Result<Number, NumberError> {
+    let is_digit = |c: char| c.is_digit(radix.base());
+    if digits.is_empty() || !digits.chars().all(|c| is_digit(c) || c == '_') {
+        return Err(NumberError::InvalidDigits(radix));
+    }
+
+    let digits = strip_separators(digits, NumberError::InvalidDigits(radix))?;
+    let value = u64::from_str_radix(&digits, radix.base()).map_err(|_| NumberError::OutOfRange)?;
+    to_number(negative, value)
+}
+
+fn parse_decimal(negative: bool, body: &str) -> Result<Number, NumberError> {
+    let (mantissa, exponent) = match body.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (body, None),
+    };
+    let (whole, fraction) = match mantissa.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (mantissa, None),
+    };
+
+    let whole = match (whole, fraction) {
+        ("", Some(_)) => String::new(),
+        _ => strip_separators(whole, NumberError::InvalidDecimal)?,
+    };
+    let fraction = fraction
+        .map(|fraction| strip_separators(fraction, NumberError::InvalidDecimal))
+        .transpose()?;
+    let exponent = exponent
+        .map(|exponent| {
+            let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            if digits.starts_with(|c: char| c.is_ascii_digit() || c == '_') {
+                strip_separators(digits, NumberError::InvalidExponent)
+                    .map(|_| exponent.replace('_', ""))
+            } else {
+                Err(NumberError::InvalidExponent)
+            }
+        })
+        .transpose()?;
+
+    if fraction.is_none() && exponent.is_none() {
+        let value = whole.parse::<u64>().map_err(|_| NumberError::OutOfRange)?;
+        return to_number(negative, value);
+    }
+
+    let mut literal = String::from(if negative { "-" } else { "" });
+    literal.push_str(&whole);
+    if let Some(fraction) = fraction {
+        literal.push('.');
+        literal.push_str(&fraction);
+    }
+    if let Some(exponent) = exponent {
+        literal.push('e');
+        literal.push_str(&exponent);
+    }
+
+    // A finite literal too large for `f64` parses as infinity.
+    match literal.parse::<f64>() {
+        Ok(float) if float.is_finite() => Ok(Number::Float(float)),
+        Ok(_) => Err(NumberError::OutOfRange),
+        Err(_) => Err(NumberError::InvalidExponent),
+    }
+}
+
+/// Removes `_` separators, which are only allowed between two digits.
+/// `invalid` is returned for any other character than a digit or a letter.
+fn strip_separators(digits: &str, invalid: NumberError) -> Result<String, NumberError> {
+    let misplaced = digits.is_empty()
+        || digits.starts_with('_')
+        || digits.ends_with('_')
+        || digits.contains("__");
+    if misplaced {
+        return Err(NumberError::MisplacedSeparator);
+    }
+
+    if !digits
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(invalid);
+    }
+
+    Ok(digits.replace('_', ""))
+}
+
+fn to_number(negative: bool, value: u64) -> Result<Number, NumberError> {
+    if !negative {
+        return Ok(Number::UInt(value));
+    }
+
+    i64::try_from(-i128::from(value))
+        .map(Number::Int)
+        .map_err(|_| NumberError::OutOfRange)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Option<Result<Number, NumberError>> {
+        parse_number(s)
+    }
+
+    mod valid {
+        use super::*;
+
+        #[test]
+        fn test_radix_literals() {
+            assert_eq!(parse("0xFF"), Some(Ok(Number::UInt(255))));
+            assert_eq!(parse("0o755"), Some(Ok(Number::UInt(0o755))));
+            assert_eq!(parse("0b1010"), Some(Ok(Number::UInt(10))));
+            assert_eq!(parse("0xdead_beef"), Some(Ok(Number::UInt(0xdead_beef))));
+            assert_eq!(parse("-0x10"), Some(Ok(Number::Int(-16))));
+        }
+
+        #[test]
+        fn test_separators_and_exponents() {
+            assert_eq!(parse("1_000_000"), Some(Ok(Number::UInt(1_000_000))));
+            assert_eq!(parse("-1_000"), Some(Ok(Number::Int(-1000))));
+            assert_eq!(parse("1e-3"), Some(Ok(Number::Float(1e-3))));
+            assert_eq!(parse("2.5E+2"), Some(Ok(Number::Float(250.0))));
+            assert_eq!(parse("1_000.000_1"), Some(Ok(Number::Float(1000.0001))));
+        }
+
+        #[test]
+        fn test_special_floats() {
+            assert_eq!(parse("inf"), Some(Ok(Number::Float(f64::INFINITY))));
+            assert_eq!(parse("-inf"), Some(Ok(Number::Float(f64::NEG_INFINITY))));
+            assert!(matches!(parse("nan"), Some(Ok(Number::Float(f))) if f.is_nan()));
+        }
+
+        #[test]
+        fn test_not_numbers() {
+            assert_eq!(parse("hello"), None);
+            assert_eq!(parse("1.2.3"), None);
+            assert_eq!(parse("123."), None);
+            assert_eq!(parse("12-34"), None);
+            assert_eq!(parse("_100"), None);
+        }
+    }
+
+    mod invalid {
+        use super::*;
+
+        #[test]
+        fn test_invalid_radix_digits() {
+            assert_eq!(
+                parse("0xZZ"),
+                Some(Err(NumberError::InvalidDigits(Radix::Hex)))
+            );
+            assert_eq!(
+                parse("0o758"),
+                Some(Err(NumberError::InvalidDigits(Radix::Octal)))
+            );
+            assert_eq!(
+                parse("0b102"),
+                Some(Err(NumberError::InvalidDigits(Radix::Binary)))
+            );
+            assert_eq!(
+                parse("0x"),
+                Some(Err(NumberError::InvalidDigits(Radix::Hex)))
+            );
+        }
+
+        #[test]
+        fn test_misplaced_separators() {
+            for input in ["100_", "1__000", "0x_FF", "1_.5", "1._5", "1e_5"] {
+                assert_eq!(
+                    parse(input),
+                    Some(Err(NumberError::MisplacedSeparator)),
+                    "{}",
+                    input
+                );
+            }
+        }
+
+        #[test]
+        fn test_invalid_exponents() {
+            for input in ["1e", "1e+", "1e5e5", "1e-+5", "1.5e3.0"] {
+                assert_eq!(
+                    parse(input),
+                    Some(Err(NumberError::InvalidExponent)),
+                    "{}",
+                    input
+                );
+            }
+        }
+
+        #[test]
+        fn test_invalid_decimals() {
+            for input in ["1_000.5.5", "1_0-5", "1_000+5", "1.2_3+4e5"] {
+                assert_eq!(
+                    parse(input),
+                    Some(Err(NumberError::InvalidDecimal)),
+                    "{}",
+                    input
+                );
+            }
+        }
+
+        #[test]
+        fn test_out_of_range() {
+            assert_eq!(
+                parse("0x1_0000_0000_0000_0000"),
+                Some(Err(NumberError::OutOfRange))
+            );
+            assert_eq!(
+                parse("-0xFFFF_FFFF_FFFF_FFFF"),
+                Some(Err(NumberError::OutOfRange))
+            );
+            assert_eq!(
+                parse("99999999999999999999"),
+                Some(Err(NumberError::OutOfRange))
+            );
+            assert_eq!(
+                parse("-9223372036854775809"),
+                Some(Err(NumberError::OutOfRange))
+            );
+            assert_eq!(parse("1.5e400"), Some(Err(NumberError::OutOfRange)));
+            assert_eq!(parse("-1e309"), Some(Err(NumberError::OutOfRange)));
+        }
+    }
+}
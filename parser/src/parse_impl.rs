@@ -8,7 +8,21 @@ pub trait DictLike {
     fn from_map(map: Map<String, Self>) -> Self
     where
         Self: Sized;
-    fn to_map_mut(&mut self) -> Option<&mut Map<String, Self>>
+
+    /// Returns the list index a key segment stands for, if any.
+    fn index_of(segment: &str) -> Option<usize>;
+
+    /// Creates an empty container whose first key segment is `segment`.
+    fn container_for(segment: &str) -> Self
+    where
+        Self: Sized;
+
+    fn child_or_insert_with<F>(&mut self, segment: &str, default: F) -> Option<&mut Self>
+    where
+        Self: Sized,
+        F: FnOnce() -> Self;
+
+    fn insert_child(&mut self, segment: &str, value: Self)
     where
         Self: Sized;
 }
@@ -18,12 +32,57 @@ impl DictLike for Value {
         Value::Dict(map)
     }
 
-    fn to_map_mut(&mut self) -> Option<&mut Map<String, Self>> {
-        match self {
-            Value::Dict(map) => Some(map),
+    fn index_of(segment: &str) -> Option<usize> {
+        if segment.chars().all(|c| c.is_ascii_digit()) {
+            segment.parse().ok()
+        } else {
+            None
+        }
+    }
+
+    fn container_for(segment: &str) -> Self {
+        match Self::index_of(segment) {
+            Some(_) => Value::Array(Vec::new()),
+            None => Value::Dict(Map::new()),
+        }
+    }
+
+    fn child_or_insert_with<F>(&mut self, segment: &str, default: F) -> Option<&mut Self>
+    where
+        F: FnOnce() -> Self,
+    {
+        match (self, Self::index_of(segment)) {
+            (Value::Dict(map), None) => {
+                Some(map.entry(segment.to_string()).or_insert_with(default))
+            }
+            (Value::Array(items), Some(index)) => {
+                // Indices may appear out of order; unset slots hold `Null` until filled.
+                if items.len() <= index {
+                    items.resize(index + 1, Value::Null);
+                }
+                if items[index] == Value::Null {
+                    items[index] = default();
+                }
+                Some(&mut items[index])
+            }
             _ => None,
         }
     }
+
+    fn insert_child(&mut self, segment: &str, value: Self) {
+        match (self, Self::index_of(segment)) {
+            (Value::Dict(map), None) => {
+                map.insert(segment.to_string(), value);
+            }
+            (Value::Array(items), Some(index)) => {
+                if items.len() <= index {
+                    items.resize(index + 1, Value::Null);
+                }
+                items[index] = value;
+            }
+            _ => {}
+        }
+    }
 }
 
 impl DictLike for Schema {
@@ -31,12 +90,45 @@ impl DictLike for Schema {
         Schema::Dict(map)
     }
 
-    fn to_map_mut(&mut self) -> Option<&mut Map<String, Self>> {
-        match self {
-            Schema::Dict(map) => Some(map),
+    fn index_of(segment: &str) -> Option<usize> {
+        (segment == "*").then_some(0)
+    }
+
+    fn container_for(segment: &str) -> Self {
+        match Self::index_of(segment) {
+            Some(_) => Schema::Array(Box::new(Schema::Dict(Map::new()))),
+            None => Schema::Dict(Map::new()),
+        }
+    }
+
+    fn child_or_insert_with<F>(&mut self, segment: &str, default: F) -> Option<&mut Self>
+    where
+        F: FnOnce() -> Self,
+    {
+        match (self, Self::index_of(segment)) {
+            (Schema::Dict(map), None) => {
+                Some(map.entry(segment.to_string()).or_insert_with(default))
+            }
+            (Schema::Array(inner), Some(_)) => {
+                // `container_for` leaves an empty dict as a placeholder element type.
+                if matches!(inner.as_ref(), Schema::Dict(map) if map.is_empty()) {
+                    **inner = default();
+                }
+                Some(inner)
+            }
             _ => None,
         }
     }
+
+    fn insert_child(&mut self, segment: &str, value: Self) {
+        match (self, Self::index_of(segment)) {
+            (Schema::Dict(map), None) => {
+                map.insert(segment.to_string(), value);
+            }
+            (Schema::Array(inner), Some(_)) => **inner = value,
+            _ => {}
+        }
+    }
 }
 
 impl Parser {
@@ -44,17 +136,17 @@ impl Parser {
     where
         T: DictLike + From<SyntaxValue>,
     {
-        let mut result = Map::new();
+        let mut result = T::from_map(Map::new());
 
         for pattern in syntax.patterns {
             let value: T = pattern.value.into();
             Self::insert_nested_path(&mut result, &pattern.key_parts, value);
         }
 
-        Ok(T::from_map(result))
+        Ok(result)
     }
 
-    fn insert_nested_path<T>(target: &mut Map<String, T>, path: &[String], value: T)
+    fn insert_nested_path<T>(target: &mut T, path: &[String], value: T)
     where
         T: DictLike,
     {
@@ -62,20 +154,18 @@ impl Parser {
             .split_first()
             .unwrap_or_else(|| unreachable!("path should not be empty"));
 
-        if rest.is_empty() {
-            target.insert(first.clone(), value);
+        let Some(next) = rest.first() else {
+            target.insert_child(first, value);
             return;
-        }
-
-        let nested = target
-            .entry(first.clone())
-            .or_insert_with(|| T::from_map(Map::new()));
+        };
 
-        let Some(nested_map) = nested.to_map_mut() else {
-            unreachable!("Should always be Dict")
+        // `None` here means a key path conflict, which the syntax validator
+        // has already reported; the first value wins.
+        let Some(nested) = target.child_or_insert_with(first, || T::container_for(next)) else {
+            return;
         };
 
-        Self::insert_nested_path(nested_map, rest, value);
+        Self::insert_nested_path(nested, rest, value);
     }
 }
 
@@ -83,6 +173,17 @@ impl Parser {
 mod tests {
     use super::*;
     use crate::syntax::{Pattern, Syntax, SyntaxValue};
+    use kernel::span::Span;
+    use kernel::value::Number;
+
+    fn pattern(key: &str, value: SyntaxValue) -> Pattern {
+        Pattern {
+            key_parts: key.split('.').map(String::from).collect(),
+            value,
+            key_span: Span::default(),
+            value_span: Span::default(),
+        }
+    }
 
     mod value {
         use super::*;
@@ -94,10 +195,14 @@ mod tests {
                     Pattern {
                         key_parts: vec!["a".into()],
                         value: SyntaxValue::Quoted("value1".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                     Pattern {
                         key_parts: vec!["b".into()],
                         value: SyntaxValue::Quoted("value2".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                 ],
             };
@@ -118,14 +223,20 @@ mod tests {
                     Pattern {
                         key_parts: vec!["a".into(), "b".into()],
                         value: SyntaxValue::Quoted("value1".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                     Pattern {
                         key_parts: vec!["a".into(), "c".into()],
                         value: SyntaxValue::Quoted("value2".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                     Pattern {
                         key_parts: vec!["d".into()],
                         value: SyntaxValue::Quoted("value3".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                 ],
             };
@@ -152,14 +263,20 @@ mod tests {
                     Pattern {
                         key_parts: vec!["a".into(), "b".into(), "c".into()],
                         value: SyntaxValue::Quoted("value1".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                     Pattern {
                         key_parts: vec!["a".into(), "b".into(), "d".into()],
                         value: SyntaxValue::Quoted("value2".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                     Pattern {
                         key_parts: vec!["e".into()],
                         value: SyntaxValue::Quoted("value3".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                 ],
             };
@@ -181,6 +298,41 @@ mod tests {
 
             assert_eq!(result, Value::Dict(expected));
         }
+
+        #[test]
+        fn test_indexed_syntax() {
+            let syntax = Syntax {
+                patterns: vec![
+                    pattern("servers.1.host", SyntaxValue::Quoted("b".into())),
+                    pattern("servers.0.host", SyntaxValue::Quoted("a".into())),
+                    pattern("servers.0.port", SyntaxValue::Plain("80".into())),
+                    pattern("tags.0", SyntaxValue::Quoted("x".into())),
+                ],
+            };
+
+            let result: Value = Parser::convert_to(syntax).unwrap();
+            let expected = Map::from([
+                (
+                    "servers".to_string(),
+                    Value::Array(vec![
+                        Value::Dict(Map::from([
+                            ("host".to_string(), Value::String("a".to_string())),
+                            ("port".to_string(), Value::Number(Number::UInt(80))),
+                        ])),
+                        Value::Dict(Map::from([(
+                            "host".to_string(),
+                            Value::String("b".to_string()),
+                        )])),
+                    ]),
+                ),
+                (
+                    "tags".to_string(),
+                    Value::Array(vec![Value::String("x".to_string())]),
+                ),
+            ]);
+
+            assert_eq!(result, Value::Dict(expected));
+        }
     }
 
     mod schema {
@@ -193,10 +345,14 @@ mod tests {
                     Pattern {
                         key_parts: vec!["a".into()],
                         value: SyntaxValue::Plain("string".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                     Pattern {
                         key_parts: vec!["b".into()],
                         value: SyntaxValue::Plain("string".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                 ],
             };
@@ -217,14 +373,20 @@ mod tests {
                     Pattern {
                         key_parts: vec!["a".into(), "b".into()],
                         value: SyntaxValue::Plain("string".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                     Pattern {
                         key_parts: vec!["a".into(), "c".into()],
                         value: SyntaxValue::Plain("string".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                     Pattern {
                         key_parts: vec!["d".into()],
                         value: SyntaxValue::Plain("string".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                 ],
             };
@@ -243,5 +405,33 @@ mod tests {
 
             assert_eq!(result, Schema::Dict(expected));
         }
+
+        #[test]
+        fn test_wildcard_syntax() {
+            let syntax = Syntax {
+                patterns: vec![
+                    pattern("servers.*.host", SyntaxValue::Plain("string".into())),
+                    pattern("servers.*.port", SyntaxValue::Plain("integer".into())),
+                    pattern("matrix.*.*", SyntaxValue::Plain("float".into())),
+                ],
+            };
+
+            let result: Schema = Parser::convert_to(syntax).unwrap();
+            let expected = Map::from([
+                (
+                    "servers".to_string(),
+                    Schema::Array(Box::new(Schema::Dict(Map::from([
+                        ("host".to_string(), Schema::String),
+                        ("port".to_string(), Schema::Integer),
+                    ])))),
+                ),
+                (
+                    "matrix".to_string(),
+                    Schema::Array(Box::new(Schema::Array(Box::new(Schema::Float)))),
+                ),
+            ]);
+
+            assert_eq!(result, Schema::Dict(expected));
+        }
     }
 }
@@ -17,6 +17,11 @@ impl SchemaExt for Schema {
                 "unsigned_integer" => Some(Schema::UnsignedInteger),
                 "float" => Some(Schema::Float),
                 "string" => Some(Schema::String),
+                "date" => Some(Schema::Date),
+                "time" => Some(Schema::Time),
+                "datetime" => Some(Schema::DateTime),
+                "duration" => Some(Schema::Duration),
+                "size" => Some(Schema::Size),
                 _ => None,
             }
         }
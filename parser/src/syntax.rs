@@ -1,11 +1,17 @@
+use super::Parser;
 use super::schema_impl::SchemaExt;
 use super::value_impl::ValueExt;
-use kernel::{schema::Schema, value::Value};
+use kernel::{error::SyntaxValidationError, schema::Schema, shared::Map, span::Span, value::Value};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SyntaxValue {
     Plain(String),
     Quoted(String),
+    Array(Vec<SyntaxValue>),
+    Table(Syntax),
+    /// A type marked optional with `?` that the lexer cannot keep in a
+    /// plain value, such as `[integer]?`.
+    Optional(Box<SyntaxValue>),
 }
 
 impl From<SyntaxValue> for Value {
@@ -13,6 +19,13 @@ impl From<SyntaxValue> for Value {
         match syntax_value {
             SyntaxValue::Plain(s) => Value::from_plain_string(s),
             SyntaxValue::Quoted(s) => Value::String(s),
+            SyntaxValue::Array(items) => Value::Array(items.into_iter().map(Value::from).collect()),
+            SyntaxValue::Table(syntax) => {
+                Parser::convert_to(syntax).unwrap_or_else(|_| Value::Dict(Map::new()))
+            }
+            SyntaxValue::Optional(_) => {
+                unreachable!("Optional types should not be converted to Value")
+            }
         }
     }
 }
@@ -25,19 +38,115 @@ impl From<SyntaxValue> for Schema {
             SyntaxValue::Quoted(_) => {
                 unreachable!("Quoted values should not be converted to Schema directly")
             }
+            SyntaxValue::Array(mut items) => match (items.pop(), items.is_empty()) {
+                (Some(item), true) => Schema::Array(Box::new(item.into())),
+                _ => unreachable!("Array types should have exactly one element type"),
+            },
+            SyntaxValue::Table(syntax) => {
+                Parser::convert_to(syntax).unwrap_or_else(|_| Schema::Dict(Map::new()))
+            }
+            SyntaxValue::Optional(inner) => Schema::Optional(Box::new((*inner).into())),
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Syntax {
     pub patterns: Vec<Pattern>,
 }
 
-#[derive(Debug, PartialEq)]
+impl Syntax {
+    /// Puts `pattern` in place of the value of its key. A table replaces the
+    /// keys below it one by one, and a key inside an inline table replaces
+    /// the key in that table. A value cannot replace a table or a table a
+    /// value; the clash is reported at the key that is already there.
+    pub(crate) fn replace(&mut self, pattern: Pattern) -> Result<(), SyntaxValidationError> {
+        let key = &pattern.key_parts;
+        let conflict = |key: &[String], span| SyntaxValidationError::KeyPathConflict {
+            key: display_key(key),
+            span,
+        };
+
+        if let Some(existing) = self.patterns.iter_mut().find(|existing| {
+            key.len() > existing.key_parts.len() && key.starts_with(&existing.key_parts)
+        }) {
+            let SyntaxValue::Table(table) = &mut existing.value else {
+                return Err(conflict(&existing.key_parts, existing.key_span));
+            };
+            let depth = existing.key_parts.len();
+            let table_key = display_key(&existing.key_parts);
+            return table
+                .replace(Pattern {
+                    key_parts: key[depth..].to_vec(),
+                    ..pattern
+                })
+                .map_err(|error| match error {
+                    SyntaxValidationError::KeyPathConflict { key, span } => {
+                        SyntaxValidationError::KeyPathConflict {
+                            key: format!("{}.{}", table_key, key),
+                            span,
+                        }
+                    }
+                    error => error,
+                });
+        }
+
+        let existing = self
+            .patterns
+            .iter()
+            .find(|existing| existing.key_parts.starts_with(key))
+            .map(|existing| {
+                let holds_table = existing.key_parts.len() > key.len()
+                    || matches!(existing.value, SyntaxValue::Table(_));
+                (holds_table, existing.key_span)
+            });
+        match (existing, pattern.value) {
+            (Some((true, _)), SyntaxValue::Table(table)) => {
+                table.patterns.into_iter().try_for_each(|inner| {
+                    self.replace(Pattern {
+                        key_parts: [key.as_slice(), &inner.key_parts].concat(),
+                        ..inner
+                    })
+                })
+            }
+            (Some((true, span)), _) | (Some((false, span)), SyntaxValue::Table(_)) => {
+                Err(conflict(key, span))
+            }
+            (_, value) => {
+                self.patterns.retain(|existing| &existing.key_parts != key);
+                self.patterns.push(Pattern { value, ..pattern });
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Pattern {
     pub key_parts: Vec<String>,
     pub value: SyntaxValue,
+    pub key_span: Span,
+    pub value_span: Span,
+}
+
+/// Joins key parts for error messages, quoting any part that could not be
+/// written bare, so `routes."/api/v1"` is not shown as `routes./api/v1`.
+pub fn display_key(key_parts: &[String]) -> String {
+    key_parts
+        .iter()
+        .map(|part| {
+            let is_bare = !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '*'));
+            match (is_bare, part.contains('"')) {
+                (true, _) => part.clone(),
+                (false, false) => format!("\"{}\"", part),
+                (false, true) => format!("'{}'", part),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".")
 }
 
 #[cfg(test)]
@@ -61,6 +170,21 @@ mod tests {
 
         let result: Value = SyntaxValue::Quoted("quoted string".into()).into();
         assert_eq!(result, Value::String("quoted string".into()));
+
+        let result: Value = SyntaxValue::Array(vec![
+            SyntaxValue::Plain("a".into()),
+            SyntaxValue::Quoted("b".into()),
+            SyntaxValue::Plain("1".into()),
+        ])
+        .into();
+        assert_eq!(
+            result,
+            Value::Array(vec![
+                Value::String("a".into()),
+                Value::String("b".into()),
+                Value::Number(Number::UInt(1)),
+            ])
+        );
     }
 
     #[test]
@@ -70,6 +194,19 @@ mod tests {
 
         let result: Schema = SyntaxValue::Plain("integer?".into()).into();
         assert_eq!(result, Schema::Optional(Box::new(Schema::Integer)));
+
+        let result: Schema = SyntaxValue::Array(vec![SyntaxValue::Plain("string".into())]).into();
+        assert_eq!(result, Schema::Array(Box::new(Schema::String)));
+
+        let result: Schema =
+            SyntaxValue::Optional(Box::new(SyntaxValue::Array(vec![SyntaxValue::Plain(
+                "integer".into(),
+            )])))
+            .into();
+        assert_eq!(
+            result,
+            Schema::Optional(Box::new(Schema::Array(Box::new(Schema::Integer))))
+        );
     }
 
     #[test]
@@ -83,4 +220,76 @@ mod tests {
     fn test_convert_base_schema_type_invalid() {
         let _result: Schema = SyntaxValue::Plain("invalid_type".into()).into();
     }
+
+    #[test]
+    fn test_display_key() {
+        let key = |parts: &[&str]| parts.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            display_key(&key(&["servers", "0", "host"])),
+            "servers.0.host"
+        );
+        assert_eq!(
+            display_key(&key(&["routes", "/api/v1", "timeout"])),
+            "routes.\"/api/v1\".timeout"
+        );
+        assert_eq!(display_key(&key(&["say", "\"hi\""])), "say.'\"hi\"'");
+    }
+
+    fn pattern(key: &str, value: SyntaxValue) -> Pattern {
+        Pattern {
+            key_parts: key.split('.').map(String::from).collect(),
+            value,
+            key_span: Span::default(),
+            value_span: Span::default(),
+        }
+    }
+
+    fn plain(value: &str) -> SyntaxValue {
+        SyntaxValue::Plain(value.into())
+    }
+
+    #[test]
+    fn test_replace() {
+        let table = |patterns| SyntaxValue::Table(Syntax { patterns });
+        let mut syntax = Syntax {
+            patterns: vec![
+                pattern("server.port", plain("8080")),
+                pattern("db", table(vec![pattern("host", plain("a"))])),
+            ],
+        };
+
+        syntax
+            .replace(pattern("server.port", plain("443")))
+            .unwrap();
+        syntax.replace(pattern("db.host", plain("b"))).unwrap();
+        syntax
+            .replace(pattern("server", table(vec![pattern("host", plain("c"))])))
+            .unwrap();
+
+        assert_eq!(
+            syntax.patterns,
+            vec![
+                pattern("db", table(vec![pattern("host", plain("b"))])),
+                pattern("server.port", plain("443")),
+                pattern("server.host", plain("c")),
+            ]
+        );
+
+        let error = syntax.replace(pattern("server", plain("off"))).unwrap_err();
+        assert!(
+            matches!(error, SyntaxValidationError::KeyPathConflict { key, .. } if key == "server")
+        );
+        let error = syntax
+            .replace(pattern("db.host.name", plain("d")))
+            .unwrap_err();
+        assert!(
+            matches!(error, SyntaxValidationError::KeyPathConflict { key, .. } if key == "db.host")
+        );
+        let error = syntax
+            .replace(pattern("server.port", table(Vec::new())))
+            .unwrap_err();
+        assert!(
+            matches!(error, SyntaxValidationError::KeyPathConflict { key, .. } if key == "server.port")
+        );
+    }
 }
@@ -1,83 +1,170 @@
 use crate::syntax::{Pattern, Syntax, SyntaxValue};
 use kernel::error::TokenValidationError;
-use kernel::token::Token;
+use kernel::span::Span;
+use kernel::token::{SpannedToken, Token};
 
 use crate::validator::token::TokenValidator;
 
 pub struct TokenAnalyzer;
 
 impl TokenAnalyzer {
-    pub fn analyze(tokens: Vec<Token>) -> Result<Syntax, TokenValidationError> {
-        let token_lines: Vec<Vec<Token>> = tokens
-            .split(|token| matches!(token, Token::Newline | Token::Eof))
-            .map(|line| line.to_vec())
+    pub fn analyze(tokens: Vec<SpannedToken>) -> (Syntax, Vec<TokenValidationError>) {
+        let token_lines: Vec<(Vec<SpannedToken>, bool)> = tokens
+            .split_inclusive(|t| matches!(t.token, Token::Newline | Token::Eof))
+            .map(|line| match line.split_last() {
+                Some((last, rest)) if matches!(last.token, Token::Newline | Token::Eof) => {
+                    (rest.to_vec(), last.token == Token::Eof)
+                }
+                _ => (line.to_vec(), false),
+            })
             .collect();
 
         let mut patterns = Vec::new();
+        let mut errors = Vec::new();
+        // `None` after a header that failed validation: the lines below it are
+        // still checked, but their patterns are dropped.
+        let mut section = Some(Vec::new());
+        let mut after_eof = false;
+
+        for (line, ends_with_eof) in token_lines {
+            // An included file ends with its own `Eof`, which closes the
+            // sections it opened.
+            if std::mem::replace(&mut after_eof, ends_with_eof) {
+                section = Some(Vec::new());
+            }
 
-        for line in token_lines {
             if line.is_empty() {
                 continue;
             }
 
             if line
                 .iter()
-                .any(|token| matches!(token, Token::Newline | Token::Eof))
+                .any(|t| matches!(t.token, Token::Newline | Token::Eof))
             {
                 unreachable!("Lines containing Newline/Eof tokens should not be processed");
             };
 
-            TokenValidator::validate_line_structure(&line)?;
-            let (key_tokens, value_tokens) = Self::split_key_value(&line)?;
-            TokenValidator::validate_key(&key_tokens)?;
-            TokenValidator::validate_value(&value_tokens)?;
-            let key = Self::build_key(&key_tokens);
-            let value = Self::build_value(&value_tokens)?;
-            patterns.push(Pattern {
-                key_parts: key,
-                value,
-            });
+            if TokenValidator::is_section_header(&line) {
+                section = match TokenValidator::validate_section_header(&line) {
+                    Ok(key_tokens) => Some(Self::build_key(key_tokens)),
+                    Err(error) => {
+                        errors.push(error);
+                        None
+                    }
+                };
+                continue;
+            }
+
+            match (Self::analyze_line(&line), &section) {
+                (Ok(mut pattern), Some(section)) => {
+                    pattern.key_parts.splice(0..0, section.iter().cloned());
+                    Self::flatten(pattern, &mut patterns);
+                }
+                (Ok(_), None) => {}
+                (Err(error), _) => errors.push(error),
+            }
         }
 
-        Ok(Syntax { patterns })
+        (Syntax { patterns }, errors)
     }
 
-    fn split_key_value(tokens: &[Token]) -> Result<(Vec<Token>, Vec<Token>), TokenValidationError> {
+    fn analyze_line(line: &[SpannedToken]) -> Result<Pattern, TokenValidationError> {
+        TokenValidator::validate_line_structure(line)?;
+        let (key_tokens, separator_span, value_tokens) = Self::split_key_value(line)?;
+        TokenValidator::validate_key(&key_tokens)?;
+        TokenValidator::validate_value(&value_tokens)?;
+        let key = Self::build_key(&key_tokens);
+        let value = Self::build_value(&value_tokens)?;
+        Ok(Pattern {
+            key_parts: key,
+            value,
+            key_span: TokenValidator::span_of(&key_tokens),
+            value_span: Self::value_span(&value_tokens, separator_span),
+        })
+    }
+
+    /// Expands a non-empty inline table into one pattern per entry, so that
+    /// `a = { b = 1 }` is treated exactly like `a.b = 1`.
+    fn flatten(pattern: Pattern, patterns: &mut Vec<Pattern>) {
+        match pattern.value {
+            SyntaxValue::Table(syntax) if !syntax.patterns.is_empty() => {
+                for entry in syntax.patterns {
+                    let mut key_parts = pattern.key_parts.clone();
+                    key_parts.extend(entry.key_parts);
+                    patterns.push(Pattern { key_parts, ..entry });
+                }
+            }
+            _ => patterns.push(pattern),
+        }
+    }
+
+    fn split_key_value(
+        tokens: &[SpannedToken],
+    ) -> Result<(Vec<SpannedToken>, Span, Vec<SpannedToken>), TokenValidationError> {
         let mut key_tokens = Vec::new();
         let mut value_tokens = Vec::new();
 
-        let equal_pos = tokens.iter().position(|t| matches!(t, Token::Separator));
+        let equal_pos = tokens.iter().position(|t| t.token == Token::Separator);
 
-        match equal_pos {
+        let separator_span = match equal_pos {
             Some(pos) => {
                 key_tokens.extend_from_slice(&tokens[..pos]);
                 value_tokens.extend_from_slice(&tokens[pos + 1..]);
+                tokens[pos].span
             }
             None => {
                 unreachable!("There should be exactly one Separator token at this point");
             }
+        };
+
+        Ok((key_tokens, separator_span, value_tokens))
+    }
+
+    fn value_span(value_tokens: &[SpannedToken], separator_span: Span) -> Span {
+        if value_tokens.is_empty() {
+            return separator_span;
         }
 
-        Ok((key_tokens, value_tokens))
+        TokenValidator::span_of(value_tokens)
     }
 
-    fn build_key(key_tokens: &[Token]) -> Vec<String> {
+    fn build_key(key_tokens: &[SpannedToken]) -> Vec<String> {
         key_tokens
             .iter()
-            .filter_map(|token| match token {
-                Token::Ident(name) => Some(name.clone()),
+            .filter_map(|t| match &t.token {
+                Token::Ident(name) | Token::QuotedIdent(name) => Some(name.clone()),
                 _ => None,
             })
             .collect()
     }
 
-    fn build_value(value_tokens: &[Token]) -> Result<SyntaxValue, TokenValidationError> {
+    fn build_value(value_tokens: &[SpannedToken]) -> Result<SyntaxValue, TokenValidationError> {
         if value_tokens.is_empty() {
             return Ok(SyntaxValue::Plain(String::new()));
         }
 
+        if let Some(array) = TokenValidator::optional_array(value_tokens) {
+            return Ok(SyntaxValue::Optional(Box::new(Self::build_value(array)?)));
+        }
+
+        if TokenValidator::is_array(value_tokens) {
+            let items = TokenValidator::split_array(value_tokens)?
+                .into_iter()
+                .map(Self::build_value)
+                .collect::<Result<_, _>>()?;
+            return Ok(SyntaxValue::Array(items));
+        }
+
+        if TokenValidator::is_table(value_tokens) {
+            let mut patterns = Vec::new();
+            for entry in TokenValidator::split_table(value_tokens)? {
+                Self::flatten(Self::analyze_line(entry)?, &mut patterns);
+            }
+            return Ok(SyntaxValue::Table(Syntax { patterns }));
+        }
+
         if value_tokens.len() == 1 {
-            match &value_tokens[0] {
+            match &value_tokens[0].token {
                 Token::QuotedIdent(name) => return Ok(SyntaxValue::Quoted(name.clone())),
                 Token::Ident(name) => return Ok(SyntaxValue::Plain(name.clone())),
                 _ => {}
@@ -85,8 +172,8 @@ impl TokenAnalyzer {
         }
 
         let mut result = String::new();
-        for token in value_tokens {
-            match token {
+        for t in value_tokens {
+            match &t.token {
                 Token::Ident(name) => result.push_str(name),
                 Token::Dot => result.push('.'),
                 _ => {
@@ -104,6 +191,7 @@ impl TokenAnalyzer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::validator::token::spanned;
     use kernel::token::Token;
 
     #[test]
@@ -120,7 +208,9 @@ mod tests {
             Token::Eof,
         ];
 
-        let syntax = TokenAnalyzer::analyze(tokens).unwrap();
+        let (syntax, errors) = TokenAnalyzer::analyze(spanned(tokens));
+
+        assert!(errors.is_empty());
 
         assert_eq!(syntax.patterns.len(), 2);
         assert_eq!(syntax.patterns[0].key_parts, vec!["key1".to_string()]);
@@ -135,7 +225,8 @@ mod tests {
         );
 
         let tokens: Vec<Token> = vec![Token::Eof];
-        let empty_syntax = TokenAnalyzer::analyze(tokens).unwrap();
+        let (empty_syntax, errors) = TokenAnalyzer::analyze(spanned(tokens));
+        assert!(errors.is_empty());
         assert!(empty_syntax.patterns.is_empty());
 
         let tokens = vec![
@@ -146,7 +237,8 @@ mod tests {
             Token::Ident("value1".to_string()),
             Token::Newline,
         ];
-        let syntax = TokenAnalyzer::analyze(tokens).unwrap();
+        let (syntax, errors) = TokenAnalyzer::analyze(spanned(tokens));
+        assert!(errors.is_empty());
         assert_eq!(syntax.patterns.len(), 1);
         assert_eq!(
             syntax.patterns[0].key_parts,
@@ -163,7 +255,8 @@ mod tests {
             Token::QuotedIdent("value1".to_string()),
             Token::Newline,
         ];
-        let syntax = TokenAnalyzer::analyze(tokens).unwrap();
+        let (syntax, errors) = TokenAnalyzer::analyze(spanned(tokens));
+        assert!(errors.is_empty());
         assert_eq!(syntax.patterns.len(), 1);
         assert_eq!(syntax.patterns[0].key_parts, vec!["key".to_string()]);
         assert_eq!(
@@ -180,7 +273,9 @@ mod tests {
             Token::Newline,
         ];
 
-        let syntax = TokenAnalyzer::analyze(tokens).unwrap();
+        let (syntax, errors) = TokenAnalyzer::analyze(spanned(tokens));
+
+        assert!(errors.is_empty());
         assert_eq!(syntax.patterns.len(), 1);
         assert_eq!(syntax.patterns[0].key_parts, vec!["key".to_string()]);
         assert_eq!(
@@ -195,7 +290,8 @@ mod tests {
             Token::Ident("1".to_string()),
             Token::Newline,
         ];
-        let syntax = TokenAnalyzer::analyze(tokens).unwrap();
+        let (syntax, errors) = TokenAnalyzer::analyze(spanned(tokens));
+        assert!(errors.is_empty());
         assert_eq!(syntax.patterns.len(), 1);
         assert_eq!(syntax.patterns[0].key_parts, vec!["key".to_string()]);
         assert_eq!(
@@ -208,7 +304,8 @@ mod tests {
             Token::Separator,
             Token::Newline,
         ];
-        let syntax = TokenAnalyzer::analyze(tokens).unwrap();
+        let (syntax, errors) = TokenAnalyzer::analyze(spanned(tokens));
+        assert!(errors.is_empty());
         assert_eq!(syntax.patterns.len(), 1);
         assert_eq!(syntax.patterns[0].key_parts, vec!["key".to_string()]);
         assert_eq!(syntax.patterns[0].value, SyntaxValue::Plain("".to_string()));
@@ -219,7 +316,8 @@ mod tests {
             Token::Ident("-1".to_string()),
             Token::Newline,
         ];
-        let syntax = TokenAnalyzer::analyze(tokens).unwrap();
+        let (syntax, errors) = TokenAnalyzer::analyze(spanned(tokens));
+        assert!(errors.is_empty());
         assert_eq!(syntax.patterns.len(), 1);
         assert_eq!(syntax.patterns[0].key_parts, vec!["key".to_string()]);
         assert_eq!(
@@ -231,6 +329,253 @@ mod tests {
     #[test]
     fn test_invalid_syntax() {
         let invalid_tokens = vec![Token::Ident("invalid".to_string()), Token::Newline];
-        assert!(TokenAnalyzer::analyze(invalid_tokens).is_err());
+        let (syntax, errors) = TokenAnalyzer::analyze(spanned(invalid_tokens));
+        assert!(syntax.patterns.is_empty());
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_collects_errors_past_invalid_lines() {
+        let tokens = vec![
+            Token::Ident("invalid".to_string()),
+            Token::Newline,
+            Token::Ident("key".to_string()),
+            Token::Separator,
+            Token::Ident("value".to_string()),
+            Token::Newline,
+            Token::Separator,
+            Token::Ident("value".to_string()),
+            Token::Eof,
+        ];
+        let (syntax, errors) = TokenAnalyzer::analyze(spanned(tokens));
+
+        assert_eq!(syntax.patterns.len(), 1);
+        assert_eq!(syntax.patterns[0].key_parts, vec!["key".to_string()]);
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn test_array_value() {
+        let tokens = vec![
+            Token::Ident("hosts".to_string()),
+            Token::Separator,
+            Token::LeftBracket,
+            Token::Ident("a".to_string()),
+            Token::Comma,
+            Token::QuotedIdent("b.example.com".to_string()),
+            Token::Comma,
+            Token::LeftBracket,
+            Token::RightBracket,
+            Token::RightBracket,
+            Token::Eof,
+        ];
+        let (syntax, errors) = TokenAnalyzer::analyze(spanned(tokens));
+
+        assert!(errors.is_empty());
+        assert_eq!(
+            syntax.patterns[0].value,
+            SyntaxValue::Array(vec![
+                SyntaxValue::Plain("a".to_string()),
+                SyntaxValue::Quoted("b.example.com".to_string()),
+                SyntaxValue::Array(vec![]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_inline_table_is_flattened() {
+        let tokens = vec![
+            Token::Ident("database".to_string()),
+            Token::Dot,
+            Token::Ident("primary".to_string()),
+            Token::Separator,
+            Token::LeftBrace,
+            Token::Ident("host".to_string()),
+            Token::Separator,
+            Token::QuotedIdent("db1".to_string()),
+            Token::Comma,
+            Token::Ident("pool".to_string()),
+            Token::Separator,
+            Token::LeftBrace,
+            Token::Ident("size".to_string()),
+            Token::Separator,
+            Token::Ident("5".to_string()),
+            Token::RightBrace,
+            Token::RightBrace,
+            Token::Newline,
+            Token::Ident("empty".to_string()),
+            Token::Separator,
+            Token::LeftBrace,
+            Token::RightBrace,
+            Token::Eof,
+        ];
+        let (syntax, errors) = TokenAnalyzer::analyze(spanned(tokens));
+
+        assert!(errors.is_empty());
+        let keys: Vec<String> = syntax
+            .patterns
+            .iter()
+            .map(|pattern| pattern.key_parts.join("."))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                "database.primary.host",
+                "database.primary.pool.size",
+                "empty"
+            ]
+        );
+        assert_eq!(
+            syntax.patterns[1].value,
+            SyntaxValue::Plain("5".to_string())
+        );
+        assert_eq!(
+            syntax.patterns[2].value,
+            SyntaxValue::Table(Syntax { patterns: vec![] })
+        );
+    }
+
+    #[test]
+    fn test_quoted_key_parts() {
+        let tokens = vec![
+            Token::Ident("routes".to_string()),
+            Token::Dot,
+            Token::QuotedIdent("/api/v1".to_string()),
+            Token::Dot,
+            Token::Ident("timeout".to_string()),
+            Token::Separator,
+            Token::Ident("30".to_string()),
+            Token::Newline,
+            Token::Ident("labels".to_string()),
+            Token::Separator,
+            Token::LeftBrace,
+            Token::QuotedIdent("app.kubernetes.io/name".to_string()),
+            Token::Separator,
+            Token::Ident("web".to_string()),
+            Token::RightBrace,
+            Token::Eof,
+        ];
+        let (syntax, errors) = TokenAnalyzer::analyze(spanned(tokens));
+
+        assert!(errors.is_empty());
+        assert_eq!(
+            syntax.patterns[0].key_parts,
+            vec!["routes", "/api/v1", "timeout"]
+        );
+        assert_eq!(
+            syntax.patterns[1].key_parts,
+            vec!["labels", "app.kubernetes.io/name"]
+        );
+    }
+
+    #[test]
+    fn test_section_headers() {
+        let tokens = vec![
+            Token::Ident("name".to_string()),
+            Token::Separator,
+            Token::Ident("app".to_string()),
+            Token::Newline,
+            Token::LeftBracket,
+            Token::Ident("server".to_string()),
+            Token::Dot,
+            Token::Ident("tls".to_string()),
+            Token::RightBracket,
+            Token::Newline,
+            Token::Ident("cert".to_string()),
+            Token::Separator,
+            Token::Ident("a.pem".to_string()),
+            Token::Newline,
+            Token::Ident("options".to_string()),
+            Token::Separator,
+            Token::LeftBrace,
+            Token::Ident("verify".to_string()),
+            Token::Separator,
+            Token::Ident("true".to_string()),
+            Token::RightBrace,
+            Token::Eof,
+        ];
+        let (syntax, errors) = TokenAnalyzer::analyze(spanned(tokens));
+
+        assert!(errors.is_empty());
+        let keys: Vec<String> = syntax
+            .patterns
+            .iter()
+            .map(|pattern| pattern.key_parts.join("."))
+            .collect();
+        assert_eq!(
+            keys,
+            vec!["name", "server.tls.cert", "server.tls.options.verify"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_section_header_drops_its_keys() {
+        let tokens = vec![
+            Token::LeftBracket,
+            Token::Ident("server".to_string()),
+            Token::Newline,
+            Token::Ident("host".to_string()),
+            Token::Separator,
+            Token::Ident("a".to_string()),
+            Token::Newline,
+            Token::Ident("port".to_string()),
+            Token::Newline,
+            Token::LeftBracket,
+            Token::Ident("db".to_string()),
+            Token::RightBracket,
+            Token::Newline,
+            Token::Ident("url".to_string()),
+            Token::Separator,
+            Token::Ident("b".to_string()),
+            Token::Eof,
+        ];
+        let (syntax, errors) = TokenAnalyzer::analyze(spanned(tokens));
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(syntax.patterns.len(), 1);
+        assert_eq!(syntax.patterns[0].key_parts, vec!["db", "url"]);
+    }
+
+    #[test]
+    fn test_eof_of_included_file_closes_its_sections() {
+        let tokens = vec![
+            Token::LeftBracket,
+            Token::Ident("server".to_string()),
+            Token::RightBracket,
+            Token::Newline,
+            Token::Ident("host".to_string()),
+            Token::Separator,
+            Token::Ident("a".to_string()),
+            Token::Eof,
+            Token::Newline,
+            Token::Ident("name".to_string()),
+            Token::Separator,
+            Token::Ident("b".to_string()),
+            Token::Eof,
+        ];
+        let (syntax, errors) = TokenAnalyzer::analyze(spanned(tokens));
+
+        assert!(errors.is_empty());
+        assert_eq!(syntax.patterns[0].key_parts, vec!["server", "host"]);
+        assert_eq!(syntax.patterns[1].key_parts, vec!["name"]);
+    }
+
+    #[test]
+    fn test_inline_table_entry_errors() {
+        let tokens = vec![
+            Token::Ident("db".to_string()),
+            Token::Separator,
+            Token::LeftBrace,
+            Token::Ident("host".to_string()),
+            Token::RightBrace,
+            Token::Eof,
+        ];
+        let (syntax, errors) = TokenAnalyzer::analyze(spanned(tokens));
+
+        assert!(syntax.patterns.is_empty());
+        assert!(matches!(
+            errors.as_slice(),
+            [TokenValidationError::LineStructure(_)]
+        ));
     }
 }
@@ -1,19 +1,22 @@
 use super::SyntaxValidator;
-use crate::syntax::Syntax;
+use crate::syntax::{Syntax, display_key};
 use kernel::error::SyntaxValidationError;
 use std::collections::HashSet;
 
 impl SyntaxValidator {
-    pub fn validate_duplicate_keys(syntax: &Syntax) -> Result<(), SyntaxValidationError> {
+    pub fn validate_duplicate_keys(syntax: &Syntax) -> Vec<SyntaxValidationError> {
         let mut seen_keys = HashSet::new();
+        let mut errors = Vec::new();
         for pattern in &syntax.patterns {
-            let key = pattern.key_parts.join(".");
-            if !seen_keys.insert(key.clone()) {
-                return Err(SyntaxValidationError::Duplicate { key });
+            if !seen_keys.insert(pattern.key_parts.as_slice()) {
+                errors.push(SyntaxValidationError::Duplicate {
+                    key: display_key(&pattern.key_parts),
+                    span: pattern.key_span,
+                });
             }
         }
 
-        Ok(())
+        errors
     }
 }
 
@@ -21,6 +24,7 @@ impl SyntaxValidator {
 mod tests {
     use super::*;
     use crate::syntax::{Pattern, Syntax, SyntaxValue};
+    use kernel::span::Span;
 
     #[test]
     fn test_valid() {
@@ -29,33 +33,61 @@ mod tests {
                 Pattern {
                     key_parts: vec!["key1".into()],
                     value: SyntaxValue::Plain("value1".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
                 },
                 Pattern {
                     key_parts: vec!["key2".into()],
                     value: SyntaxValue::Plain("value2".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
                 },
             ],
         };
-        assert!(SyntaxValidator::validate_duplicate_keys(&syntax).is_ok());
+        assert!(SyntaxValidator::validate_duplicate_keys(&syntax).is_empty());
 
         let syntax = Syntax {
             patterns: vec![
                 Pattern {
                     key_parts: vec!["key1".into(), "subkey1".into()],
                     value: SyntaxValue::Plain("value1".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
                 },
                 Pattern {
                     key_parts: vec!["key1".into(), "subkey2".into()],
                     value: SyntaxValue::Plain("value2".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
                 },
                 Pattern {
                     key_parts: vec!["key2".into(), "subkey1".into()],
                     value: SyntaxValue::Plain("value3".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
                 },
             ],
         };
 
-        assert!(SyntaxValidator::validate_duplicate_keys(&syntax).is_ok());
+        assert!(SyntaxValidator::validate_duplicate_keys(&syntax).is_empty());
+
+        let syntax = Syntax {
+            patterns: vec![
+                Pattern {
+                    key_parts: vec!["a".into(), "b.c".into()],
+                    value: SyntaxValue::Plain("value1".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
+                },
+                Pattern {
+                    key_parts: vec!["a".into(), "b".into(), "c".into()],
+                    value: SyntaxValue::Plain("value2".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
+                },
+            ],
+        };
+        assert!(SyntaxValidator::validate_duplicate_keys(&syntax).is_empty());
     }
 
     mod invalid {
@@ -68,16 +100,20 @@ mod tests {
                     Pattern {
                         key_parts: vec!["key1".into()],
                         value: SyntaxValue::Plain("value1".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                     Pattern {
                         key_parts: vec!["key1".into()],
                         value: SyntaxValue::Plain("value2".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                 ],
             };
             assert!(matches!(
-                SyntaxValidator::validate_duplicate_keys(&syntax),
-                Err(SyntaxValidationError::Duplicate { key }) if key == "key1"
+                SyntaxValidator::validate_duplicate_keys(&syntax).as_slice(),
+                [SyntaxValidationError::Duplicate { key, .. }] if key == "key1"
             ));
 
             let syntax = Syntax {
@@ -85,17 +121,46 @@ mod tests {
                     Pattern {
                         key_parts: vec!["key1".into(), "subkey1".into()],
                         value: SyntaxValue::Plain("value1".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                     Pattern {
                         key_parts: vec!["key1".into(), "subkey1".into()],
                         value: SyntaxValue::Plain("value2".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
                     },
                 ],
             };
             assert!(matches!(
-                SyntaxValidator::validate_duplicate_keys(&syntax),
-                Err(SyntaxValidationError::Duplicate { key }) if key == "key1.subkey1"
+                SyntaxValidator::validate_duplicate_keys(&syntax).as_slice(),
+                [SyntaxValidationError::Duplicate { key, .. }] if key == "key1.subkey1"
             ));
         }
+
+        #[test]
+        fn test_collects_every_duplicate() {
+            let syntax = Syntax {
+                patterns: ["key1", "key2", "key1", "key2"]
+                    .iter()
+                    .map(|key| Pattern {
+                        key_parts: vec![key.to_string()],
+                        value: SyntaxValue::Plain("value".into()),
+                        key_span: Span::default(),
+                        value_span: Span::default(),
+                    })
+                    .collect(),
+            };
+
+            let errors = SyntaxValidator::validate_duplicate_keys(&syntax);
+            let keys: Vec<&str> = errors
+                .iter()
+                .map(|error| match error {
+                    SyntaxValidationError::Duplicate { key, .. } => key.as_str(),
+                    _ => unreachable!(),
+                })
+                .collect();
+            assert_eq!(keys, vec!["key1", "key2"]);
+        }
     }
 }
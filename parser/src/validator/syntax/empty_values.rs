@@ -0,0 +1,108 @@
+use super::ConfigRule;
+use crate::syntax::{Syntax, SyntaxValue, display_key};
+use kernel::error::SyntaxValidationError;
+use kernel::options::EmptyValue;
+
+impl ConfigRule {
+    /// `key =` is analyzed as an empty plain value; the policy decides whether
+    /// it stays an empty string, becomes `null` or is reported and dropped.
+    pub(super) fn resolve_empty_values(
+        syntax: &mut Syntax,
+        policy: EmptyValue,
+        errors: &mut Vec<SyntaxValidationError>,
+    ) {
+        syntax.patterns.retain_mut(|pattern| {
+            if !Self::is_empty_value(&pattern.value) {
+                Self::resolve_nested(&mut pattern.value, policy, errors);
+                return true;
+            }
+
+            match policy {
+                EmptyValue::EmptyString => true,
+                EmptyValue::Null => {
+                    pattern.value = SyntaxValue::Plain("null".to_string());
+                    true
+                }
+                EmptyValue::Error => {
+                    errors.push(SyntaxValidationError::EmptyValue {
+                        key: display_key(&pattern.key_parts),
+                        span: pattern.value_span,
+                    });
+                    false
+                }
+            }
+        });
+    }
+
+    fn resolve_nested(
+        value: &mut SyntaxValue,
+        policy: EmptyValue,
+        errors: &mut Vec<SyntaxValidationError>,
+    ) {
+        match value {
+            SyntaxValue::Array(items) => {
+                for item in items {
+                    Self::resolve_nested(item, policy, errors);
+                }
+            }
+            SyntaxValue::Table(syntax) => Self::resolve_empty_values(syntax, policy, errors),
+            _ => {}
+        }
+    }
+
+    fn is_empty_value(value: &SyntaxValue) -> bool {
+        matches!(value, SyntaxValue::Plain(s) if s.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::Pattern;
+    use kernel::span::Span;
+
+    fn syntax() -> Syntax {
+        let pattern = |key: &str, value: &str| Pattern {
+            key_parts: key.split('.').map(String::from).collect(),
+            value: SyntaxValue::Plain(value.to_string()),
+            key_span: Span::default(),
+            value_span: Span::default(),
+        };
+        Syntax {
+            patterns: vec![pattern("a", ""), pattern("b", "1")],
+        }
+    }
+
+    #[test]
+    fn test_empty_string_policy() {
+        let mut syntax = syntax();
+        let mut errors = Vec::new();
+        ConfigRule::resolve_empty_values(&mut syntax, EmptyValue::EmptyString, &mut errors);
+
+        assert!(errors.is_empty());
+        assert_eq!(syntax.patterns[0].value, SyntaxValue::Plain(String::new()));
+    }
+
+    #[test]
+    fn test_null_policy() {
+        let mut syntax = syntax();
+        let mut errors = Vec::new();
+        ConfigRule::resolve_empty_values(&mut syntax, EmptyValue::Null, &mut errors);
+
+        assert!(errors.is_empty());
+        assert_eq!(syntax.patterns[0].value, SyntaxValue::Plain("null".into()));
+    }
+
+    #[test]
+    fn test_error_policy() {
+        let mut syntax = syntax();
+        let mut errors = Vec::new();
+        ConfigRule::resolve_empty_values(&mut syntax, EmptyValue::Error, &mut errors);
+
+        assert_eq!(syntax.patterns.len(), 1);
+        assert!(matches!(
+            errors.as_slice(),
+            [SyntaxValidationError::EmptyValue { key, .. }] if key == "a"
+        ));
+    }
+}
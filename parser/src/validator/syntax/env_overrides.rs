@@ -0,0 +1,280 @@
+use super::{ConfigRule, Scope, SyntaxValidator};
+use crate::syntax::{Pattern, Syntax, SyntaxValue, display_key};
+use crate::validator::token::TokenValidator;
+use kernel::env::Environment;
+use kernel::error::SyntaxValidationError;
+use kernel::span::Span;
+use kernel::token::{SpannedToken, Token};
+use std::collections::BTreeSet;
+
+/// Separates the prefix and the key parts of an override variable.
+const SEPARATOR: &str = "__";
+
+impl ConfigRule {
+    /// With the prefix `MYAPP`, `MYAPP__SERVER__PORT=9000` replaces
+    /// `server.port`. A variable whose name is not a valid key, that names a
+    /// table or a key below a value, or that skips a list index is reported
+    /// by its name. The value is read like a bare value in a file, but never
+    /// as a reference.
+    pub(super) fn override_from_env(
+        syntax: &mut Syntax,
+        prefix: &str,
+        env: &dyn Environment,
+        scope: Scope,
+        errors: &mut Vec<SyntaxValidationError>,
+    ) {
+        for (name, value) in env.vars() {
+            let Some(key_parts) = Self::override_key(&name, prefix) else {
+                continue;
+            };
+
+            if !Self::is_valid_override_key(&key_parts) {
+                errors.push(SyntaxValidationError::InvalidEnvOverrideKey { name });
+                continue;
+            }
+
+            if scope == Scope::Document
+                && let Some(key) = Self::index_out_of_sequence(syntax, &key_parts)
+            {
+                errors.push(SyntaxValidationError::EnvOverrideIndexOutOfSequence { name, key });
+                continue;
+            }
+
+            let replaced = syntax.replace(Pattern {
+                key_parts,
+                value: SyntaxValue::Plain(value.replace("${", "$${")),
+                key_span: Span::default(),
+                value_span: Span::default(),
+            });
+            if let Err(error) = replaced {
+                errors.push(match error {
+                    SyntaxValidationError::KeyPathConflict { key, .. } => {
+                        SyntaxValidationError::EnvOverrideConflict { name, key }
+                    }
+                    other => other,
+                });
+            }
+        }
+    }
+
+    pub(crate) fn override_key(name: &str, prefix: &str) -> Option<Vec<String>> {
+        let key = name.strip_prefix(prefix)?.strip_prefix(SEPARATOR)?;
+        let key_parts: Vec<String> = key.split(SEPARATOR).map(str::to_lowercase).collect();
+        key_parts
+            .iter()
+            .all(|part| !part.is_empty())
+            .then_some(key_parts)
+    }
+
+    /// Each part must be something a bare key part in a file could be, so
+    /// that the variable names exactly one key.
+    fn is_valid_override_key(key_parts: &[String]) -> bool {
+        let has_valid_chars = key_parts.iter().all(|part| {
+            part.chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        });
+
+        let tokens: Vec<SpannedToken> = key_parts
+            .iter()
+            .enumerate()
+            .flat_map(|(pos, part)| {
+                let dot = (pos > 0).then_some(Token::Dot);
+                dot.into_iter().chain([Token::Ident(part.clone())])
+            })
+            .map(SpannedToken::from)
+            .collect();
+
+        has_valid_chars && TokenValidator::validate_key(&tokens).is_ok()
+    }
+
+    /// The first list index in `key_parts` that is neither one of the
+    /// elements already set nor the next one after them.
+    fn index_out_of_sequence(syntax: &Syntax, key_parts: &[String]) -> Option<String> {
+        SyntaxValidator::indices_of(key_parts)
+            .find(|&(pos, index)| {
+                let existing: BTreeSet<&str> = syntax
+                    .patterns
+                    .iter()
+                    .filter(|pattern| {
+                        pattern.key_parts.len() > pos
+                            && pattern.key_parts[..pos] == key_parts[..pos]
+                    })
+                    .map(|pattern| pattern.key_parts[pos].as_str())
+                    .collect();
+                index > existing.len()
+            })
+            .map(|(pos, _)| display_key(&key_parts[..=pos]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn pattern(key: &str, value: &str) -> Pattern {
+        Pattern {
+            key_parts: key.split('.').map(String::from).collect(),
+            value: SyntaxValue::Plain(value.to_string()),
+            key_span: Span::default(),
+            value_span: Span::default(),
+        }
+    }
+
+    fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_override_key() {
+        assert_eq!(
+            ConfigRule::override_key("MYAPP__SERVER__MAX_CONNECTIONS", "MYAPP"),
+            Some(vec!["server".into(), "max_connections".into()])
+        );
+        assert_eq!(ConfigRule::override_key("MYAPP_SERVER", "MYAPP"), None);
+        assert_eq!(ConfigRule::override_key("OTHER__SERVER", "MYAPP"), None);
+        assert_eq!(ConfigRule::override_key("MYAPP__", "MYAPP"), None);
+        assert_eq!(ConfigRule::override_key("MYAPP__A____B", "MYAPP"), None);
+    }
+
+    #[test]
+    fn test_override_from_env() {
+        let mut syntax = Syntax {
+            patterns: vec![
+                pattern("server.host", "localhost"),
+                pattern("server.port", "8080"),
+                pattern("db.pool.size", "5"),
+            ],
+        };
+        let env = env(&[
+            ("MYAPP__SERVER__PORT", "9000"),
+            ("MYAPP__DB__POOL__SIZE", "10"),
+            ("MYAPP__LOG__FORMAT", "${json}"),
+            ("PATH", "/usr/bin"),
+        ]);
+        let mut errors = Vec::new();
+        ConfigRule::override_from_env(&mut syntax, "MYAPP", &env, Scope::Document, &mut errors);
+
+        assert!(errors.is_empty());
+        assert_eq!(
+            syntax.patterns,
+            vec![
+                pattern("server.host", "localhost"),
+                pattern("db.pool.size", "10"),
+                pattern("log.format", "$${json}"),
+                pattern("server.port", "9000"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_override_conflicts() {
+        let mut syntax = Syntax {
+            patterns: vec![
+                pattern("server.tls", "false"),
+                pattern("db.pool.size", "5"),
+                pattern("db.pool.timeout", "30s"),
+            ],
+        };
+        let env = env(&[
+            ("MYAPP__SERVER__TLS__CERT", "cert.pem"),
+            ("MYAPP__DB__POOL", "none"),
+        ]);
+        let mut errors = Vec::new();
+        ConfigRule::override_from_env(&mut syntax, "MYAPP", &env, Scope::Document, &mut errors);
+
+        let conflicts: Vec<(&str, &str)> = errors
+            .iter()
+            .map(|error| match error {
+                SyntaxValidationError::EnvOverrideConflict { name, key } => {
+                    (name.as_str(), key.as_str())
+                }
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(
+            conflicts,
+            vec![
+                ("MYAPP__DB__POOL", "db.pool"),
+                ("MYAPP__SERVER__TLS__CERT", "server.tls")
+            ]
+        );
+        assert_eq!(
+            syntax.patterns,
+            vec![
+                pattern("server.tls", "false"),
+                pattern("db.pool.size", "5"),
+                pattern("db.pool.timeout", "30s"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_override_keys() {
+        let mut syntax = Syntax {
+            patterns: vec![pattern("servers.0.host", "a"), pattern("y", "1")],
+        };
+        let env = env(&[
+            ("MYAPP__Y.Z", "2"),
+            ("MYAPP__SERVER-__PORT", "80"),
+            ("MYAPP__0__HOST", "b"),
+            ("MYAPP__SERVERS__01__HOST", "b"),
+        ]);
+        let mut errors = Vec::new();
+        ConfigRule::override_from_env(&mut syntax, "MYAPP", &env, Scope::Document, &mut errors);
+
+        let names: Vec<&str> = errors
+            .iter()
+            .map(|error| match error {
+                SyntaxValidationError::InvalidEnvOverrideKey { name } => name.as_str(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "MYAPP__0__HOST",
+                "MYAPP__SERVER-__PORT",
+                "MYAPP__SERVERS__01__HOST",
+                "MYAPP__Y.Z"
+            ]
+        );
+        assert_eq!(syntax.patterns.len(), 2);
+    }
+
+    #[test]
+    fn test_override_indices() {
+        let mut syntax = Syntax {
+            patterns: vec![pattern("servers.0.host", "a")],
+        };
+        let env = env(&[
+            ("MYAPP__SERVERS__0__HOST", "b"),
+            ("MYAPP__SERVERS__1__HOST", "c"),
+            ("MYAPP__SERVERS__5__HOST", "d"),
+        ]);
+        let mut errors = Vec::new();
+        ConfigRule::override_from_env(&mut syntax, "MYAPP", &env, Scope::Document, &mut errors);
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            SyntaxValidationError::EnvOverrideIndexOutOfSequence { name, key }
+                if name == "MYAPP__SERVERS__5__HOST" && key == "servers.5"
+        ));
+        assert_eq!(
+            syntax.patterns,
+            vec![
+                pattern("servers.0.host", "b"),
+                pattern("servers.1.host", "c")
+            ]
+        );
+
+        let mut syntax = Syntax { patterns: vec![] };
+        let env = self::env(&[("MYAPP__SERVERS__5__HOST", "d")]);
+        let mut errors = Vec::new();
+        ConfigRule::override_from_env(&mut syntax, "MYAPP", &env, Scope::Layer, &mut errors);
+        assert!(errors.is_empty());
+    }
+}
@@ -0,0 +1,90 @@
+use super::SyntaxValidator;
+use crate::syntax::{Syntax, display_key};
+use kernel::error::SyntaxValidationError;
+use std::collections::{BTreeSet, HashMap};
+
+impl SyntaxValidator {
+    pub fn validate_array_indices(syntax: &Syntax) -> Vec<SyntaxValidationError> {
+        let mut indices: HashMap<&[String], BTreeSet<usize>> = HashMap::new();
+        for pattern in &syntax.patterns {
+            for (pos, index) in Self::indices_of(&pattern.key_parts) {
+                indices
+                    .entry(&pattern.key_parts[..pos])
+                    .or_default()
+                    .insert(index);
+            }
+        }
+
+        let mut errors = Vec::new();
+        for pattern in &syntax.patterns {
+            let out_of_sequence = Self::indices_of(&pattern.key_parts)
+                .find(|&(pos, index)| index >= indices[&pattern.key_parts[..pos]].len());
+
+            if let Some((pos, _)) = out_of_sequence {
+                errors.push(SyntaxValidationError::IndexOutOfSequence {
+                    key: display_key(&pattern.key_parts[..=pos]),
+                    span: pattern.key_span,
+                });
+            }
+        }
+
+        errors
+    }
+
+    pub(super) fn indices_of(key_parts: &[String]) -> impl Iterator<Item = (usize, usize)> + '_ {
+        key_parts
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, part)| part.chars().all(|c| c.is_ascii_digit()))
+            .filter_map(|(pos, part)| part.parse().ok().map(|index| (pos, index)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::{Pattern, SyntaxValue};
+    use kernel::span::Span;
+
+    fn syntax_of(keys: &[&str]) -> Syntax {
+        Syntax {
+            patterns: keys
+                .iter()
+                .map(|key| Pattern {
+                    key_parts: key.split('.').map(String::from).collect(),
+                    value: SyntaxValue::Plain("value".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_valid_indices() {
+        let syntax = syntax_of(&["servers.1.host", "servers.0.host", "servers.1.port"]);
+        assert!(SyntaxValidator::validate_array_indices(&syntax).is_empty());
+
+        let syntax = syntax_of(&["matrix.0.0", "matrix.0.1", "matrix.1.0"]);
+        assert!(SyntaxValidator::validate_array_indices(&syntax).is_empty());
+    }
+
+    #[test]
+    fn test_index_gap() {
+        let syntax = syntax_of(&["servers.0.host", "servers.2.host"]);
+        let errors = SyntaxValidator::validate_array_indices(&syntax);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            SyntaxValidationError::IndexOutOfSequence { key, .. } if key == "servers.2"
+        ));
+    }
+
+    #[test]
+    fn test_index_not_starting_at_zero() {
+        let syntax = syntax_of(&["hosts.1"]);
+        let errors = SyntaxValidator::validate_array_indices(&syntax);
+        assert_eq!(errors.len(), 1);
+    }
+}
@@ -1,26 +1,54 @@
 use super::SyntaxValidator;
-use crate::syntax::Syntax;
+use crate::syntax::{Syntax, display_key};
 use kernel::error::SyntaxValidationError;
+use std::collections::HashMap;
 
 impl SyntaxValidator {
-    pub fn validate_key_path_conflicts(syntax: &Syntax) -> Result<(), SyntaxValidationError> {
-        let paths: Vec<String> = syntax
-            .patterns
-            .iter()
-            .map(|pattern| pattern.key_parts.join("."))
-            .collect();
-
-        for path in &paths {
-            let has_children = paths
-                .iter()
-                .any(|other| other != path && other.starts_with(&format!("{}.", path)));
+    pub fn validate_key_path_conflicts(syntax: &Syntax) -> Vec<SyntaxValidationError> {
+        let mut errors = Vec::new();
+        for pattern in &syntax.patterns {
+            let path = &pattern.key_parts;
+            let has_children = syntax.patterns.iter().any(|other| {
+                other.key_parts.len() > path.len() && other.key_parts.starts_with(path)
+            });
 
             if has_children {
-                return Err(SyntaxValidationError::KeyPathConflict { key: path.clone() });
+                errors.push(SyntaxValidationError::KeyPathConflict {
+                    key: display_key(path),
+                    span: pattern.key_span,
+                });
             }
         }
 
-        Ok(())
+        errors.extend(Self::validate_mixed_children(syntax));
+        errors
+    }
+
+    /// A key cannot have both list indices and named keys below it,
+    /// e.g. `servers.0.host` together with `servers.name`.
+    fn validate_mixed_children(syntax: &Syntax) -> Vec<SyntaxValidationError> {
+        let mut child_kinds: HashMap<&[String], bool> = HashMap::new();
+        let mut errors = Vec::new();
+
+        for pattern in &syntax.patterns {
+            let conflict = (1..pattern.key_parts.len()).find(|&pos| {
+                let part = &pattern.key_parts[pos];
+                let is_index = part == "*" || part.chars().all(|c| c.is_ascii_digit());
+                *child_kinds
+                    .entry(&pattern.key_parts[..pos])
+                    .or_insert(is_index)
+                    != is_index
+            });
+
+            if let Some(pos) = conflict {
+                errors.push(SyntaxValidationError::KeyPathConflict {
+                    key: display_key(&pattern.key_parts[..pos]),
+                    span: pattern.key_span,
+                });
+            }
+        }
+
+        errors
     }
 }
 
@@ -29,6 +57,7 @@ mod tests {
     use super::*;
 
     use crate::syntax::{Pattern, Syntax, SyntaxValue};
+    use kernel::span::Span;
 
     #[test]
     fn test_valid_key_paths() {
@@ -37,32 +66,42 @@ mod tests {
                 Pattern {
                     key_parts: vec!["key1".into()],
                     value: SyntaxValue::Plain("value1".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
                 },
                 Pattern {
                     key_parts: vec!["key2".into()],
                     value: SyntaxValue::Plain("value2".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
                 },
             ],
         };
-        assert!(SyntaxValidator::validate_key_path_conflicts(&syntax).is_ok());
+        assert!(SyntaxValidator::validate_key_path_conflicts(&syntax).is_empty());
 
         let syntax = Syntax {
             patterns: vec![
                 Pattern {
                     key_parts: vec!["key1".into(), "subkey1".into()],
                     value: SyntaxValue::Plain("value1".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
                 },
                 Pattern {
                     key_parts: vec!["key1".into(), "subkey2".into()],
                     value: SyntaxValue::Plain("value2".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
                 },
                 Pattern {
                     key_parts: vec!["key2".into(), "subkey1".into()],
                     value: SyntaxValue::Plain("value3".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
                 },
             ],
         };
-        assert!(SyntaxValidator::validate_key_path_conflicts(&syntax).is_ok());
+        assert!(SyntaxValidator::validate_key_path_conflicts(&syntax).is_empty());
     }
 
     #[test]
@@ -72,27 +111,61 @@ mod tests {
                 Pattern {
                     key_parts: vec!["key1".into()],
                     value: SyntaxValue::Plain("value1".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
                 },
                 Pattern {
                     key_parts: vec!["key1".into(), "subkey".into()],
                     value: SyntaxValue::Plain("value2".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
                 },
             ],
         };
-        assert!(SyntaxValidator::validate_key_path_conflicts(&syntax).is_err());
+        assert!(!SyntaxValidator::validate_key_path_conflicts(&syntax).is_empty());
 
         let syntax = Syntax {
             patterns: vec![
                 Pattern {
                     key_parts: vec!["key1".into()],
                     value: SyntaxValue::Plain("value1".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
                 },
                 Pattern {
                     key_parts: vec!["key1".into(), "subkey".into(), "subsubkey".into()],
                     value: SyntaxValue::Plain("value2".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
+                },
+            ],
+        };
+        assert!(!SyntaxValidator::validate_key_path_conflicts(&syntax).is_empty());
+    }
+
+    #[test]
+    fn test_mixed_index_and_named_children() {
+        let syntax = Syntax {
+            patterns: vec![
+                Pattern {
+                    key_parts: vec!["servers".into(), "0".into(), "host".into()],
+                    value: SyntaxValue::Plain("value1".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
+                },
+                Pattern {
+                    key_parts: vec!["servers".into(), "name".into()],
+                    value: SyntaxValue::Plain("value2".into()),
+                    key_span: Span::default(),
+                    value_span: Span::default(),
                 },
             ],
         };
-        assert!(SyntaxValidator::validate_key_path_conflicts(&syntax).is_err());
+        let errors = SyntaxValidator::validate_key_path_conflicts(&syntax);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            SyntaxValidationError::KeyPathConflict { key, .. } if key == "servers"
+        ));
     }
 }
@@ -1,28 +1,159 @@
 mod duplicates;
+mod empty_values;
+mod env_overrides;
+mod indices;
 mod key_path_conflicts;
+mod profiles;
+mod references;
 
+use crate::schema_impl::SchemaExt;
 use crate::syntax::{Syntax, SyntaxValue};
+use kernel::env::Environment;
 use kernel::error::SyntaxValidationError;
+use kernel::options::ParseOptions;
+use kernel::schema::Schema;
+use kernel::span::Span;
+
+/// What the parsed keys make up: a whole document, or a layer applied on top
+/// of a value that is not known here. The list indices of a layer are only
+/// checked against that value, when the layer is applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Document,
+    Layer,
+}
 
 pub trait ValidationRule {
-    fn validate_value(value: &SyntaxValue) -> Result<(), SyntaxValidationError>;
+    fn validate_key(_key_parts: &[String], _span: Span) -> Result<(), SyntaxValidationError> {
+        Ok(())
+    }
+
+    fn validate_value(value: &SyntaxValue, span: Span) -> Result<(), SyntaxValidationError>;
+
+    /// Rewrites the parsed syntax according to options that only some
+    /// grammars understand, reporting the patterns it had to drop.
+    fn apply_options(_syntax: &mut Syntax, _options: &ParseOptions) -> Vec<SyntaxValidationError> {
+        Vec::new()
+    }
+
+    /// Puts the keys of the active profile in place of the base keys,
+    /// reporting the patterns it had to drop.
+    fn apply_profile(_syntax: &mut Syntax, _options: &ParseOptions) -> Vec<SyntaxValidationError> {
+        Vec::new()
+    }
+
+    /// Replaces parsed values with overrides taken from the environment,
+    /// reporting the overrides that do not fit the keys they replace.
+    fn apply_env_overrides(
+        _syntax: &mut Syntax,
+        _options: &ParseOptions,
+        _env: &dyn Environment,
+        _scope: Scope,
+    ) -> Vec<SyntaxValidationError> {
+        Vec::new()
+    }
+
+    /// Resolves references between values, and to the environment, once
+    /// every key is known, reporting the patterns it had to drop.
+    fn resolve_references(
+        _syntax: &mut Syntax,
+        _env: &dyn Environment,
+    ) -> Vec<SyntaxValidationError> {
+        Vec::new()
+    }
 }
 
 pub struct ConfigRule;
 
 impl ValidationRule for ConfigRule {
-    fn validate_value(_value: &SyntaxValue) -> Result<(), SyntaxValidationError> {
-        Ok(())
+    fn validate_value(value: &SyntaxValue, span: Span) -> Result<(), SyntaxValidationError> {
+        match value {
+            SyntaxValue::Optional(_) => Err(SyntaxValidationError::OptionalNotAllowed { span }),
+            SyntaxValue::Array(items) => items
+                .iter()
+                .try_for_each(|item| Self::validate_value(item, span)),
+            SyntaxValue::Table(syntax) => syntax
+                .patterns
+                .iter()
+                .try_for_each(|pattern| Self::validate_value(&pattern.value, pattern.value_span)),
+            SyntaxValue::Plain(_) | SyntaxValue::Quoted(_) => Ok(()),
+        }
+    }
+
+    fn apply_options(syntax: &mut Syntax, options: &ParseOptions) -> Vec<SyntaxValidationError> {
+        let mut errors = Vec::new();
+        Self::resolve_empty_values(syntax, options.empty_value, &mut errors);
+        errors
+    }
+
+    fn apply_profile(syntax: &mut Syntax, options: &ParseOptions) -> Vec<SyntaxValidationError> {
+        let mut errors = Vec::new();
+        Self::select_profile(syntax, options.profile.as_deref(), &mut errors);
+        errors
+    }
+
+    fn apply_env_overrides(
+        syntax: &mut Syntax,
+        options: &ParseOptions,
+        env: &dyn Environment,
+        scope: Scope,
+    ) -> Vec<SyntaxValidationError> {
+        let mut errors = Vec::new();
+        if let Some(prefix) = &options.env_prefix {
+            Self::override_from_env(syntax, prefix, env, scope, &mut errors);
+        }
+        errors
+    }
+
+    fn resolve_references(
+        syntax: &mut Syntax,
+        env: &dyn Environment,
+    ) -> Vec<SyntaxValidationError> {
+        let mut errors = Vec::new();
+        Self::interpolate(syntax, env, &mut errors);
+        errors
     }
 }
 
 pub struct SchemaRule;
 
 impl ValidationRule for SchemaRule {
-    fn validate_value(value: &SyntaxValue) -> Result<(), SyntaxValidationError> {
+    fn validate_key(key_parts: &[String], span: Span) -> Result<(), SyntaxValidationError> {
+        let is_valid = key_parts.iter().enumerate().all(|(pos, part)| {
+            let is_wildcard = part == "*";
+            let is_index = part.chars().all(|c| c.is_ascii_digit());
+            !is_index && (!part.contains('*') || is_wildcard && pos > 0)
+        });
+
+        if is_valid {
+            Ok(())
+        } else {
+            Err(SyntaxValidationError::InvalidSchemaKey {
+                key: key_parts.join("."),
+                span,
+            })
+        }
+    }
+
+    fn validate_value(value: &SyntaxValue, span: Span) -> Result<(), SyntaxValidationError> {
         match value {
-            SyntaxValue::Quoted(_) => Err(SyntaxValidationError::QuotedNotAllowed),
+            SyntaxValue::Quoted(_) => Err(SyntaxValidationError::QuotedNotAllowed { span }),
+            SyntaxValue::Plain(name) if Schema::from_symbol(name).is_none() => {
+                Err(SyntaxValidationError::UnknownType {
+                    name: name.clone(),
+                    span,
+                })
+            }
             SyntaxValue::Plain(_) => Ok(()),
+            SyntaxValue::Array(items) => match items.as_slice() {
+                [item] => Self::validate_value(item, span),
+                _ => Err(SyntaxValidationError::InvalidArrayType { span }),
+            },
+            SyntaxValue::Table(syntax) => syntax.patterns.iter().try_for_each(|pattern| {
+                Self::validate_key(&pattern.key_parts, pattern.key_span)?;
+                Self::validate_value(&pattern.value, pattern.value_span)
+            }),
+            SyntaxValue::Optional(inner) => Self::validate_value(inner, span),
         }
     }
 }
@@ -30,14 +161,48 @@ impl ValidationRule for SchemaRule {
 pub struct SyntaxValidator;
 
 impl SyntaxValidator {
-    pub fn validate<R: ValidationRule>(syntax: &Syntax) -> Result<(), SyntaxValidationError> {
-        Self::validate_duplicate_keys(syntax)?;
-        Self::validate_key_path_conflicts(syntax)?;
+    pub fn validate<R: ValidationRule>(syntax: &Syntax) -> Vec<SyntaxValidationError> {
+        let mut errors = Self::validate_structure(syntax);
 
         for pattern in &syntax.patterns {
-            R::validate_value(&pattern.value)?;
+            if let Err(error) = R::validate_key(&pattern.key_parts, pattern.key_span) {
+                errors.push(error);
+            }
+            if let Err(error) = R::validate_value(&pattern.value, pattern.value_span) {
+                errors.push(error);
+            }
         }
 
-        Ok(())
+        errors
+    }
+
+    /// The list indices of the top-level keys are left to the caller, which
+    /// knows what they index into.
+    fn validate_structure(syntax: &Syntax) -> Vec<SyntaxValidationError> {
+        let mut errors = Self::validate_duplicate_keys(syntax);
+        errors.extend(Self::validate_key_path_conflicts(syntax));
+
+        for pattern in &syntax.patterns {
+            Self::validate_nested_tables(&pattern.value, &mut errors);
+        }
+
+        errors
+    }
+
+    /// Inline tables inside arrays are not flattened into the surrounding
+    /// syntax, so their keys are checked on their own.
+    fn validate_nested_tables(value: &SyntaxValue, errors: &mut Vec<SyntaxValidationError>) {
+        match value {
+            SyntaxValue::Array(items) => {
+                for item in items {
+                    Self::validate_nested_tables(item, errors);
+                }
+            }
+            SyntaxValue::Table(syntax) => {
+                errors.extend(Self::validate_structure(syntax));
+                errors.extend(Self::validate_array_indices(syntax));
+            }
+            _ => {}
+        }
     }
 }
@@ -0,0 +1,182 @@
+use super::ConfigRule;
+use crate::syntax::{Syntax, SyntaxValue, display_key};
+use kernel::error::SyntaxValidationError;
+
+/// The top-level key whose tables are profiles.
+const PROFILE_KEY: &str = "profile";
+
+impl ConfigRule {
+    /// With the profile `production`, `profile.production.server.port = 443`
+    /// replaces `server.port`. A profile key naming a table, or a key below a
+    /// value, is reported as a conflict. The keys of every other profile are
+    /// dropped. Without a profile, `profile` is an ordinary key.
+    pub(super) fn select_profile(
+        syntax: &mut Syntax,
+        active: Option<&str>,
+        errors: &mut Vec<SyntaxValidationError>,
+    ) {
+        let Some(active) = active else {
+            return;
+        };
+
+        let (profiles, base): (Vec<_>, Vec<_>) = std::mem::take(&mut syntax.patterns)
+            .into_iter()
+            .partition(|pattern| pattern.key_parts[0] == PROFILE_KEY);
+        syntax.patterns = base;
+
+        for mut pattern in profiles {
+            if pattern.key_parts.len() <= 2 {
+                // `profile.name = {}` declares an empty profile.
+                if !matches!(pattern.value, SyntaxValue::Table(_)) {
+                    errors.push(SyntaxValidationError::InvalidProfileKey {
+                        key: display_key(&pattern.key_parts),
+                        span: pattern.key_span,
+                    });
+                }
+                continue;
+            }
+
+            if pattern.key_parts[1] == active {
+                pattern.key_parts.drain(..2);
+                errors.extend(syntax.replace(pattern).err());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::Pattern;
+    use kernel::span::Span;
+
+    fn pattern(key: &str, value: &str) -> Pattern {
+        Pattern {
+            key_parts: key.split('.').map(String::from).collect(),
+            value: SyntaxValue::Plain(value.to_string()),
+            key_span: Span::default(),
+            value_span: Span::default(),
+        }
+    }
+
+    fn syntax() -> Syntax {
+        Syntax {
+            patterns: vec![
+                pattern("server.host", "localhost"),
+                pattern("server.port", "8080"),
+                pattern("server.tls", "false"),
+                pattern("profile.production.server.port", "443"),
+                pattern("profile.production.server.tls", "true"),
+                pattern("profile.staging.server.host", "staging"),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_select_profile() {
+        let mut syntax = syntax();
+        let mut errors = Vec::new();
+        ConfigRule::select_profile(&mut syntax, Some("production"), &mut errors);
+
+        assert!(errors.is_empty());
+        assert_eq!(
+            syntax.patterns,
+            vec![
+                pattern("server.host", "localhost"),
+                pattern("server.port", "443"),
+                pattern("server.tls", "true"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_profile_conflicts() {
+        let mut syntax = Syntax {
+            patterns: vec![
+                pattern("server.tls", "false"),
+                pattern("db.pool.size", "5"),
+                pattern("profile.production.server.tls.cert", "cert.pem"),
+                pattern("profile.production.db.pool", "none"),
+            ],
+        };
+        let mut errors = Vec::new();
+        ConfigRule::select_profile(&mut syntax, Some("production"), &mut errors);
+
+        let keys: Vec<&str> = errors
+            .iter()
+            .map(|error| match error {
+                SyntaxValidationError::KeyPathConflict { key, .. } => key.as_str(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(keys, vec!["server.tls", "db.pool"]);
+        assert_eq!(
+            syntax.patterns,
+            vec![pattern("server.tls", "false"), pattern("db.pool.size", "5")]
+        );
+    }
+
+    #[test]
+    fn test_no_active_profile() {
+        let mut syntax = Syntax {
+            patterns: vec![
+                pattern("profile.name", "x"),
+                pattern("profile.avatar.url", "y"),
+            ],
+        };
+        let mut errors = Vec::new();
+        ConfigRule::select_profile(&mut syntax, None, &mut errors);
+
+        assert!(errors.is_empty());
+        assert_eq!(
+            syntax.patterns,
+            vec![
+                pattern("profile.name", "x"),
+                pattern("profile.avatar.url", "y")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_other_profiles_dropped() {
+        let mut syntax = syntax();
+        let mut errors = Vec::new();
+        ConfigRule::select_profile(&mut syntax, Some("development"), &mut errors);
+
+        assert!(errors.is_empty());
+        assert_eq!(
+            syntax.patterns,
+            vec![
+                pattern("server.host", "localhost"),
+                pattern("server.port", "8080"),
+                pattern("server.tls", "false"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_profile_key() {
+        let mut syntax = Syntax {
+            patterns: vec![
+                pattern("profile", "production"),
+                pattern("profile.production", "true"),
+                Pattern {
+                    value: SyntaxValue::Table(Syntax { patterns: vec![] }),
+                    ..pattern("profile.staging", "")
+                },
+            ],
+        };
+        let mut errors = Vec::new();
+        ConfigRule::select_profile(&mut syntax, Some("production"), &mut errors);
+
+        assert!(syntax.patterns.is_empty());
+        let keys: Vec<&str> = errors
+            .iter()
+            .map(|error| match error {
+                SyntaxValidationError::InvalidProfileKey { key, .. } => key.as_str(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(keys, vec!["profile", "profile.production"]);
+    }
+}
@@ -0,0 +1,619 @@
+use super::ConfigRule;
+use crate::syntax::{Pattern, Syntax, SyntaxValue, display_key};
+use kernel::env::Environment;
+use kernel::error::SyntaxValidationError;
+use kernel::span::Span;
+
+impl ConfigRule {
+    /// Replaces `${key}` references with the values they name and
+    /// `${env:NAME}` references with environment variables. A value that is a
+    /// single bare reference keeps the type of its target, and a variable is
+    /// typed like any other bare value; any other text is interpolated into a
+    /// string. Patterns whose references cannot be resolved are reported and
+    /// dropped, along with the patterns that depend on them.
+    pub(super) fn interpolate(
+        syntax: &mut Syntax,
+        env: &dyn Environment,
+        errors: &mut Vec<SyntaxValidationError>,
+    ) {
+        let mut resolver = Resolver::new(&mut syntax.patterns, env);
+        for index in 0..resolver.patterns.len() {
+            let _ = resolver.resolve(index);
+        }
+
+        let Resolver {
+            states,
+            errors: resolve_errors,
+            ..
+        } = resolver;
+        errors.extend(resolve_errors);
+
+        let mut states = states.into_iter();
+        syntax
+            .patterns
+            .retain(|_| states.next() != Some(State::Failed));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Pending,
+    InProgress,
+    Resolved,
+    Failed,
+}
+
+/// A reference could not be resolved; the error has already been recorded
+/// where the problem started.
+struct Unresolved;
+
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    Text(String),
+    Reference(&'a str),
+}
+
+struct Resolver<'a> {
+    patterns: &'a mut [Pattern],
+    env: &'a dyn Environment,
+    states: Vec<State>,
+    /// The patterns being resolved, outermost first, to name the keys of a cycle.
+    stack: Vec<usize>,
+    errors: Vec<SyntaxValidationError>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(patterns: &'a mut [Pattern], env: &'a dyn Environment) -> Self {
+        let states = vec![State::Pending; patterns.len()];
+        Self {
+            patterns,
+            env,
+            states,
+            stack: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn resolve(&mut self, index: usize) -> Result<(), Unresolved> {
+        match self.states[index] {
+            State::Resolved => return Ok(()),
+            State::Failed => return Err(Unresolved),
+            State::Pending | State::InProgress => {}
+        }
+
+        self.states[index] = State::InProgress;
+        self.stack.push(index);
+
+        let span = self.patterns[index].value_span;
+        let mut value = std::mem::replace(
+            &mut self.patterns[index].value,
+            SyntaxValue::Plain(String::new()),
+        );
+        let result = self.resolve_value(&mut value, span);
+        self.patterns[index].value = value;
+
+        self.stack.pop();
+        self.states[index] = match result {
+            Ok(()) => State::Resolved,
+            Err(Unresolved) => State::Failed,
+        };
+        result
+    }
+
+    fn resolve_value(&mut self, value: &mut SyntaxValue, span: Span) -> Result<(), Unresolved> {
+        match value {
+            SyntaxValue::Plain(text) | SyntaxValue::Quoted(text) if !text.contains("${") => Ok(()),
+            SyntaxValue::Plain(text) => {
+                *value = self.resolve_text(&text.clone(), true, span)?;
+                Ok(())
+            }
+            SyntaxValue::Quoted(text) => {
+                *value = self.resolve_text(&text.clone(), false, span)?;
+                Ok(())
+            }
+            SyntaxValue::Array(items) => items
+                .iter_mut()
+                .try_for_each(|item| self.resolve_value(item, span)),
+            SyntaxValue::Table(syntax) => syntax
+                .patterns
+                .iter_mut()
+                .try_for_each(|pattern| self.resolve_value(&mut pattern.value, pattern.value_span)),
+            SyntaxValue::Optional(inner) => self.resolve_value(inner, span),
+        }
+    }
+
+    fn resolve_text(
+        &mut self,
+        text: &str,
+        is_plain: bool,
+        span: Span,
+    ) -> Result<SyntaxValue, Unresolved> {
+        let segments = Self::segments(text);
+        if let (true, [Segment::Reference(reference)]) = (is_plain, segments.as_slice()) {
+            return self.lookup(reference, span);
+        }
+
+        let mut resolved = String::new();
+        for segment in segments {
+            match segment {
+                Segment::Text(text) => resolved.push_str(&text),
+                Segment::Reference(reference) => match self.lookup(reference, span)? {
+                    SyntaxValue::Plain(text) | SyntaxValue::Quoted(text) => {
+                        resolved.push_str(&text)
+                    }
+                    SyntaxValue::Array(_) | SyntaxValue::Table(_) | SyntaxValue::Optional(_) => {
+                        return Err(self.fail(SyntaxValidationError::InvalidReference {
+                            reference: reference.to_string(),
+                            span,
+                        }));
+                    }
+                },
+            }
+        }
+        Ok(SyntaxValue::Quoted(resolved))
+    }
+
+    /// Splits text into literal parts and references; `$${` is a literal `${`.
+    fn segments(text: &str) -> Vec<Segment<'_>> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut rest = text;
+
+        while let Some(c) = rest.chars().next() {
+            if let Some(after) = rest.strip_prefix("$${") {
+                literal.push_str("${");
+                rest = after;
+            } else if let Some((reference, after)) =
+                rest.strip_prefix("${").and_then(|r| r.split_once('}'))
+            {
+                if !literal.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut literal)));
+                }
+                segments.push(Segment::Reference(reference));
+                rest = after;
+            } else {
+                literal.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Text(literal));
+        }
+        segments
+    }
+
+    /// Splits a reference into key parts, where a part may be quoted to hold
+    /// dots, e.g. `routes."/api/v1".timeout`.
+    fn key_parts(reference: &str) -> Option<Vec<String>> {
+        let mut parts = Vec::new();
+        let mut rest = reference;
+        loop {
+            let (part, after) = match rest.chars().next() {
+                Some(quote @ ('"' | '\'')) => rest[1..].split_once(quote)?,
+                _ => {
+                    let end = rest.find('.').unwrap_or(rest.len());
+                    let part = &rest[..end];
+                    if part.is_empty() || part.contains(['"', '\'', ' ']) {
+                        return None;
+                    }
+                    (part, &rest[end..])
+                }
+            };
+            parts.push(part.to_string());
+
+            match after.strip_prefix('.') {
+                Some(next) => rest = next,
+                None if after.is_empty() => return Some(parts),
+                None => return None,
+            }
+        }
+    }
+
+    fn lookup(&mut self, reference: &str, span: Span) -> Result<SyntaxValue, Unresolved> {
+        let unresolved = || SyntaxValidationError::UnresolvedReference {
+            reference: reference.to_string(),
+            span,
+        };
+
+        if let Some(variable) = reference.strip_prefix("env:") {
+            return self.lookup_env(reference, variable, span);
+        }
+
+        let Some(path) = Self::key_parts(reference) else {
+            return Err(self.fail(SyntaxValidationError::InvalidReference {
+                reference: reference.to_string(),
+                span,
+            }));
+        };
+
+        if let Some(index) = self
+            .patterns
+            .iter()
+            .position(|pattern| path.starts_with(&pattern.key_parts))
+        {
+            self.enter(index, span)?;
+            let pattern = &self.patterns[index];
+            return match Self::descend(&pattern.value, &path[pattern.key_parts.len()..]) {
+                Some(value) => Ok(value),
+                None => Err(self.fail(unresolved())),
+            };
+        }
+
+        let nested: Vec<usize> = (0..self.patterns.len())
+            .filter(|&index| Self::is_nested(&self.patterns[index], &path))
+            .collect();
+        if nested.is_empty() {
+            return Err(self.fail(unresolved()));
+        }
+        for &index in &nested {
+            self.enter(index, span)?;
+        }
+        Ok(Self::subtree(
+            nested.into_iter().map(|index| &self.patterns[index]),
+            &path,
+        ))
+    }
+
+    /// `NAME` or `NAME:-default`, where the default also replaces an empty
+    /// variable.
+    fn lookup_env(
+        &mut self,
+        reference: &str,
+        variable: &str,
+        span: Span,
+    ) -> Result<SyntaxValue, Unresolved> {
+        let (name, default) = match variable.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (variable, None),
+        };
+
+        let is_valid_name =
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !is_valid_name {
+            return Err(self.fail(SyntaxValidationError::InvalidReference {
+                reference: reference.to_string(),
+                span,
+            }));
+        }
+
+        match (self.env.var(name), default) {
+            (Some(value), Some(default)) if value.is_empty() => {
+                Ok(SyntaxValue::Plain(default.to_string()))
+            }
+            (Some(value), _) => Ok(SyntaxValue::Plain(value)),
+            (None, Some(default)) => Ok(SyntaxValue::Plain(default.to_string())),
+            (None, None) => Err(self.fail(SyntaxValidationError::UndefinedVariable {
+                name: name.to_string(),
+                span,
+            })),
+        }
+    }
+
+    /// Resolves the pattern a reference points to, or reports the cycle if it
+    /// is still being resolved further up.
+    fn enter(&mut self, index: usize, span: Span) -> Result<(), Unresolved> {
+        if self.states[index] != State::InProgress {
+            return self.resolve(index);
+        }
+
+        let start = self.stack.iter().position(|&i| i == index).unwrap_or(0);
+        let chain = self.stack[start..]
+            .iter()
+            .chain([&index])
+            .map(|&i| display_key(&self.patterns[i].key_parts))
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        Err(self.fail(SyntaxValidationError::CircularReference { chain, span }))
+    }
+
+    fn fail(&mut self, error: SyntaxValidationError) -> Unresolved {
+        self.errors.push(error);
+        Unresolved
+    }
+
+    /// The part of an already resolved value that `path` names.
+    fn descend(value: &SyntaxValue, path: &[String]) -> Option<SyntaxValue> {
+        let Some((first, rest)) = path.split_first() else {
+            return Some(value.clone());
+        };
+
+        match value {
+            SyntaxValue::Array(items) => {
+                let item = items.get(first.parse::<usize>().ok()?)?;
+                Self::descend(item, rest)
+            }
+            SyntaxValue::Table(syntax) => {
+                if let Some(pattern) = syntax
+                    .patterns
+                    .iter()
+                    .find(|pattern| path.starts_with(&pattern.key_parts))
+                {
+                    return Self::descend(&pattern.value, &path[pattern.key_parts.len()..]);
+                }
+
+                let mut nested = syntax
+                    .patterns
+                    .iter()
+                    .filter(|pattern| Self::is_nested(pattern, path))
+                    .peekable();
+                nested.peek()?;
+                Some(Self::subtree(nested, path))
+            }
+            SyntaxValue::Plain(_) | SyntaxValue::Quoted(_) | SyntaxValue::Optional(_) => None,
+        }
+    }
+
+    fn is_nested(pattern: &Pattern, path: &[String]) -> bool {
+        pattern.key_parts.len() > path.len() && pattern.key_parts.starts_with(path)
+    }
+
+    /// A table of the patterns below `path`, e.g. `${server}` for the keys
+    /// `server.host` and `server.port`.
+    fn subtree<'p>(patterns: impl Iterator<Item = &'p Pattern>, path: &[String]) -> SyntaxValue {
+        SyntaxValue::Table(Syntax {
+            patterns: patterns
+                .map(|pattern| Pattern {
+                    key_parts: pattern.key_parts[path.len()..].to_vec(),
+                    ..pattern.clone()
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kernel::env::ProcessEnvironment;
+    use std::collections::HashMap;
+
+    fn pattern(key: &str, value: SyntaxValue) -> Pattern {
+        Pattern {
+            key_parts: key.split('.').map(String::from).collect(),
+            value,
+            key_span: Span::default(),
+            value_span: Span::default(),
+        }
+    }
+
+    fn plain(value: &str) -> SyntaxValue {
+        SyntaxValue::Plain(value.to_string())
+    }
+
+    fn quoted(value: &str) -> SyntaxValue {
+        SyntaxValue::Quoted(value.to_string())
+    }
+
+    fn interpolate(patterns: Vec<Pattern>) -> (Syntax, Vec<SyntaxValidationError>) {
+        let mut syntax = Syntax { patterns };
+        let mut errors = Vec::new();
+        ConfigRule::interpolate(&mut syntax, &ProcessEnvironment, &mut errors);
+        (syntax, errors)
+    }
+
+    fn interpolate_with_env(patterns: Vec<Pattern>) -> (Syntax, Vec<SyntaxValidationError>) {
+        let env: HashMap<String, String> = [("PORT", "9000"), ("HOST", "db"), ("EMPTY", "")]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        let mut syntax = Syntax { patterns };
+        let mut errors = Vec::new();
+        ConfigRule::interpolate(&mut syntax, &env, &mut errors);
+        (syntax, errors)
+    }
+
+    fn value_of<'s>(syntax: &'s Syntax, key: &str) -> Option<&'s SyntaxValue> {
+        syntax
+            .patterns
+            .iter()
+            .find(|pattern| pattern.key_parts.join(".") == key)
+            .map(|pattern| &pattern.value)
+    }
+
+    #[test]
+    fn test_segments() {
+        assert_eq!(
+            Resolver::segments("${app.home}/logs"),
+            vec![
+                Segment::Reference("app.home"),
+                Segment::Text("/logs".into())
+            ]
+        );
+        assert_eq!(
+            Resolver::segments("$${literal} and ${a}"),
+            vec![
+                Segment::Text("${literal} and ".into()),
+                Segment::Reference("a")
+            ]
+        );
+        assert_eq!(
+            Resolver::segments("cost: $5, ${unclosed"),
+            vec![Segment::Text("cost: $5, ${unclosed".into())]
+        );
+    }
+
+    #[test]
+    fn test_key_parts() {
+        assert_eq!(
+            Resolver::key_parts("app.home"),
+            Some(vec!["app".into(), "home".into()])
+        );
+        assert_eq!(
+            Resolver::key_parts("routes.\"/api/v1\".timeout"),
+            Some(vec!["routes".into(), "/api/v1".into(), "timeout".into()])
+        );
+        assert_eq!(Resolver::key_parts(""), None);
+        assert_eq!(Resolver::key_parts("a..b"), None);
+        assert_eq!(Resolver::key_parts("a.\"b"), None);
+    }
+
+    mod valid {
+        use super::*;
+
+        #[test]
+        fn test_environment_variables() {
+            let (syntax, errors) = interpolate_with_env(vec![
+                pattern("port", plain("${env:PORT}")),
+                pattern("fallback", plain("${env:MISSING:-8080}")),
+                pattern("empty", plain("${env:EMPTY:-none}")),
+                pattern("set", plain("${env:EMPTY}")),
+                pattern("url", quoted("${env:HOST}:${port}")),
+            ]);
+
+            assert!(errors.is_empty());
+            assert_eq!(value_of(&syntax, "port"), Some(&plain("9000")));
+            assert_eq!(value_of(&syntax, "fallback"), Some(&plain("8080")));
+            assert_eq!(value_of(&syntax, "empty"), Some(&plain("none")));
+            assert_eq!(value_of(&syntax, "set"), Some(&plain("")));
+            assert_eq!(value_of(&syntax, "url"), Some(&quoted("db:9000")));
+        }
+
+        #[test]
+        fn test_whole_value_keeps_type() {
+            let (syntax, errors) = interpolate(vec![
+                pattern("replica.port", plain("${primary.port}")),
+                pattern("primary.port", plain("5432")),
+                pattern("replica.hosts", plain("${primary.hosts}")),
+                pattern(
+                    "primary.hosts",
+                    SyntaxValue::Array(vec![plain("a"), quoted("b")]),
+                ),
+            ]);
+
+            assert!(errors.is_empty());
+            assert_eq!(value_of(&syntax, "replica.port"), Some(&plain("5432")));
+            assert_eq!(
+                value_of(&syntax, "replica.hosts"),
+                Some(&SyntaxValue::Array(vec![plain("a"), quoted("b")]))
+            );
+        }
+
+        #[test]
+        fn test_interpolation() {
+            let (syntax, errors) = interpolate(vec![
+                pattern("app.home", quoted("/srv/app")),
+                pattern("app.port", plain("80")),
+                pattern("log.dir", quoted("${app.home}/logs")),
+                pattern("log.port", quoted("${app.port}")),
+                pattern("url", plain("http://${app.home}:${app.port}")),
+                pattern("literal", quoted("$${app.home}")),
+            ]);
+
+            assert!(errors.is_empty());
+            assert_eq!(value_of(&syntax, "log.dir"), Some(&quoted("/srv/app/logs")));
+            assert_eq!(value_of(&syntax, "log.port"), Some(&quoted("80")));
+            assert_eq!(
+                value_of(&syntax, "url"),
+                Some(&quoted("http:///srv/app:80"))
+            );
+            assert_eq!(value_of(&syntax, "literal"), Some(&quoted("${app.home}")));
+        }
+
+        #[test]
+        fn test_reference_chains_and_subtrees() {
+            let (syntax, errors) = interpolate(vec![
+                pattern("c", plain("${b}")),
+                pattern("b", plain("${a}")),
+                pattern("a", plain("1")),
+                pattern("copy", plain("${server}")),
+                pattern("server.host", plain("localhost")),
+                pattern("server.port", plain("${a}")),
+                pattern("first", plain("${list.0.name}")),
+                pattern(
+                    "list",
+                    SyntaxValue::Array(vec![SyntaxValue::Table(Syntax {
+                        patterns: vec![pattern("name", quoted("x"))],
+                    })]),
+                ),
+            ]);
+
+            assert!(errors.is_empty());
+            assert_eq!(value_of(&syntax, "c"), Some(&plain("1")));
+            assert_eq!(value_of(&syntax, "first"), Some(&quoted("x")));
+            assert_eq!(
+                value_of(&syntax, "copy"),
+                Some(&SyntaxValue::Table(Syntax {
+                    patterns: vec![
+                        pattern("host", plain("localhost")),
+                        pattern("port", plain("1")),
+                    ],
+                }))
+            );
+        }
+    }
+
+    mod invalid {
+        use super::*;
+
+        #[test]
+        fn test_undefined_variable() {
+            let (syntax, errors) = interpolate_with_env(vec![
+                pattern("password", plain("${env:DB_PASSWORD}")),
+                pattern("url", quoted("${env:HOST}/${env:}")),
+            ]);
+
+            assert!(syntax.patterns.is_empty());
+            assert!(matches!(
+                errors.as_slice(),
+                [
+                    SyntaxValidationError::UndefinedVariable { name, .. },
+                    SyntaxValidationError::InvalidReference { reference, .. },
+                ] if name == "DB_PASSWORD" && reference == "env:"
+            ));
+        }
+
+        #[test]
+        fn test_unresolved_reference() {
+            let (syntax, errors) = interpolate(vec![
+                pattern("a", plain("${missing}")),
+                pattern("b", quoted("${a}/x")),
+                pattern("c", plain("1")),
+            ]);
+
+            assert_eq!(syntax.patterns.len(), 1);
+            assert!(matches!(
+                errors.as_slice(),
+                [SyntaxValidationError::UnresolvedReference { reference, .. }]
+                    if reference == "missing"
+            ));
+        }
+
+        #[test]
+        fn test_circular_reference() {
+            let (syntax, errors) = interpolate(vec![
+                pattern("a", plain("${b}")),
+                pattern("b", quoted("x${c}")),
+                pattern("c", plain("${a}")),
+                pattern("self", plain("${self}")),
+            ]);
+
+            assert!(syntax.patterns.is_empty());
+            assert!(matches!(
+                errors.as_slice(),
+                [
+                    SyntaxValidationError::CircularReference { chain, .. },
+                    SyntaxValidationError::CircularReference { chain: self_chain, .. },
+                ] if chain == "a -> b -> c -> a" && self_chain == "self -> self"
+            ));
+        }
+
+        #[test]
+        fn test_invalid_reference() {
+            let (syntax, errors) = interpolate(vec![
+                pattern("list", SyntaxValue::Array(vec![plain("1")])),
+                pattern("text", quoted("items: ${list}")),
+                pattern("empty", quoted("${}")),
+            ]);
+
+            assert_eq!(syntax.patterns.len(), 1);
+            assert!(matches!(
+                errors.as_slice(),
+                [
+                    SyntaxValidationError::InvalidReference { reference: list, .. },
+                    SyntaxValidationError::InvalidReference { reference: empty, .. },
+                ] if list == "list" && empty.is_empty()
+            ));
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use super::TokenValidator;
+use Token::*;
+use kernel::error::{TokenValidationError as BaseError, ValueError};
+use kernel::span::Span;
+use kernel::token::{SpannedToken, Token};
+
+#[derive(Clone, Copy)]
+enum Delimiter {
+    Array,
+    Table,
+}
+
+impl Delimiter {
+    fn close(self) -> Token {
+        match self {
+            Delimiter::Array => RightBracket,
+            Delimiter::Table => RightBrace,
+        }
+    }
+
+    fn unterminated(self, span: Span) -> BaseError {
+        BaseError::Value(match self {
+            Delimiter::Array => ValueError::UnterminatedArray { span },
+            Delimiter::Table => ValueError::UnterminatedInlineTable { span },
+        })
+    }
+
+    fn empty_item(self, span: Span) -> BaseError {
+        BaseError::Value(match self {
+            Delimiter::Array => ValueError::EmptyArrayElement { span },
+            Delimiter::Table => ValueError::EmptyInlineTableEntry { span },
+        })
+    }
+}
+
+impl TokenValidator {
+    pub fn is_array(tokens: &[SpannedToken]) -> bool {
+        matches!(tokens.first(), Some(t) if t.token == LeftBracket)
+    }
+
+    pub fn is_table(tokens: &[SpannedToken]) -> bool {
+        matches!(tokens.first(), Some(t) if t.token == LeftBrace)
+    }
+
+    /// The array of an optional array type such as `[integer]?`, whose `?`
+    /// directly follows the closing bracket.
+    pub fn optional_array(tokens: &[SpannedToken]) -> Option<&[SpannedToken]> {
+        match tokens {
+            [array @ .., close, mark]
+                if Self::is_array(array)
+                    && close.token == RightBracket
+                    && mark.token == Ident("?".into())
+                    && mark.span.start == close.span.end =>
+            {
+                Some(&tokens[..tokens.len() - 1])
+            }
+            _ => None,
+        }
+    }
+
+    pub fn validate_array(tokens: &[SpannedToken]) -> Result<(), BaseError> {
+        for element in Self::split_array(tokens)? {
+            Self::validate_value(element)?;
+        }
+
+        Ok(())
+    }
+
+    /// Only the braces and commas are checked here; each entry is a
+    /// `key = value` line of its own and is validated when it is analyzed.
+    pub fn validate_table(tokens: &[SpannedToken]) -> Result<(), BaseError> {
+        Self::split_table(tokens).map(|_| ())
+    }
+
+    pub fn split_array(tokens: &[SpannedToken]) -> Result<Vec<&[SpannedToken]>, BaseError> {
+        Self::split_delimited(tokens, Delimiter::Array)
+    }
+
+    pub fn split_table(tokens: &[SpannedToken]) -> Result<Vec<&[SpannedToken]>, BaseError> {
+        Self::split_delimited(tokens, Delimiter::Table)
+    }
+
+    pub(super) fn nesting_change(token: &Token) -> i32 {
+        match token {
+            LeftBracket | LeftBrace => 1,
+            RightBracket | RightBrace => -1,
+            _ => 0,
+        }
+    }
+
+    fn split_delimited(
+        tokens: &[SpannedToken],
+        delimiter: Delimiter,
+    ) -> Result<Vec<&[SpannedToken]>, BaseError> {
+        let close = Self::closing_position(tokens, delimiter)?;
+
+        if close + 1 < tokens.len() {
+            return Err(BaseError::Value(ValueError::InvalidValueFormat {
+                span: Self::span_of(&tokens[close + 1..]),
+            }));
+        }
+
+        let inner = &tokens[1..close];
+        if inner.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut items = Vec::new();
+        let mut depth = 0;
+        let mut start = 0;
+
+        for (pos, token) in inner.iter().enumerate() {
+            depth += Self::nesting_change(&token.token);
+
+            if depth == 0 && token.token == Comma {
+                if pos == start {
+                    return Err(delimiter.empty_item(token.span));
+                }
+                items.push(&inner[start..pos]);
+                start = pos + 1;
+            }
+        }
+
+        if start == inner.len() {
+            return Err(delimiter.empty_item(inner[start - 1].span));
+        }
+        items.push(&inner[start..]);
+
+        Ok(items)
+    }
+
+    fn closing_position(tokens: &[SpannedToken], delimiter: Delimiter) -> Result<usize, BaseError> {
+        let mut depth = 0;
+
+        for (pos, token) in tokens.iter().enumerate() {
+            depth += Self::nesting_change(&token.token);
+
+            if depth == 0 {
+                return if token.token == delimiter.close() {
+                    Ok(pos)
+                } else {
+                    Err(delimiter.unterminated(Self::span_of(&tokens[..=pos])))
+                };
+            }
+        }
+
+        Err(delimiter.unterminated(Self::span_of(tokens)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validator::token::spanned;
+
+    mod valid {
+        use super::*;
+
+        #[test]
+        fn test_split_array() {
+            let tokens = spanned(vec![
+                LeftBracket,
+                Ident("a".into()),
+                Comma,
+                QuotedIdent("b.example.com".into()),
+                Comma,
+                Ident("1".into()),
+                Dot,
+                Ident("5".into()),
+                RightBracket,
+            ]);
+
+            let elements = TokenValidator::split_array(&tokens).unwrap();
+            assert_eq!(elements.len(), 3);
+            assert_eq!(elements[0], [Ident("a".into())]);
+            assert_eq!(elements[1], [QuotedIdent("b.example.com".into())]);
+            assert_eq!(elements[2], [Ident("1".into()), Dot, Ident("5".into())]);
+        }
+
+        #[test]
+        fn test_split_empty_array() {
+            let tokens = spanned(vec![LeftBracket, RightBracket]);
+            assert!(TokenValidator::split_array(&tokens).unwrap().is_empty());
+        }
+
+        #[test]
+        fn test_split_table() {
+            let tokens = spanned(vec![
+                LeftBrace,
+                Ident("host".into()),
+                Separator,
+                QuotedIdent("db1".into()),
+                Comma,
+                Ident("ports".into()),
+                Separator,
+                LeftBracket,
+                Ident("1".into()),
+                Comma,
+                Ident("2".into()),
+                RightBracket,
+                RightBrace,
+            ]);
+
+            let entries = TokenValidator::split_table(&tokens).unwrap();
+            assert_eq!(entries.len(), 2);
+            assert_eq!(
+                entries[0],
+                [Ident("host".into()), Separator, QuotedIdent("db1".into())]
+            );
+            assert_eq!(entries[1].len(), 7);
+        }
+
+        #[test]
+        fn test_split_nested_array() {
+            let tokens = spanned(vec![
+                LeftBracket,
+                LeftBracket,
+                Ident("a".into()),
+                Comma,
+                Ident("b".into()),
+                RightBracket,
+                Comma,
+                LeftBracket,
+                RightBracket,
+                RightBracket,
+            ]);
+
+            let elements = TokenValidator::split_array(&tokens).unwrap();
+            assert_eq!(elements.len(), 2);
+            assert_eq!(elements[1], [LeftBracket, RightBracket]);
+            assert!(TokenValidator::validate_array(&tokens).is_ok());
+        }
+    }
+
+    mod invalid {
+        use super::*;
+
+        #[test]
+        fn test_unterminated_array() {
+            let tokens = spanned(vec![
+                LeftBracket,
+                Ident("a".into()),
+                Comma,
+                Ident("b".into()),
+            ]);
+            assert!(matches!(
+                TokenValidator::validate_array(&tokens),
+                Err(BaseError::Value(ValueError::UnterminatedArray { .. }))
+            ));
+        }
+
+        #[test]
+        fn test_unterminated_table() {
+            let tokens = spanned(vec![
+                LeftBrace,
+                Ident("a".into()),
+                Separator,
+                Ident("b".into()),
+            ]);
+            assert!(matches!(
+                TokenValidator::validate_table(&tokens),
+                Err(BaseError::Value(ValueError::UnterminatedInlineTable { .. }))
+            ));
+
+            let tokens = spanned(vec![LeftBrace, Ident("a".into()), RightBracket]);
+            assert!(matches!(
+                TokenValidator::validate_table(&tokens),
+                Err(BaseError::Value(ValueError::UnterminatedInlineTable { .. }))
+            ));
+        }
+
+        #[test]
+        fn test_empty_array_element() {
+            let tokens = spanned(vec![
+                LeftBracket,
+                Ident("a".into()),
+                Comma,
+                Comma,
+                Ident("b".into()),
+                RightBracket,
+            ]);
+            assert!(matches!(
+                TokenValidator::validate_array(&tokens),
+                Err(BaseError::Value(ValueError::EmptyArrayElement { .. }))
+            ));
+
+            let tokens = spanned(vec![LeftBracket, Ident("a".into()), Comma, RightBracket]);
+            assert!(matches!(
+                TokenValidator::validate_array(&tokens),
+                Err(BaseError::Value(ValueError::EmptyArrayElement { .. }))
+            ));
+        }
+
+        #[test]
+        fn test_tokens_after_array() {
+            let tokens = spanned(vec![
+                LeftBracket,
+                Ident("a".into()),
+                RightBracket,
+                Ident("b".into()),
+            ]);
+            assert!(matches!(
+                TokenValidator::validate_array(&tokens),
+                Err(BaseError::Value(ValueError::InvalidValueFormat { .. }))
+            ));
+        }
+
+        #[test]
+        fn test_invalid_element() {
+            let tokens = spanned(vec![
+                LeftBracket,
+                Ident("a".into()),
+                Ident("b".into()),
+                RightBracket,
+            ]);
+            assert!(matches!(
+                TokenValidator::validate_array(&tokens),
+                Err(BaseError::Value(
+                    ValueError::MultipleNonNumericIdents { .. }
+                ))
+            ));
+        }
+    }
+}
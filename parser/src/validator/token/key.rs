@@ -1,30 +1,43 @@
 use super::TokenValidator;
 use kernel::error::{KeyError, TokenValidationError};
-use kernel::token::Token;
+use kernel::span::Span;
+use kernel::token::{SpannedToken, Token};
 
 use Token::*;
 use TokenValidationError as BaseError;
 
 impl TokenValidator {
-    pub fn validate_key(tokens: &[Token]) -> Result<(), BaseError> {
+    pub fn validate_key(tokens: &[SpannedToken]) -> Result<(), BaseError> {
         let mut iter = tokens.iter();
+        let mut is_first = true;
         loop {
-            let key_part = match iter.next() {
-                Some(Ident(name)) => name,
-                _ => {
-                    return Err(BaseError::Key(KeyError::UnexpectedTokenInKey));
+            match iter.next() {
+                Some(SpannedToken {
+                    token: Ident(key_part),
+                    span,
+                }) => {
+                    Self::validate_no_reference(key_part, *span)?;
+                    Self::validate_start_with_hyphen(key_part, *span)?;
+                    Self::validate_ends_with_hyphen(key_part, *span)?;
+                    if is_first || !Self::is_index(key_part) {
+                        Self::validate_first_char_is_not_numeric(key_part, *span)?;
+                    }
                 }
-            };
-
-            Self::validate_start_with_hyphen(key_part)?;
-            Self::validate_ends_with_hyphen(key_part)?;
-            Self::validate_first_char_is_not_numeric(key_part)?;
+                Some(SpannedToken {
+                    token: QuotedIdent(key_part),
+                    span,
+                }) => Self::validate_quoted_key_part(key_part, *span)?,
+                other => {
+                    return Err(Self::unexpected_token_in_key(other, tokens));
+                }
+            }
+            is_first = false;
 
             match iter.next() {
-                Some(Dot) => continue,
+                Some(SpannedToken { token: Dot, .. }) => continue,
                 None => break,
-                _ => {
-                    return Err(BaseError::Key(KeyError::UnexpectedTokenInKey));
+                other => {
+                    return Err(Self::unexpected_token_in_key(other, tokens));
                 }
             }
         }
@@ -32,28 +45,66 @@ impl TokenValidator {
         Ok(())
     }
 
-    fn validate_start_with_hyphen(key_part: &str) -> Result<(), BaseError> {
+    /// A list index such as the `0` in `servers.0.host`.
+    fn is_index(key_part: &str) -> bool {
+        key_part.chars().all(|c| c.is_ascii_digit())
+            && (key_part == "0" || !key_part.starts_with('0'))
+    }
+
+    fn unexpected_token_in_key(token: Option<&SpannedToken>, tokens: &[SpannedToken]) -> BaseError {
+        let span = token.map_or_else(|| Self::span_of(tokens), |token| token.span);
+        BaseError::Key(KeyError::UnexpectedTokenInKey { span })
+    }
+
+    /// `${...}` is only resolved in values; keys must be known before any
+    /// reference can be looked up.
+    fn validate_no_reference(key_part: &str, span: Span) -> Result<(), BaseError> {
+        if key_part.contains("${") {
+            return Err(BaseError::Key(KeyError::ReferenceInKey {
+                key_part: key_part.to_string(),
+                span,
+            }));
+        }
+        Ok(())
+    }
+
+    fn validate_start_with_hyphen(key_part: &str, span: Span) -> Result<(), BaseError> {
         if key_part.starts_with('-') {
             return Err(BaseError::Key(KeyError::InvalidKeyStartsWithHyphen {
                 key_part: key_part.to_string(),
+                span,
             }));
         }
         Ok(())
     }
 
-    fn validate_ends_with_hyphen(key_part: &str) -> Result<(), BaseError> {
+    fn validate_ends_with_hyphen(key_part: &str, span: Span) -> Result<(), BaseError> {
         if key_part.ends_with('-') {
             return Err(BaseError::Key(KeyError::InvalidKeyEndsWithHyphen {
                 key_part: key_part.to_string(),
+                span,
+            }));
+        }
+        Ok(())
+    }
+
+    /// A quoted part such as `"/api/v1"` is taken literally, so it may hold
+    /// dots or spaces, but must not be empty or look like a list index.
+    fn validate_quoted_key_part(key_part: &str, span: Span) -> Result<(), BaseError> {
+        if key_part.is_empty() || key_part.chars().all(|c| c.is_ascii_digit()) {
+            return Err(BaseError::Key(KeyError::InvalidQuotedKey {
+                key_part: key_part.to_string(),
+                span,
             }));
         }
         Ok(())
     }
 
-    fn validate_first_char_is_not_numeric(key_part: &str) -> Result<(), BaseError> {
-        if key_part.chars().next().is_some_and(|c| c.is_numeric()) {
+    fn validate_first_char_is_not_numeric(key_part: &str, span: Span) -> Result<(), BaseError> {
+        if key_part.chars().next().is_some_and(|c| c.is_ascii_digit()) {
             return Err(BaseError::Key(KeyError::KeyCannotBeNumeric {
                 key_part: key_part.to_string(),
+                span,
             }));
         }
         Ok(())
@@ -63,22 +114,23 @@ impl TokenValidator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::validator::token::spanned;
     use TokenValidationError::Key as KeyError;
     use kernel::error::{KeyError as KE, TokenValidationError};
 
     #[test]
     fn test_valid() {
         let tokens = vec![Ident("key".to_string())];
-        assert!(TokenValidator::validate_key(&tokens).is_ok());
+        assert!(TokenValidator::validate_key(&spanned(tokens)).is_ok());
 
         let tokens_with_hyphen = vec![Ident("key-subkey".to_string())];
-        assert!(TokenValidator::validate_key(&tokens_with_hyphen).is_ok());
+        assert!(TokenValidator::validate_key(&spanned(tokens_with_hyphen)).is_ok());
 
         let tokens_with_numeric = vec![Ident("key123".to_string())];
-        assert!(TokenValidator::validate_key(&tokens_with_numeric).is_ok());
+        assert!(TokenValidator::validate_key(&spanned(tokens_with_numeric)).is_ok());
 
         let tokens_with_dot = vec![Ident("key".to_string()), Dot, Ident("subkey".to_string())];
-        assert!(TokenValidator::validate_key(&tokens_with_dot).is_ok());
+        assert!(TokenValidator::validate_key(&spanned(tokens_with_dot)).is_ok());
 
         let tokens_with_multiple_dots = vec![
             Ident("key".to_string()),
@@ -87,7 +139,30 @@ mod tests {
             Dot,
             Ident("subsubkey".to_string()),
         ];
-        assert!(TokenValidator::validate_key(&tokens_with_multiple_dots).is_ok());
+        assert!(TokenValidator::validate_key(&spanned(tokens_with_multiple_dots)).is_ok());
+
+        let tokens_with_index = vec![
+            Ident("servers".to_string()),
+            Dot,
+            Ident("10".to_string()),
+            Dot,
+            Ident("host".to_string()),
+        ];
+        assert!(TokenValidator::validate_key(&spanned(tokens_with_index)).is_ok());
+
+        let tokens_with_quoted = vec![
+            Ident("labels".to_string()),
+            Dot,
+            QuotedIdent("app.kubernetes.io/name".to_string()),
+        ];
+        assert!(TokenValidator::validate_key(&spanned(tokens_with_quoted)).is_ok());
+
+        let tokens_with_quoted_first = vec![
+            QuotedIdent("-weird key-".to_string()),
+            Dot,
+            Ident("value".to_string()),
+        ];
+        assert!(TokenValidator::validate_key(&spanned(tokens_with_quoted_first)).is_ok());
     }
 
     mod invalid {
@@ -96,7 +171,7 @@ mod tests {
         #[test]
         fn test_key_starts_with_hyphen() {
             let tokens = vec![Ident("-key".to_string())];
-            let result = TokenValidator::validate_key(&tokens);
+            let result = TokenValidator::validate_key(&spanned(tokens));
             assert!(matches!(
                 result,
                 Err(KeyError(KE::InvalidKeyStartsWithHyphen { .. }))
@@ -106,7 +181,7 @@ mod tests {
         #[test]
         fn test_key_with_trailing_hyphen() {
             let tokens = vec![Ident("key-".to_string())];
-            let result = TokenValidator::validate_key(&tokens);
+            let result = TokenValidator::validate_key(&spanned(tokens));
             assert!(matches!(
                 result.unwrap_err(),
                 KeyError(KE::InvalidKeyEndsWithHyphen { .. })
@@ -116,43 +191,90 @@ mod tests {
         #[test]
         fn test_dot_after_dot() {
             let tokens = vec![Ident("key".to_string()), Dot, Dot];
-            let result = TokenValidator::validate_key(&tokens);
+            let result = TokenValidator::validate_key(&spanned(tokens));
             assert!(matches!(
                 result.unwrap_err(),
-                KeyError(KE::UnexpectedTokenInKey)
+                KeyError(KE::UnexpectedTokenInKey { .. })
             ));
         }
 
         #[test]
         fn test_unexpected_token_in_key() {
             let tokens = vec![Ident("key".to_string()), Ident("subkey".to_string())];
-            let result = TokenValidator::validate_key(&tokens);
+            let result = TokenValidator::validate_key(&spanned(tokens));
             assert!(matches!(
                 result.unwrap_err(),
-                KeyError(KE::UnexpectedTokenInKey)
+                KeyError(KE::UnexpectedTokenInKey { .. })
             ));
         }
 
         #[test]
         fn test_key_with_trailing_dot() {
             let tokens = vec![Ident("key".to_string()), Dot];
-            let result = TokenValidator::validate_key(&tokens);
+            let result = TokenValidator::validate_key(&spanned(tokens));
             assert!(result.is_err());
             assert!(matches!(
                 result.unwrap_err(),
-                KeyError(KE::UnexpectedTokenInKey)
+                KeyError(KE::UnexpectedTokenInKey { .. })
             ));
         }
 
         #[test]
         fn test_numeric_key() {
             let tokens = vec![Ident("123".to_string())];
-            let result = TokenValidator::validate_key(&tokens);
+            let result = TokenValidator::validate_key(&spanned(tokens));
             assert!(matches!(
                 result.unwrap_err(),
-                KeyError(KE::KeyCannotBeNumeric { key_part })
+                KeyError(KE::KeyCannotBeNumeric { key_part, .. })
                 if key_part == "123"
             ));
         }
+
+        #[test]
+        fn test_invalid_index_key() {
+            let tokens = vec![Ident("servers".to_string()), Dot, Ident("01".to_string())];
+            let result = TokenValidator::validate_key(&spanned(tokens));
+            assert!(matches!(
+                result.unwrap_err(),
+                KeyError(KE::KeyCannotBeNumeric { key_part, .. })
+                if key_part == "01"
+            ));
+
+            let tokens = vec![Ident("servers".to_string()), Dot, Ident("1st".to_string())];
+            let result = TokenValidator::validate_key(&spanned(tokens));
+            assert!(matches!(
+                result.unwrap_err(),
+                KeyError(KE::KeyCannotBeNumeric { key_part, .. })
+                if key_part == "1st"
+            ));
+        }
+
+        #[test]
+        fn test_invalid_quoted_key() {
+            for key_part in ["", "0", "123"] {
+                let tokens = vec![
+                    Ident("servers".to_string()),
+                    Dot,
+                    QuotedIdent(key_part.to_string()),
+                ];
+                let result = TokenValidator::validate_key(&spanned(tokens));
+                assert!(matches!(
+                    result.unwrap_err(),
+                    KeyError(KE::InvalidQuotedKey { key_part: part, .. })
+                    if part == key_part
+                ));
+            }
+        }
+
+        #[test]
+        fn test_reference_in_key() {
+            let tokens = vec![Ident("${env}".to_string()), Dot, Ident("port".to_string())];
+            let result = TokenValidator::validate_key(&spanned(tokens));
+            assert!(matches!(
+                result.unwrap_err(),
+                KeyError(KE::ReferenceInKey { key_part, .. })
+                if key_part == "${env}"
+            ));
+        }
     }
 }
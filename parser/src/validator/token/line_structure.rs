@@ -3,71 +3,104 @@ use LineStructureError as LineError;
 use Token::*;
 use TokenValidationError as BaseError;
 use kernel::error::{LineStructureError, TokenValidationError};
-use kernel::token::Token;
+use kernel::token::{SpannedToken, Token};
 
 impl TokenValidator {
-    pub fn validate_line_structure(tokens: &[Token]) -> Result<(), BaseError> {
+    pub fn validate_line_structure(tokens: &[SpannedToken]) -> Result<(), BaseError> {
         Self::validate_equal_count(tokens)?;
 
-        let (left_side, right_side) = Self::split_side_by_side(tokens)?;
+        let (left_side, separator, right_side) = Self::split_side_by_side(tokens)?;
 
-        Self::validate_left_side_last(left_side)?;
+        Self::validate_left_side_last(left_side, separator)?;
         Self::validate_right_side_last(right_side)?;
 
         Ok(())
     }
 
-    fn validate_equal_count(tokens: &[Token]) -> Result<(), BaseError> {
-        let equal_count = tokens
-            .iter()
-            .filter(|t| matches!(t, Token::Separator))
-            .count();
-        if equal_count == 0 {
-            return Err(BaseError::LineStructure(LineError::MissingSeparators));
-        } else if equal_count > 1 {
-            return Err(BaseError::LineStructure(LineError::MultipleSeparators));
+    fn validate_equal_count(tokens: &[SpannedToken]) -> Result<(), BaseError> {
+        // Separators inside inline tables belong to their own entries.
+        let mut depth = 0;
+        let mut separators = tokens.iter().filter(|t| {
+            depth += Self::nesting_change(&t.token);
+            depth == 0 && t.token == Separator
+        });
+
+        if separators.next().is_none() {
+            return Err(BaseError::LineStructure(LineError::MissingSeparators {
+                span: Self::span_of(tokens),
+            }));
+        }
+
+        if let Some(extra) = separators.next() {
+            return Err(BaseError::LineStructure(LineError::MultipleSeparators {
+                span: extra.span,
+            }));
         }
 
         Ok(())
     }
 
-    fn validate_left_side_last(left_side: &[Token]) -> Result<(), BaseError> {
+    fn validate_left_side_last(
+        left_side: &[SpannedToken],
+        separator: &SpannedToken,
+    ) -> Result<(), BaseError> {
         match left_side.last() {
-            Some(Ident(_)) => {}
-            Some(_) => return Err(BaseError::LineStructure(LineError::LeftSideMustBeIdent)),
-            None => return Err(BaseError::LineStructure(LineError::MissingLeftSide)),
+            Some(SpannedToken {
+                token: Ident(_) | QuotedIdent(_),
+                ..
+            }) => {}
+            Some(other) => {
+                return Err(BaseError::LineStructure(LineError::LeftSideMustBeIdent {
+                    span: other.span,
+                }));
+            }
+            None => {
+                return Err(BaseError::LineStructure(LineError::MissingLeftSide {
+                    span: separator.span,
+                }));
+            }
         }
         Ok(())
     }
 
-    fn validate_right_side_last(right_side: &[Token]) -> Result<(), BaseError> {
+    fn validate_right_side_last(right_side: &[SpannedToken]) -> Result<(), BaseError> {
         match right_side.last() {
-            Some(Ident(_) | QuotedIdent(_)) => {}
+            Some(SpannedToken {
+                token: Ident(_) | QuotedIdent(_) | RightBracket | RightBrace,
+                ..
+            }) => {}
             None => {}
-            Some(_) => {
+            Some(other) => {
                 return Err(BaseError::LineStructure(
-                    LineError::RightSideContainsInvalidTokens,
+                    LineError::RightSideContainsInvalidTokens { span: other.span },
                 ));
             }
         }
         Ok(())
     }
 
-    fn split_side_by_side(tokens: &[Token]) -> Result<(&[Token], &[Token]), BaseError> {
+    fn split_side_by_side(
+        tokens: &[SpannedToken],
+    ) -> Result<(&[SpannedToken], &SpannedToken, &[SpannedToken]), BaseError> {
         let equal_pos = tokens
             .iter()
-            .position(|t| matches!(t, Token::Separator))
+            .position(|t| t.token == Separator)
             .unwrap_or_else(|| {
                 unreachable!("Separator token must be validated before calling this function")
             });
 
-        Ok((&tokens[..equal_pos], &tokens[equal_pos + 1..]))
+        Ok((
+            &tokens[..equal_pos],
+            &tokens[equal_pos],
+            &tokens[equal_pos + 1..],
+        ))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::validator::token::spanned;
     use TokenValidationError::LineStructure as LineStructureError;
     use kernel::error::{LineStructureError as LE, TokenValidationError};
 
@@ -78,7 +111,7 @@ mod tests {
             Separator,
             Ident("value".to_string()),
         ];
-        assert!(TokenValidator::validate_line_structure(&tokens).is_ok());
+        assert!(TokenValidator::validate_line_structure(&spanned(tokens)).is_ok());
 
         let tokens = vec![
             Ident("key".to_string()),
@@ -88,14 +121,23 @@ mod tests {
             Ident("value".to_string()),
         ];
 
-        assert!(TokenValidator::validate_line_structure(&tokens).is_ok());
+        assert!(TokenValidator::validate_line_structure(&spanned(tokens)).is_ok());
 
         let tokens = vec![
             Ident("key".to_string()),
             Separator,
             QuotedIdent("value".to_string()),
         ];
-        assert!(TokenValidator::validate_line_structure(&tokens).is_ok());
+        assert!(TokenValidator::validate_line_structure(&spanned(tokens)).is_ok());
+
+        let tokens = vec![
+            Ident("routes".to_string()),
+            Dot,
+            QuotedIdent("/api/v1".to_string()),
+            Separator,
+            Ident("value".to_string()),
+        ];
+        assert!(TokenValidator::validate_line_structure(&spanned(tokens)).is_ok());
     }
 
     mod invalid {
@@ -104,10 +146,10 @@ mod tests {
         #[test]
         fn test_missing_equal() {
             let tokens = vec![Ident("key".to_string()), Ident("value".to_string())];
-            let result = TokenValidator::validate_line_structure(&tokens);
+            let result = TokenValidator::validate_line_structure(&spanned(tokens));
             assert!(matches!(
                 result.unwrap_err(),
-                LineStructureError(LE::MissingSeparators)
+                LineStructureError(LE::MissingSeparators { .. })
             ));
         }
 
@@ -120,10 +162,10 @@ mod tests {
                 Separator,
                 Ident("value2".to_string()),
             ];
-            let result = TokenValidator::validate_line_structure(&tokens);
+            let result = TokenValidator::validate_line_structure(&spanned(tokens));
             assert!(matches!(
                 result.unwrap_err(),
-                LineStructureError(LE::MultipleSeparators)
+                LineStructureError(LE::MultipleSeparators { .. })
             ));
 
             let tokens = vec![
@@ -133,52 +175,53 @@ mod tests {
                 Ident("value".to_string()),
             ];
 
-            let result = TokenValidator::validate_line_structure(&tokens);
+            let result = TokenValidator::validate_line_structure(&spanned(tokens));
             assert!(matches!(
                 result.unwrap_err(),
-                LineStructureError(LE::MultipleSeparators)
+                LineStructureError(LE::MultipleSeparators { .. })
             ));
         }
 
         #[test]
         fn test_missing_left_side() {
             let tokens = vec![Separator, Ident("value".to_string()), Newline];
-            let result = TokenValidator::validate_line_structure(&tokens);
+            let result = TokenValidator::validate_line_structure(&spanned(tokens));
             assert!(matches!(
                 result.unwrap_err(),
-                LineStructureError(LE::MissingLeftSide)
+                LineStructureError(LE::MissingLeftSide { .. })
             ));
         }
 
         #[test]
         fn test_left_side_must_be_ident() {
             let tokens = vec![
-                QuotedIdent("key".to_string()),
+                Ident("key".to_string()),
+                Dot,
                 Separator,
                 Ident("value".to_string()),
                 Newline,
             ];
-            let result = TokenValidator::validate_line_structure(&tokens);
+            let result = TokenValidator::validate_line_structure(&spanned(tokens));
             assert!(matches!(
                 result.unwrap_err(),
-                LineStructureError(LE::LeftSideMustBeIdent)
+                LineStructureError(LE::LeftSideMustBeIdent { .. })
             ));
 
             let tokens = vec![Dot, Separator, Ident("value".to_string()), Newline];
-            let result = TokenValidator::validate_line_structure(&tokens);
+            let result = TokenValidator::validate_line_structure(&spanned(tokens));
             assert!(matches!(
                 result.unwrap_err(),
-                LineStructureError(LE::LeftSideMustBeIdent)
+                LineStructureError(LE::LeftSideMustBeIdent { .. })
             ));
         }
 
         #[test]
         fn test_right_side_contains_invalid_tokens() {
             let tokens = vec![Ident("key".to_string()), Separator, Dot, Newline];
-            let result = TokenValidator::validate_line_structure(&tokens);
+            let result = TokenValidator::validate_line_structure(&spanned(tokens));
             assert!(matches!(
                 result.unwrap_err(),
-                LineStructureError(LE::RightSideContainsInvalidTokens)
+                LineStructureError(LE::RightSideContainsInvalidTokens { .. })
             ));
         }
     }
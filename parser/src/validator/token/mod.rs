@@ -1,5 +1,24 @@
+mod delimited;
 mod key;
 mod line_structure;
+mod section;
 mod value;
 
+use kernel::span::Span;
+use kernel::token::SpannedToken;
+
 pub struct TokenValidator;
+
+impl TokenValidator {
+    pub fn span_of(tokens: &[SpannedToken]) -> Span {
+        match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => first.span.to(last.span),
+            _ => Span::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub fn spanned(tokens: Vec<kernel::token::Token>) -> Vec<SpannedToken> {
+    tokens.into_iter().map(SpannedToken::from).collect()
+}
@@ -0,0 +1,95 @@
+use super::TokenValidator;
+use LineStructureError as LineError;
+use Token::*;
+use TokenValidationError as BaseError;
+use kernel::error::{LineStructureError, TokenValidationError};
+use kernel::token::{SpannedToken, Token};
+
+impl TokenValidator {
+    /// A line opening with `[` is a section header such as `[server.tls]`.
+    pub fn is_section_header(tokens: &[SpannedToken]) -> bool {
+        tokens.first().is_some_and(|t| t.token == LeftBracket)
+    }
+
+    /// Checks a header line and returns the tokens of the key between its brackets.
+    pub fn validate_section_header(tokens: &[SpannedToken]) -> Result<&[SpannedToken], BaseError> {
+        match tokens {
+            [open, key_tokens @ .., close]
+                if open.token == LeftBracket
+                    && close.token == RightBracket
+                    && !key_tokens.is_empty() =>
+            {
+                Self::validate_key(key_tokens)?;
+                Ok(key_tokens)
+            }
+            _ => Err(BaseError::LineStructure(LineError::InvalidSectionHeader {
+                span: Self::span_of(tokens),
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validator::token::spanned;
+    use kernel::error::KeyError;
+
+    #[test]
+    fn test_valid() {
+        let tokens = spanned(vec![
+            LeftBracket,
+            Ident("server".to_string()),
+            Dot,
+            QuotedIdent("tls v2".to_string()),
+            RightBracket,
+        ]);
+        assert!(TokenValidator::is_section_header(&tokens));
+        let key_tokens = TokenValidator::validate_section_header(&tokens).unwrap();
+        assert_eq!(key_tokens.len(), 3);
+
+        let tokens = spanned(vec![Ident("key".to_string()), Separator, LeftBracket]);
+        assert!(!TokenValidator::is_section_header(&tokens));
+    }
+
+    mod invalid {
+        use super::*;
+
+        #[test]
+        fn test_invalid_section_header() {
+            let cases = [
+                vec![LeftBracket, RightBracket],
+                vec![LeftBracket, Ident("server".to_string())],
+                vec![
+                    LeftBracket,
+                    Ident("server".to_string()),
+                    RightBracket,
+                    Ident("extra".to_string()),
+                ],
+            ];
+            for tokens in cases {
+                let tokens = spanned(tokens);
+                let result = TokenValidator::validate_section_header(&tokens);
+                assert!(matches!(
+                    result.unwrap_err(),
+                    BaseError::LineStructure(LineError::InvalidSectionHeader { .. })
+                ));
+            }
+        }
+
+        #[test]
+        fn test_invalid_section_key() {
+            let tokens = spanned(vec![
+                LeftBracket,
+                Ident("server".to_string()),
+                Dot,
+                RightBracket,
+            ]);
+            let result = TokenValidator::validate_section_header(&tokens);
+            assert!(matches!(
+                result.unwrap_err(),
+                BaseError::Key(KeyError::UnexpectedTokenInKey { .. })
+            ));
+        }
+    }
+}
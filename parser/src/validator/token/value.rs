@@ -1,8 +1,12 @@
 use super::TokenValidator;
+use crate::number::parse_number;
 use From;
 use Token::*;
+use kernel::datetime::Datetime;
 use kernel::error::{TokenValidationError as BaseError, ValueError};
-use kernel::token::Token;
+use kernel::span::Span;
+use kernel::token::{SpannedToken, Token};
+use kernel::units::{parse_duration, parse_size};
 
 enum InnerPlaneToken {
     String,
@@ -13,7 +17,7 @@ enum InnerPlaneToken {
 impl From<&Token> for InnerPlaneToken {
     fn from(token: &Token) -> Self {
         match token {
-            Ident(name) if name.chars().all(|c| c.is_numeric()) => InnerPlaneToken::Numeric,
+            Ident(name) if name.chars().all(|c| c.is_ascii_digit()) => InnerPlaneToken::Numeric,
             Ident(_) => InnerPlaneToken::String,
             Dot => InnerPlaneToken::Dot,
             _ => unreachable!(
@@ -25,43 +29,127 @@ impl From<&Token> for InnerPlaneToken {
 }
 
 impl TokenValidator {
-    pub fn validate_value(tokens: &[Token]) -> Result<(), BaseError> {
+    pub fn validate_value(tokens: &[SpannedToken]) -> Result<(), BaseError> {
         if tokens.is_empty() {
             return Ok(());
         }
 
+        if let Some(array) = Self::optional_array(tokens) {
+            return Self::validate_array(array);
+        }
+
+        if Self::is_array(tokens) {
+            return Self::validate_array(tokens);
+        }
+
+        if Self::is_table(tokens) {
+            return Self::validate_table(tokens);
+        }
+
+        Self::validate_no_delimiters(tokens)?;
+
+        if let Some(text) = Self::plain_text(tokens)
+            && Datetime::looks_like(&text)
+        {
+            return Self::validate_datetime(&text, Self::span_of(tokens));
+        }
+
+        if let Some(text) = Self::plain_text(tokens)
+            && let Some(result) = parse_duration(&text)
+                .map(|r| r.map(|_| ()))
+                .or_else(|| parse_size(&text).map(|r| r.map(|_| ())))
+        {
+            let span = Self::span_of(tokens);
+            return result.map_err(|error| BaseError::Value(error.at(span)));
+        }
+
+        if let Some(text) = Self::plain_text(tokens)
+            && let Some(result) = parse_number(&text)
+        {
+            let span = Self::span_of(tokens);
+            return result
+                .map(|_| ())
+                .map_err(|error| BaseError::Value(error.at(span)));
+        }
+
         if tokens.len() == 1 {
-            return Self::validate_single_value(tokens[0].clone());
+            return Self::validate_single_value(&tokens[0]);
         }
 
-        Self::validate_multiple_quoted_idents(tokens)?;
-        Self::validate_mixed_idents(tokens)?;
-        Self::validate_multiple_dots(tokens)?;
-        Self::validate_ending_dot(tokens)?;
+        let span = Self::span_of(tokens);
+        let tokens: Vec<Token> = tokens.iter().map(|t| t.token.clone()).collect();
+
+        Self::validate_multiple_quoted_idents(&tokens, span)?;
+        Self::validate_mixed_idents(&tokens, span)?;
+        Self::validate_multiple_dots(&tokens, span)?;
+        Self::validate_ending_dot(&tokens, span)?;
 
         let inner_simple_tokens: Vec<InnerPlaneToken> =
             tokens.iter().map(InnerPlaneToken::from).collect();
 
-        Self::validate_multiple_non_numeric_idents(&inner_simple_tokens)?;
-        Self::validate_mixed_dot_and_non_numeric_idents(&inner_simple_tokens)?;
-        Self::validate_mixed_non_numeric_and_numeric_idents(&inner_simple_tokens)?;
-        Self::validate_consecutive_numeric_idents(&inner_simple_tokens)?;
+        Self::validate_multiple_non_numeric_idents(&inner_simple_tokens, span)?;
+        Self::validate_mixed_dot_and_non_numeric_idents(&inner_simple_tokens, span)?;
+        Self::validate_mixed_non_numeric_and_numeric_idents(&inner_simple_tokens, span)?;
+        Self::validate_consecutive_numeric_idents(&inner_simple_tokens, span)?;
 
         Ok(())
     }
 
-    fn validate_single_value(token: Token) -> Result<(), BaseError> {
-        if token == Dot {
-            return Err(BaseError::Value(ValueError::InvalidValueFormat));
+    fn validate_no_delimiters(tokens: &[SpannedToken]) -> Result<(), BaseError> {
+        match tokens.iter().find(|t| {
+            matches!(
+                t.token,
+                LeftBracket | RightBracket | LeftBrace | RightBrace | Comma | Separator
+            )
+        }) {
+            Some(token) => Err(BaseError::Value(ValueError::UnexpectedDelimiter {
+                span: token.span,
+            })),
+            None => Ok(()),
+        }
+    }
+
+    /// Joins a value made only of idents and dots back into its source text,
+    /// e.g. the fractional part of a datetime or `1.5h` is lexed as separate
+    /// tokens.
+    fn plain_text(tokens: &[SpannedToken]) -> Option<String> {
+        let has_adjacent_idents = tokens
+            .windows(2)
+            .any(|pair| matches!((&pair[0].token, &pair[1].token), (Ident(_), Ident(_))));
+        if has_adjacent_idents {
+            return None;
+        }
+
+        tokens
+            .iter()
+            .map(|t| match &t.token {
+                Ident(name) => Some(name.as_str()),
+                Dot => Some("."),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn validate_datetime(text: &str, span: Span) -> Result<(), BaseError> {
+        text.parse::<Datetime>()
+            .map(|_| ())
+            .map_err(|_| BaseError::Value(ValueError::InvalidDatetime { span }))
+    }
+
+    fn validate_single_value(token: &SpannedToken) -> Result<(), BaseError> {
+        if token.token == Dot {
+            return Err(BaseError::Value(ValueError::InvalidValueFormat {
+                span: token.span,
+            }));
         }
         Ok(())
     }
 
-    fn validate_mixed_idents(tokens: &[Token]) -> Result<(), BaseError> {
+    fn validate_mixed_idents(tokens: &[Token], span: Span) -> Result<(), BaseError> {
         let has_simple_ident = tokens.iter().any(|t| matches!(t, Ident(_)));
         let has_quoted_ident = tokens.iter().any(|t| matches!(t, QuotedIdent(_)));
         if has_simple_ident && has_quoted_ident {
-            return Err(BaseError::Value(ValueError::MultipleMixedIdents));
+            return Err(BaseError::Value(ValueError::MultipleMixedIdents { span }));
         }
 
         Ok(())
@@ -69,6 +157,7 @@ impl TokenValidator {
 
     fn validate_mixed_dot_and_non_numeric_idents(
         inner_simple_tokens: &[InnerPlaneToken],
+        span: Span,
     ) -> Result<(), BaseError> {
         let has_dot = inner_simple_tokens
             .iter()
@@ -78,7 +167,7 @@ impl TokenValidator {
             .any(|c| matches!(c, InnerPlaneToken::String));
 
         if has_dot && has_non_numeric {
-            return Err(BaseError::Value(ValueError::InvalidValueFormat));
+            return Err(BaseError::Value(ValueError::InvalidValueFormat { span }));
         }
 
         Ok(())
@@ -86,6 +175,7 @@ impl TokenValidator {
 
     fn validate_mixed_non_numeric_and_numeric_idents(
         inner_simple_tokens: &[InnerPlaneToken],
+        span: Span,
     ) -> Result<(), BaseError> {
         let has_numeric = inner_simple_tokens
             .iter()
@@ -95,28 +185,28 @@ impl TokenValidator {
             .any(|c| matches!(c, InnerPlaneToken::String));
 
         if has_numeric && has_non_numeric {
-            return Err(BaseError::Value(ValueError::InvalidValueFormat));
+            return Err(BaseError::Value(ValueError::InvalidValueFormat { span }));
         }
 
         Ok(())
     }
 
-    fn validate_multiple_quoted_idents(tokens: &[Token]) -> Result<(), BaseError> {
+    fn validate_multiple_quoted_idents(tokens: &[Token], span: Span) -> Result<(), BaseError> {
         let quoted_ident_count = tokens
             .iter()
             .filter(|t| matches!(t, QuotedIdent(_)))
             .count();
         if quoted_ident_count > 1 {
-            return Err(BaseError::Value(ValueError::MultipleQuotedIdents));
+            return Err(BaseError::Value(ValueError::MultipleQuotedIdents { span }));
         }
 
         Ok(())
     }
 
-    fn validate_multiple_dots(tokens: &[Token]) -> Result<(), BaseError> {
+    fn validate_multiple_dots(tokens: &[Token], span: Span) -> Result<(), BaseError> {
         let dot_count = tokens.iter().filter(|t| matches!(t, Dot)).count();
         if dot_count > 1 {
-            return Err(BaseError::Value(ValueError::MultipleDots));
+            return Err(BaseError::Value(ValueError::MultipleDots { span }));
         }
 
         Ok(())
@@ -124,33 +214,37 @@ impl TokenValidator {
 
     fn validate_multiple_non_numeric_idents(
         inner_simple_tokens: &[InnerPlaneToken],
+        span: Span,
     ) -> Result<(), BaseError> {
         let simple_string_idents_count = inner_simple_tokens
             .iter()
             .filter(|c| matches!(c, InnerPlaneToken::String))
             .count();
         if simple_string_idents_count > 1 {
-            return Err(BaseError::Value(ValueError::MultipleNonNumericIdents));
+            return Err(BaseError::Value(ValueError::MultipleNonNumericIdents {
+                span,
+            }));
         }
 
         Ok(())
     }
 
-    fn validate_ending_dot(tokens: &[Token]) -> Result<(), BaseError> {
+    fn validate_ending_dot(tokens: &[Token], span: Span) -> Result<(), BaseError> {
         if tokens.last() == Some(&Dot) {
-            return Err(BaseError::Value(ValueError::InvalidValueFormat));
+            return Err(BaseError::Value(ValueError::InvalidValueFormat { span }));
         }
         Ok(())
     }
 
     fn validate_consecutive_numeric_idents(
         inner_simple_tokens: &[InnerPlaneToken],
+        span: Span,
     ) -> Result<(), BaseError> {
         for i in 0..inner_simple_tokens.len() {
-            if matches!(inner_simple_tokens[i], InnerPlaneToken::Numeric) {
-                if let Some(InnerPlaneToken::Numeric) = inner_simple_tokens.get(i + 1) {
-                    return Err(BaseError::Value(ValueError::InvalidValueFormat));
-                }
+            if matches!(inner_simple_tokens[i], InnerPlaneToken::Numeric)
+                && let Some(InnerPlaneToken::Numeric) = inner_simple_tokens.get(i + 1)
+            {
+                return Err(BaseError::Value(ValueError::InvalidValueFormat { span }));
             }
         }
         Ok(())
@@ -160,40 +254,109 @@ impl TokenValidator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::validator::token::spanned;
     use TokenValidationError::Value as ValueError;
     use kernel::error::{TokenValidationError, ValueError as VE};
 
     #[test]
     fn test_valid() {
         let tokens = vec![Ident("value".to_string())];
-        assert!(TokenValidator::validate_value(&tokens).is_ok());
+        assert!(TokenValidator::validate_value(&spanned(tokens)).is_ok());
 
         let tokens = vec![QuotedIdent("value".to_string())];
-        assert!(TokenValidator::validate_value(&tokens).is_ok());
+        assert!(TokenValidator::validate_value(&spanned(tokens)).is_ok());
 
         let tokens = vec![Ident("123".to_string())];
-        assert!(TokenValidator::validate_value(&tokens).is_ok());
+        assert!(TokenValidator::validate_value(&spanned(tokens)).is_ok());
 
         let tokens = vec![Ident("123".to_string()), Dot, Ident("456".to_string())];
-        assert!(TokenValidator::validate_value(&tokens).is_ok());
+        assert!(TokenValidator::validate_value(&spanned(tokens)).is_ok());
 
         let tokens = vec![Dot, Ident("123".to_string())];
-        assert!(TokenValidator::validate_value(&tokens).is_ok());
+        assert!(TokenValidator::validate_value(&spanned(tokens)).is_ok());
 
         let tokens: Vec<Token> = vec![];
-        assert!(TokenValidator::validate_value(&tokens).is_ok());
+        assert!(TokenValidator::validate_value(&spanned(tokens)).is_ok());
+
+        let tokens = vec![
+            Ident("2024-01-15T10:30:00".to_string()),
+            Dot,
+            Ident("123Z".to_string()),
+        ];
+        assert!(TokenValidator::validate_value(&spanned(tokens)).is_ok());
+
+        let tokens = vec![Ident("1".to_string()), Dot, Ident("5GiB".to_string())];
+        assert!(TokenValidator::validate_value(&spanned(tokens)).is_ok());
+
+        let tokens = vec![Ident("1_000".to_string()), Dot, Ident("5e-3".to_string())];
+        assert!(TokenValidator::validate_value(&spanned(tokens)).is_ok());
     }
 
     mod invalid {
         use super::*;
 
+        #[test]
+        fn test_invalid_number_literals() {
+            let tokens = vec![Ident("0o758".to_string())];
+            let result = TokenValidator::validate_value(&spanned(tokens));
+            assert!(matches!(
+                result,
+                Err(ValueError(VE::InvalidOctalLiteral { .. }))
+            ));
+
+            let tokens = vec![Ident("1".to_string()), Dot, Ident("5e".to_string())];
+            let result = TokenValidator::validate_value(&spanned(tokens));
+            assert!(matches!(
+                result,
+                Err(ValueError(VE::InvalidExponent { .. }))
+            ));
+        }
+
+        #[test]
+        fn test_invalid_datetime() {
+            let tokens = vec![Ident("2024-02-30".to_string())];
+            let result = TokenValidator::validate_value(&spanned(tokens));
+            assert!(matches!(
+                result,
+                Err(ValueError(VE::InvalidDatetime { .. }))
+            ));
+
+            let tokens = vec![Ident("25:00:00".to_string())];
+            let result = TokenValidator::validate_value(&spanned(tokens));
+            assert!(matches!(
+                result,
+                Err(ValueError(VE::InvalidDatetime { .. }))
+            ));
+        }
+
+        #[test]
+        fn test_invalid_durations_and_sizes() {
+            let tokens = vec![Ident("1ms5s".to_string())];
+            let result = TokenValidator::validate_value(&spanned(tokens));
+            assert!(matches!(
+                result,
+                Err(ValueError(VE::MisorderedDurationUnits { .. }))
+            ));
+
+            let tokens = vec![Ident("99999999999999999999d".to_string())];
+            let result = TokenValidator::validate_value(&spanned(tokens));
+            assert!(matches!(
+                result,
+                Err(ValueError(VE::DurationOutOfRange { .. }))
+            ));
+
+            let tokens = vec![Ident("18446744073709551616PiB".to_string())];
+            let result = TokenValidator::validate_value(&spanned(tokens));
+            assert!(matches!(result, Err(ValueError(VE::SizeOutOfRange { .. }))));
+        }
+
         #[test]
         fn test_multiple_non_numeric_idents() {
             let tokens = vec![Ident("value1".to_string()), Ident("value2".to_string())];
-            let result = TokenValidator::validate_value(&tokens);
+            let result = TokenValidator::validate_value(&spanned(tokens));
             assert!(matches!(
                 result,
-                Err(ValueError(VE::MultipleNonNumericIdents))
+                Err(ValueError(VE::MultipleNonNumericIdents { .. }))
             ));
         }
 
@@ -203,8 +366,11 @@ mod tests {
                 QuotedIdent("value1".to_string()),
                 QuotedIdent("value2".to_string()),
             ];
-            let result = TokenValidator::validate_value(&tokens);
-            assert!(matches!(result, Err(ValueError(VE::MultipleQuotedIdents))));
+            let result = TokenValidator::validate_value(&spanned(tokens));
+            assert!(matches!(
+                result,
+                Err(ValueError(VE::MultipleQuotedIdents { .. }))
+            ));
         }
 
         #[test]
@@ -213,23 +379,35 @@ mod tests {
                 Ident("value1".to_string()),
                 QuotedIdent("value2".to_string()),
             ];
-            let result = TokenValidator::validate_value(&tokens);
-            assert!(matches!(result, Err(ValueError(VE::MultipleMixedIdents))));
+            let result = TokenValidator::validate_value(&spanned(tokens));
+            assert!(matches!(
+                result,
+                Err(ValueError(VE::MultipleMixedIdents { .. }))
+            ));
 
             let tokens = vec![Ident("123".to_string()), QuotedIdent("value2".to_string())];
-            let result = TokenValidator::validate_value(&tokens);
-            assert!(matches!(result, Err(ValueError(VE::MultipleMixedIdents))));
+            let result = TokenValidator::validate_value(&spanned(tokens));
+            assert!(matches!(
+                result,
+                Err(ValueError(VE::MultipleMixedIdents { .. }))
+            ));
 
             let tokens = vec![QuotedIdent("value2".to_string()), Ident("123".to_string())];
-            let result = TokenValidator::validate_value(&tokens);
-            assert!(matches!(result, Err(ValueError(VE::MultipleMixedIdents))));
+            let result = TokenValidator::validate_value(&spanned(tokens));
+            assert!(matches!(
+                result,
+                Err(ValueError(VE::MultipleMixedIdents { .. }))
+            ));
 
             let tokens = vec![
                 QuotedIdent("value2".to_string()),
                 Ident("value1".to_string()),
             ];
-            let result = TokenValidator::validate_value(&tokens);
-            assert!(matches!(result, Err(ValueError(VE::MultipleMixedIdents))));
+            let result = TokenValidator::validate_value(&spanned(tokens));
+            assert!(matches!(
+                result,
+                Err(ValueError(VE::MultipleMixedIdents { .. }))
+            ));
         }
     }
 
@@ -242,39 +420,51 @@ mod tests {
             Dot,
             Ident("789".to_string()),
         ];
-        let result = TokenValidator::validate_value(&tokens);
-        assert!(matches!(result, Err(ValueError(VE::MultipleDots))));
+        let result = TokenValidator::validate_value(&spanned(tokens));
+        assert!(matches!(result, Err(ValueError(VE::MultipleDots { .. }))));
 
         let tokens = vec![Ident("123".to_string()), Dot, Dot, Ident("789".to_string())];
-        let result = TokenValidator::validate_value(&tokens);
-        assert!(matches!(result, Err(ValueError(VE::MultipleDots))));
+        let result = TokenValidator::validate_value(&spanned(tokens));
+        assert!(matches!(result, Err(ValueError(VE::MultipleDots { .. }))));
 
         let tokens = vec![Dot, Dot, Ident("789".to_string())];
-        let result = TokenValidator::validate_value(&tokens);
-        assert!(matches!(result, Err(ValueError(VE::MultipleDots))));
+        let result = TokenValidator::validate_value(&spanned(tokens));
+        assert!(matches!(result, Err(ValueError(VE::MultipleDots { .. }))));
 
         let tokens = vec![Dot, Dot];
-        let result = TokenValidator::validate_value(&tokens);
-        assert!(matches!(result, Err(ValueError(VE::MultipleDots))));
+        let result = TokenValidator::validate_value(&spanned(tokens));
+        assert!(matches!(result, Err(ValueError(VE::MultipleDots { .. }))));
     }
 
     #[test]
     fn test_invalid_value_format() {
         let tokens = vec![Dot];
-        let result = TokenValidator::validate_value(&tokens);
-        assert!(matches!(result, Err(ValueError(VE::InvalidValueFormat))));
+        let result = TokenValidator::validate_value(&spanned(tokens));
+        assert!(matches!(
+            result,
+            Err(ValueError(VE::InvalidValueFormat { .. }))
+        ));
 
         let tokens = vec![Ident("123".to_string()), Dot];
-        let result = TokenValidator::validate_value(&tokens);
-        assert!(matches!(result, Err(ValueError(VE::InvalidValueFormat))));
+        let result = TokenValidator::validate_value(&spanned(tokens));
+        assert!(matches!(
+            result,
+            Err(ValueError(VE::InvalidValueFormat { .. }))
+        ));
 
         let tokens = vec![Ident("123".to_string()), Ident("aaa".to_string())];
-        let result = TokenValidator::validate_value(&tokens);
-        assert!(matches!(result, Err(ValueError(VE::InvalidValueFormat))));
+        let result = TokenValidator::validate_value(&spanned(tokens));
+        assert!(matches!(
+            result,
+            Err(ValueError(VE::InvalidValueFormat { .. }))
+        ));
 
         let tokens = vec![Ident("123".to_string()), Ident("456".to_string())];
-        let result = TokenValidator::validate_value(&tokens);
-        assert!(matches!(result, Err(ValueError(VE::InvalidValueFormat))));
+        let result = TokenValidator::validate_value(&spanned(tokens));
+        assert!(matches!(
+            result,
+            Err(ValueError(VE::InvalidValueFormat { .. }))
+        ));
 
         let tokens = vec![
             Ident("123".to_string()),
@@ -282,7 +472,10 @@ mod tests {
             Ident("456".to_string()),
             Ident("789".to_string()),
         ];
-        let result = TokenValidator::validate_value(&tokens);
-        assert!(matches!(result, Err(ValueError(VE::InvalidValueFormat))));
+        let result = TokenValidator::validate_value(&spanned(tokens));
+        assert!(matches!(
+            result,
+            Err(ValueError(VE::InvalidValueFormat { .. }))
+        ));
     }
 }
@@ -1,4 +1,9 @@
-use kernel::value::{Number, Value};
+use super::number::parse_number;
+use kernel::{
+    datetime::Datetime,
+    units::{parse_duration, parse_size},
+    value::Value,
+};
 
 pub trait ValueExt {
     fn from_plain_string(s: String) -> Self;
@@ -6,22 +11,29 @@ pub trait ValueExt {
 
 impl ValueExt for Value {
     fn from_plain_string(s: String) -> Self {
+        if s == "null" {
+            return Value::Null;
+        }
+
         if s == "true" || s == "false" {
             return Value::Bool(s.parse().unwrap());
         }
 
-        if s.contains('.') {
-            if let Ok(float) = s.parse::<f64>() {
-                return Value::Number(Number::Float(float));
-            }
+        if let Ok(datetime) = s.parse::<Datetime>() {
+            return Value::Datetime(datetime, s);
+        }
+
+        if let Some(Ok(duration)) = parse_duration(&s) {
+            return Value::Duration(duration, s);
+        }
+
+        if let Some(Ok(bytes)) = parse_size(&s) {
+            return Value::Size(bytes, s);
         }
 
-        if let Ok(uint) = s.parse::<u64>() {
-            Value::Number(Number::UInt(uint))
-        } else if let Ok(int) = s.parse::<i64>() {
-            Value::Number(Number::Int(int))
-        } else {
-            Value::String(s)
+        match parse_number(&s) {
+            Some(Ok(number)) => Value::Number(number),
+            _ => Value::String(s),
         }
     }
 }
@@ -29,6 +41,7 @@ impl ValueExt for Value {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use kernel::value::Number;
 
     #[test]
     fn test_value_from_plain_string() {
@@ -40,6 +53,7 @@ mod tests {
             Value::from_plain_string("false".to_string()),
             Value::Bool(false)
         );
+        assert_eq!(Value::from_plain_string("null".to_string()), Value::Null);
         assert_eq!(
             Value::from_plain_string("123".to_string()),
             Value::Number(Number::UInt(123))
@@ -68,6 +82,41 @@ mod tests {
             Value::from_plain_string("hello".to_string()),
             Value::String("hello".to_string())
         );
+        assert_eq!(
+            Value::from_plain_string("2024-01-15".to_string()),
+            Value::Datetime("2024-01-15".parse().unwrap(), "2024-01-15".into())
+        );
+        assert_eq!(
+            Value::from_plain_string("2024-01-15T10:30:00+09:00".to_string()),
+            Value::Datetime(
+                "2024-01-15T10:30:00+09:00".parse().unwrap(),
+                "2024-01-15T10:30:00+09:00".into()
+            )
+        );
+        assert_eq!(
+            Value::from_plain_string("0o755".to_string()),
+            Value::Number(Number::UInt(0o755))
+        );
+        assert_eq!(
+            Value::from_plain_string("1_000".to_string()),
+            Value::Number(Number::UInt(1000))
+        );
+        assert_eq!(
+            Value::from_plain_string("1e-3".to_string()),
+            Value::Number(Number::Float(1e-3))
+        );
+        assert_eq!(
+            Value::from_plain_string("1h30m".to_string()),
+            Value::Duration(std::time::Duration::from_secs(5400), "1h30m".into())
+        );
+        assert_eq!(
+            Value::from_plain_string("512KiB".to_string()),
+            Value::Size(512 * 1024, "512KiB".into())
+        );
+        assert_eq!(
+            Value::from_plain_string("30x".to_string()),
+            Value::String("30x".to_string())
+        );
         assert_eq!(
             Value::from_plain_string("".to_string()),
             Value::String("".to_string())
@@ -0,0 +1,319 @@
+use kernel::{
+    env::{Environment, ProcessEnvironment},
+    error::Error,
+    options::ParseOptions,
+    shared::Map,
+    token::{SpannedToken, Token},
+    value::Value,
+};
+use parser::{Entry, Parser};
+use serde::de::DeserializeOwned;
+
+use super::from_schema::from_schema;
+use super::from_str::parse_config_entries;
+use super::from_str_with_schema::{apply_schema, validate_and_cast};
+use super::include::Includes;
+use super::origin::{Origin, Origins};
+use super::overrides::{override_entries, set_entries, set_entry, set_env_entry};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The extension of the files read from a drop-in directory.
+const DROP_IN_EXTENSION: &str = "conf";
+
+/// Stacks configuration sources, each overriding the keys of those before it:
+///
+/// 1. the defaults,
+/// 2. files and drop-in directories, in the order they were added,
+/// 3. environment variables with the prefix, if one is set,
+/// 4. `key=value` overrides.
+///
+/// Tables are merged key by key, while arrays and other values are replaced
+/// as a whole. A layer may not turn a table into a value or a value into a
+/// table. The merged value is checked against the schema, if one is set.
+pub struct ConfigBuilder<'a> {
+    defaults: Option<String>,
+    layers: Vec<Layer>,
+    overrides: Vec<String>,
+    schema: Option<SchemaInput>,
+    options: ParseOptions,
+    env: &'a dyn Environment,
+}
+
+enum Layer {
+    File { path: PathBuf, required: bool },
+    Dir(PathBuf),
+}
+
+enum SchemaInput {
+    Text(String),
+    File(PathBuf),
+}
+
+impl Default for ConfigBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> ConfigBuilder<'a> {
+    pub fn new() -> Self {
+        Self {
+            defaults: None,
+            layers: Vec::new(),
+            overrides: Vec::new(),
+            schema: None,
+            options: ParseOptions::default(),
+            env: &ProcessEnvironment,
+        }
+    }
+
+    /// Config text whose keys every other layer may override.
+    pub fn with_defaults(mut self, defaults: &str) -> Self {
+        self.defaults = Some(defaults.to_string());
+        self
+    }
+
+    /// A file that must exist, such as the base configuration.
+    pub fn with_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.layers.push(Layer::File {
+            path: path.as_ref().to_path_buf(),
+            required: true,
+        });
+        self
+    }
+
+    /// A file that is skipped if it does not exist, such as the file for the
+    /// current environment.
+    pub fn with_optional_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.layers.push(Layer::File {
+            path: path.as_ref().to_path_buf(),
+            required: false,
+        });
+        self
+    }
+
+    /// Every `.conf` file in a directory, in the order of their names. A
+    /// missing directory is skipped.
+    pub fn with_dir<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.layers.push(Layer::Dir(path.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Lets variables such as `MYAPP__SERVER__PORT` override keys.
+    pub fn with_env_prefix(mut self, prefix: &str) -> Self {
+        self.options.env_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Lets the keys of `[profile.<name>]` in each layer replace the base keys
+    /// of that layer.
+    pub fn with_profile(mut self, profile: &str) -> Self {
+        self.options.profile = Some(profile.to_string());
+        self
+    }
+
+    /// `key.path=value` overrides, as passed to `--set`.
+    pub fn with_overrides<I, S>(mut self, overrides: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.overrides
+            .extend(overrides.into_iter().map(|arg| arg.as_ref().to_string()));
+        self
+    }
+
+    pub fn with_schema(mut self, schema: &str) -> Self {
+        self.schema = Some(SchemaInput::Text(schema.to_string()));
+        self
+    }
+
+    pub fn with_schema_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.schema = Some(SchemaInput::File(path.as_ref().to_path_buf()));
+        self
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Where `${env:NAME}` references and prefixed overrides are read from,
+    /// instead of the process environment.
+    pub fn with_env(mut self, env: &'a dyn Environment) -> Self {
+        self.env = env;
+        self
+    }
+
+    pub fn build<T>(&self) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let value = self.build_value()?;
+        T::deserialize(value).map_err(Error::Serde)
+    }
+
+    pub fn build_value(&self) -> Result<Value, Error> {
+        self.build_value_with_origins().map(|(value, _)| value)
+    }
+
+    /// Like `build_value`, also telling which layer set each key.
+    pub fn build_value_with_origins(&self) -> Result<(Value, Origins), Error> {
+        let mut value = Value::Dict(Map::new());
+        let mut origins = Origins::new();
+
+        if let Some(defaults) = &self.defaults {
+            let entries = self.read_layer(defaults, Includes::new())?;
+            merge(&mut value, &mut origins, entries)?;
+        }
+
+        for path in self.files()? {
+            fs::read_to_string(&path)
+                .map_err(Error::from)
+                .and_then(|contents| self.read_layer(&contents, Includes::for_file(&path)))
+                .and_then(|entries| merge(&mut value, &mut origins, entries))
+                .map_err(|error| Error::InFile {
+                    path: path.display().to_string(),
+                    source: Box::new(error),
+                })?;
+        }
+
+        if let Some(prefix) = &self.options.env_prefix {
+            merge(&mut value, &mut origins, self.env_entries(prefix)?)?;
+        }
+
+        for arg in &self.overrides {
+            override_entries(arg, self.env)
+                .map(|entries| {
+                    let origin = Origin::Override { arg: arg.clone() };
+                    entries
+                        .into_iter()
+                        .map(|entry| (entry, origin.clone()))
+                        .collect()
+                })
+                .and_then(|entries| merge(&mut value, &mut origins, entries))
+                .map_err(|error| Error::Override {
+                    arg: arg.clone(),
+                    source: Box::new(error),
+                })?;
+        }
+
+        let value = match &self.schema {
+            Some(SchemaInput::Text(schema)) => apply_schema(value, schema, Includes::new())?,
+            Some(SchemaInput::File(path)) => {
+                let schema = fs::read_to_string(path)
+                    .map_err(Error::from)
+                    .and_then(|schema| from_schema(&schema, Includes::for_file(path)))
+                    .map_err(|error| Error::InFile {
+                        path: path.display().to_string(),
+                        source: Box::new(error),
+                    })?;
+                validate_and_cast(value, &schema)?
+            }
+            None => value,
+        };
+        Ok((value, origins))
+    }
+
+    /// Environment overrides are applied once, after every file.
+    fn layer_options(&self) -> ParseOptions {
+        ParseOptions {
+            env_prefix: None,
+            ..self.options.clone()
+        }
+    }
+
+    /// The files to read, in order, leaving out the optional ones that are
+    /// missing.
+    fn files(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut files = Vec::new();
+        for layer in &self.layers {
+            match layer {
+                Layer::File { path, required } => {
+                    if *required || path.exists() {
+                        files.push(path.clone());
+                    }
+                }
+                Layer::Dir(dir) if dir.is_dir() => {
+                    let mut drop_ins = Vec::new();
+                    for entry in fs::read_dir(dir)? {
+                        let path = entry?.path();
+                        let is_conf = path.extension().is_some_and(|ext| ext == DROP_IN_EXTENSION);
+                        if is_conf && path.is_file() {
+                            drop_ins.push(path);
+                        }
+                    }
+                    drop_ins.sort();
+                    files.extend(drop_ins);
+                }
+                Layer::Dir(_) => {}
+            }
+        }
+        Ok(files)
+    }
+
+    /// Parses the defaults or a file, each entry coming from the line of
+    /// its key, in the file that line is in.
+    fn read_layer(
+        &self,
+        input: &str,
+        mut includes: Includes,
+    ) -> Result<Vec<(Entry, Origin)>, Error> {
+        let entries = parse_config_entries(input, self.layer_options(), self.env, &mut includes)?;
+        Ok(entries
+            .into_iter()
+            .map(|entry| {
+                let line = entry.span.line;
+                let origin = match includes.path_of(entry.span) {
+                    Some(path) => Origin::File {
+                        path: path.to_path_buf(),
+                        line,
+                    },
+                    None => Origin::Defaults { line },
+                };
+                (entry, origin)
+            })
+            .collect())
+    }
+
+    /// Parsing nothing with the prefix set leaves only the overrides. Of the
+    /// variables naming the same key, the last one is the one applied.
+    fn env_entries(&self, prefix: &str) -> Result<Vec<(Entry, Origin)>, Error> {
+        let tokens = vec![SpannedToken::from(Token::Eof)];
+        let entries = Parser::parse_entries(tokens, self.options.clone(), self.env)?;
+
+        let vars = self.env.vars();
+        Ok(entries
+            .into_iter()
+            .map(|entry| {
+                let name = vars
+                    .iter()
+                    .rev()
+                    .map(|(name, _)| name)
+                    .find(|name| {
+                        Parser::env_override_key(name, prefix).as_ref() == Some(&entry.key_parts)
+                    })
+                    .cloned()
+                    .unwrap_or_default();
+                (entry, Origin::Env { name })
+            })
+            .collect())
+    }
+}
+
+fn merge(
+    value: &mut Value,
+    origins: &mut Origins,
+    entries: Vec<(Entry, Origin)>,
+) -> Result<(), Error> {
+    set_entries(value, entries, |value, (entry, origin)| {
+        match origin {
+            Origin::Env { name } => set_env_entry(value, entry, name)?,
+            _ => set_entry(value, entry)?,
+        }
+        origins.record(&entry.key_parts, origin.clone());
+        Ok(())
+    })
+}
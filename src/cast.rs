@@ -1,6 +1,7 @@
 use kernel::{
     schema::Schema,
     shared::Map,
+    units::format_size,
     value::{Number, Value},
 };
 
@@ -8,9 +9,19 @@ pub fn cast(value: &Value, schema: &Schema) -> Value {
     match (value, schema) {
         (Value::Number(num), Schema::Integer) => cast_as_integer(num),
         (Value::Number(num), Schema::UnsignedInteger) => cast_as_unsigned_integer(num),
+        (Value::Number(num), Schema::Size) => cast_as_size(num),
+
+        (
+            Value::Datetime(_, text) | Value::Duration(_, text) | Value::Size(_, text),
+            Schema::String,
+        ) => Value::String(text.clone()),
 
         (value, Schema::Optional(inner_schema)) => cast(value, inner_schema),
 
+        (Value::Array(items), Schema::Array(item_schema)) => {
+            Value::Array(items.iter().map(|item| cast(item, item_schema)).collect())
+        }
+
         (Value::Dict(value_map), Schema::Dict(schema_map)) => {
             let mut casted_map = Map::new();
             for (key, inner_schema) in schema_map {
@@ -40,6 +51,15 @@ fn cast_as_unsigned_integer(num: &Number) -> Value {
     }
 }
 
+fn cast_as_size(num: &Number) -> Value {
+    let bytes = match num {
+        Number::Int(i) => *i as u64,
+        Number::UInt(u) => *u,
+        Number::Float(f) => *f as u64,
+    };
+    Value::Size(bytes, format_size(bytes))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,6 +96,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cast_as_size() {
+        let size = Value::Size(42, "42B".into());
+        assert_eq!(cast_as_size(&Number::Int(42)), size);
+        assert_eq!(cast_as_size(&Number::UInt(42)), size);
+        assert_eq!(cast_as_size(&Number::Float(42.0)), size);
+    }
+
     #[test]
     fn test_cast_with_schema() {
         let value = Value::Number(Number::UInt(42));
@@ -92,6 +120,27 @@ mod tests {
         assert_eq!(cast(&value, &schema), Value::Number(Number::Int(42)));
     }
 
+    #[test]
+    fn test_cast_datetime_as_string() {
+        let text = "2024-02-29t10:30:00.500z";
+        let value = Value::Datetime(text.parse().unwrap(), text.into());
+
+        assert_eq!(cast(&value, &Schema::String), Value::String(text.into()));
+        assert_eq!(cast(&value, &Schema::DateTime), value);
+    }
+
+    #[test]
+    fn test_cast_duration_and_size_as_string() {
+        let value = Value::Duration(std::time::Duration::from_secs(60), "60s".into());
+        assert_eq!(cast(&value, &Schema::String), Value::String("60s".into()));
+
+        let value = Value::Size(1_000_000_000, "1000MB".into());
+        assert_eq!(
+            cast(&value, &Schema::String),
+            Value::String("1000MB".into())
+        );
+    }
+
     #[test]
     fn test_cast_no_change() {
         let value = Value::String("hello".into());
@@ -117,4 +166,21 @@ mod tests {
         expected_map.insert("port".into(), Value::Number(Number::Int(8080)));
         assert_eq!(result, Value::Dict(expected_map));
     }
+
+    #[test]
+    fn test_cast_array() {
+        let value = Value::Array(vec![
+            Value::Number(Number::UInt(1)),
+            Value::Number(Number::Float(2.0)),
+        ]);
+        let schema = Schema::Array(Box::new(Schema::Integer));
+
+        assert_eq!(
+            cast(&value, &schema),
+            Value::Array(vec![
+                Value::Number(Number::Int(1)),
+                Value::Number(Number::Int(2)),
+            ])
+        );
+    }
 }
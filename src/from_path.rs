@@ -0,0 +1,52 @@
+use kernel::{env::ProcessEnvironment, error::Error, options::ParseOptions, value::Value};
+
+use super::from_str::parse_config;
+use super::from_str_with_schema::apply_schema;
+use super::include::Includes;
+
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::Path;
+
+/// Reads a config file. `include` paths in it resolve against its directory.
+pub fn from_path<P, T>(path: P) -> Result<T, Error>
+where
+    P: AsRef<Path>,
+    T: DeserializeOwned,
+{
+    from_path_with_options(path, ParseOptions::default())
+}
+
+pub fn from_path_with_options<P, T>(path: P, options: ParseOptions) -> Result<T, Error>
+where
+    P: AsRef<Path>,
+    T: DeserializeOwned,
+{
+    let path = path.as_ref();
+    let contents = fs::read_to_string(path)?;
+    let value = parse_config(
+        &contents,
+        options,
+        &ProcessEnvironment,
+        Includes::for_file(path),
+    )?;
+    T::deserialize(value).map_err(Error::Serde)
+}
+
+pub fn from_path_with_schema<P1, P2>(config_path: P1, schema_path: P2) -> Result<Value, Error>
+where
+    P1: AsRef<Path>,
+    P2: AsRef<Path>,
+{
+    let (config_path, schema_path) = (config_path.as_ref(), schema_path.as_ref());
+    let config_contents = fs::read_to_string(config_path)?;
+    let schema_contents = fs::read_to_string(schema_path)?;
+
+    let value = parse_config(
+        &config_contents,
+        ParseOptions::default(),
+        &ProcessEnvironment,
+        Includes::for_file(config_path),
+    )?;
+    apply_schema(value, &schema_contents, Includes::for_file(schema_path))
+}
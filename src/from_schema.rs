@@ -1,12 +1,33 @@
-use kernel::{error::Error, parse::Parse, schema::Schema, tokenize::Tokenize};
+use kernel::{
+    error::{Error, Errors},
+    parse::Parse,
+    schema::Schema,
+    tokenize::Tokenize,
+};
 
 use lexer::{Lexer, SchemaSource};
 
 use parser::Parser;
 
-pub fn from_schema(input: &str) -> Result<Schema, Error> {
+use super::include::Includes;
+
+pub fn from_schema(input: &str, mut includes: Includes) -> Result<Schema, Error> {
     let source = SchemaSource::new(input.to_string());
     let tokens = Lexer::tokenize(source)?;
-    let value: Schema = Parser::parse(tokens)?;
+    let tokens = includes.expand(tokens, &SchemaSource::new)?;
+    let value: Schema = Parser::parse(tokens).map_err(|e| includes.locate(e))?;
     Ok(value)
 }
+
+pub fn from_schema_recovering(input: &str, mut includes: Includes) -> (Schema, Errors) {
+    let source = SchemaSource::new(input.to_string());
+    let (tokens, token_errors) = Lexer::tokenize_all(source);
+    let (tokens, include_errors) = includes.expand_all(tokens, &SchemaSource::new);
+    let (schema, parse_errors): (Schema, _) = Parser::parse_all(tokens);
+
+    let mut errors = Errors::new();
+    errors.extend(token_errors);
+    errors.extend(include_errors);
+    errors.extend(parse_errors.into_iter().map(|e| includes.locate(e)));
+    (schema, errors)
+}
@@ -1,19 +1,91 @@
-use kernel::{error::Error, parse::Parse, tokenize::Tokenize, value::Value};
+use kernel::{
+    env::{Environment, ProcessEnvironment},
+    error::{Error, Errors},
+    options::ParseOptions,
+    parse::Parse,
+    tokenize::Tokenize,
+    value::Value,
+};
 use lexer::{ConfigSource, Lexer};
-use parser::Parser;
+use parser::{Entry, Parser};
 use serde::de::DeserializeOwned;
 
-fn parse_config(input: &str) -> Result<Value, Error> {
-    let source = ConfigSource::new(input.to_string());
-    let tokens = Lexer::tokenize(source)?;
-    let value: Value = Parser::parse(tokens)?;
+use super::include::Includes;
+
+pub(crate) fn parse_config(
+    input: &str,
+    options: ParseOptions,
+    env: &dyn Environment,
+    mut includes: Includes,
+) -> Result<Value, Error> {
+    let new_source = |input| ConfigSource::new(input).with_options(options.clone());
+    let tokens = Lexer::tokenize(new_source(input.to_string()))?;
+    let tokens = includes.expand(tokens, &new_source)?;
+    let value: Value =
+        Parser::parse_with_env(tokens, options, env).map_err(|e| includes.locate(e))?;
     Ok(value)
 }
 
+/// Like `parse_config`, keeping one entry per key for layering. `includes`
+/// is left holding the files the entries may come from.
+pub(crate) fn parse_config_entries(
+    input: &str,
+    options: ParseOptions,
+    env: &dyn Environment,
+    includes: &mut Includes,
+) -> Result<Vec<Entry>, Error> {
+    let new_source = |input| ConfigSource::new(input).with_options(options.clone());
+    let tokens = Lexer::tokenize(new_source(input.to_string()))?;
+    let tokens = includes.expand(tokens, &new_source)?;
+    Parser::parse_entries(tokens, options, env).map_err(|e| includes.locate(e))
+}
+
+pub(crate) fn parse_config_recovering(input: &str, mut includes: Includes) -> (Value, Errors) {
+    let source = ConfigSource::new(input.to_string());
+    let (tokens, token_errors) = Lexer::tokenize_all(source);
+    let (tokens, include_errors) = includes.expand_all(tokens, &ConfigSource::new);
+    let (value, parse_errors): (Value, _) = Parser::parse_all(tokens);
+
+    let mut errors = Errors::new();
+    errors.extend(token_errors);
+    errors.extend(include_errors);
+    errors.extend(parse_errors.into_iter().map(|e| includes.locate(e)));
+    (value, errors)
+}
+
 pub fn from_str<T>(input: &str) -> Result<T, Error>
 where
     T: DeserializeOwned,
 {
-    let value = parse_config(input)?;
-    T::deserialize(value).map_err(|e| Error::Serde(e.to_string()))
+    from_str_with_options(input, ParseOptions::default())
+}
+
+pub fn from_str_with_options<T>(input: &str, options: ParseOptions) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    from_str_with_env(input, options, &ProcessEnvironment)
+}
+
+/// Like `from_str_with_options`, looking up `${env:NAME}` references in `env`
+/// instead of the process environment.
+pub fn from_str_with_env<T>(
+    input: &str,
+    options: ParseOptions,
+    env: &dyn Environment,
+) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let value = parse_config(input, options, env, Includes::new())?;
+    T::deserialize(value).map_err(Error::Serde)
+}
+
+pub fn from_str_recovering<T>(input: &str) -> Result<T, Errors>
+where
+    T: DeserializeOwned,
+{
+    let (value, errors) = parse_config_recovering(input, Includes::new());
+    let value = errors.into_result(value)?;
+    T::deserialize(value).map_err(|e| Error::Serde(e).into())
 }
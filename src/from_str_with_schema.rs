@@ -1,15 +1,62 @@
-use kernel::{error::Error, value::Value};
+use kernel::{
+    env::{Environment, ProcessEnvironment},
+    error::{Error, Errors},
+    options::ParseOptions,
+    schema::Schema,
+    value::Value,
+};
 
 use super::cast::cast;
-use super::from_schema::from_schema;
-use super::from_str;
-use super::validation::validate;
+use super::from_schema::{from_schema, from_schema_recovering};
+use super::from_str::{parse_config, parse_config_recovering};
+use super::include::Includes;
+use super::validation::{validate, validate_all};
 
 pub fn from_str_with_schema(input: &str, schema: &str) -> Result<Value, Error> {
-    let value = from_str(input)?;
-    let schema = from_schema(schema)?;
-    validate(&value, &schema)?;
-    let value = cast(&value, &schema);
+    from_str_with_schema_and_env(input, schema, ParseOptions::default(), &ProcessEnvironment)
+}
+
+/// Like `from_str_with_schema`, with options and the environment that
+/// `${env:NAME}` references and prefixed overrides are read from. Overrides
+/// are validated against the schema like the values in `input`.
+pub fn from_str_with_schema_and_env(
+    input: &str,
+    schema: &str,
+    options: ParseOptions,
+    env: &dyn Environment,
+) -> Result<Value, Error> {
+    let value = parse_config(input, options, env, Includes::new())?;
+    apply_schema(value, schema, Includes::new())
+}
+
+pub(crate) fn apply_schema(
+    value: Value,
+    schema: &str,
+    schema_includes: Includes,
+) -> Result<Value, Error> {
+    let schema = from_schema(schema, schema_includes)?;
+    validate_and_cast(value, &schema)
+}
+
+pub(crate) fn validate_and_cast(value: Value, schema: &Schema) -> Result<Value, Error> {
+    validate(&value, schema)?;
+    let value = cast(&value, schema);
 
     Ok(value)
 }
+
+pub fn from_str_with_schema_recovering(input: &str, schema: &str) -> Result<Value, Errors> {
+    let (value, mut errors) = parse_config_recovering(input, Includes::new());
+    let (schema, schema_errors) = from_schema_recovering(schema, Includes::new());
+
+    // A partially parsed schema would report every key of its broken lines
+    // as unknown, so the config is only checked against a complete schema.
+    if schema_errors.is_empty() {
+        errors.extend(validate_all(&value, &schema));
+    } else {
+        errors.extend(schema_errors);
+    }
+
+    errors.into_result(())?;
+    Ok(cast(&value, &schema))
+}
@@ -0,0 +1,284 @@
+use kernel::{
+    error::Error,
+    source::TryFromSource,
+    span::Span,
+    token::{SpannedToken, Token},
+    tokenize::Tokenize,
+};
+use lexer::Lexer;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How many `include` directives may be nested inside each other.
+pub(crate) const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Replaces `include 'path'` lines with the tokens of the named file, so the
+/// parser sees a single stream and checks duplicates and conflicts across files.
+pub(crate) struct Includes {
+    /// The file the input was read from, if any.
+    root: Option<PathBuf>,
+    /// The included files currently being expanded, outermost first.
+    chain: Vec<PathBuf>,
+    /// Every file included so far; the spans of its tokens have its index
+    /// plus one as their source.
+    sources: Vec<PathBuf>,
+}
+
+impl Includes {
+    /// For input without a file of its own; paths resolve against the working directory.
+    pub(crate) fn new() -> Self {
+        Self {
+            root: None,
+            chain: Vec::new(),
+            sources: Vec::new(),
+        }
+    }
+
+    /// For input read from `path`; paths resolve against its directory.
+    pub(crate) fn for_file(path: &Path) -> Self {
+        Self {
+            root: Some(path.to_path_buf()),
+            chain: Vec::new(),
+            sources: Vec::new(),
+        }
+    }
+
+    /// The file `span` is in, or `None` for input without a file of its own.
+    pub(crate) fn path_of(&self, span: Span) -> Option<&Path> {
+        match span.source {
+            0 => self.root.as_deref(),
+            source => self.sources.get(source - 1).map(PathBuf::as_path),
+        }
+    }
+
+    /// Wraps an error found in an included file in `Error::Included`, so it
+    /// names the file its line and column refer to.
+    pub(crate) fn locate(&self, error: impl Into<Error>) -> Error {
+        let error = error.into();
+        match error.span().filter(|span| span.source != 0) {
+            Some(span) => match self.path_of(span) {
+                Some(path) => Error::Included {
+                    path: path.display().to_string(),
+                    source: Box::new(error),
+                },
+                None => error,
+            },
+            None => error,
+        }
+    }
+
+    pub(crate) fn expand<S, F>(
+        &mut self,
+        tokens: Vec<SpannedToken>,
+        new_source: &F,
+    ) -> Result<Vec<SpannedToken>, Error>
+    where
+        S: TryFromSource,
+        F: Fn(String) -> S,
+    {
+        let (tokens, errors) = self.expand_all(tokens, new_source);
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(tokens),
+        }
+    }
+
+    /// Expands every include it can and collects the errors of the others,
+    /// whose lines are dropped.
+    pub(crate) fn expand_all<S, F>(
+        &mut self,
+        tokens: Vec<SpannedToken>,
+        new_source: &F,
+    ) -> (Vec<SpannedToken>, Vec<Error>)
+    where
+        S: TryFromSource,
+        F: Fn(String) -> S,
+    {
+        let mut expanded = Vec::with_capacity(tokens.len());
+        let mut errors = Vec::new();
+        let mut section_header: &[SpannedToken] = &[];
+
+        for line in tokens.split_inclusive(|t| Self::is_line_end(&t.token)) {
+            let Some(path) = Self::include_path(line) else {
+                if line.first().is_some_and(|t| t.token == Token::LeftBracket) {
+                    section_header = line;
+                }
+                expanded.extend_from_slice(line);
+                continue;
+            };
+
+            let (included, include_errors) = self.include(path, new_source);
+            expanded.extend(included);
+            expanded.extend(line.last().filter(|t| Self::is_line_end(&t.token)).cloned());
+            // The included file ends the sections it opened, so the current
+            // one is opened again for the lines below the include.
+            expanded.extend_from_slice(section_header);
+            errors.extend(include_errors);
+        }
+
+        (expanded, errors)
+    }
+
+    fn is_line_end(token: &Token) -> bool {
+        matches!(token, Token::Newline | Token::Eof)
+    }
+
+    /// The path of a line of the form `include 'path'`.
+    fn include_path(line: &[SpannedToken]) -> Option<&str> {
+        match line {
+            [
+                SpannedToken {
+                    token: Token::Ident(keyword),
+                    ..
+                },
+                SpannedToken {
+                    token: Token::QuotedIdent(path),
+                    ..
+                },
+                rest @ ..,
+            ] if keyword == "include" && rest.iter().all(|t| Self::is_line_end(&t.token)) => {
+                Some(path)
+            }
+            _ => None,
+        }
+    }
+
+    fn include<S, F>(&mut self, path: &str, new_source: &F) -> (Vec<SpannedToken>, Vec<Error>)
+    where
+        S: TryFromSource,
+        F: Fn(String) -> S,
+    {
+        let path = self.base().join(path);
+        if let Err(error) = self.enter(&path) {
+            return (Vec::new(), vec![error]);
+        }
+
+        let result = self.read(&path, new_source);
+        self.chain.pop();
+        result
+    }
+
+    fn base(&self) -> &Path {
+        self.chain
+            .last()
+            .or(self.root.as_ref())
+            .and_then(|path| path.parent())
+            .unwrap_or(Path::new(""))
+    }
+
+    fn enter(&mut self, path: &Path) -> Result<(), Error> {
+        let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let target = canonical(path);
+
+        if self.files().any(|file| canonical(file) == target) {
+            return Err(Error::IncludeCycle {
+                chain: self.describe(path),
+            });
+        }
+
+        if self.chain.len() >= MAX_INCLUDE_DEPTH {
+            return Err(Error::IncludeDepth {
+                chain: self.describe(path),
+                max: MAX_INCLUDE_DEPTH,
+            });
+        }
+
+        self.chain.push(path.to_path_buf());
+        Ok(())
+    }
+
+    fn read<S, F>(&mut self, path: &Path, new_source: &F) -> (Vec<SpannedToken>, Vec<Error>)
+    where
+        S: TryFromSource,
+        F: Fn(String) -> S,
+    {
+        let wrap = |error: Error| Error::Included {
+            path: path.display().to_string(),
+            source: Box::new(error),
+        };
+
+        let input = match fs::read_to_string(path) {
+            Ok(input) => input,
+            Err(error) => return (Vec::new(), vec![wrap(error.into())]),
+        };
+
+        // The `Eof` is kept to tell the parser where the file's sections end.
+        let (mut tokens, token_errors) = Lexer::tokenize_all(new_source(input));
+        self.sources.push(path.to_path_buf());
+        for token in &mut tokens {
+            token.span.source = self.sources.len();
+        }
+
+        let mut errors: Vec<Error> = token_errors
+            .into_iter()
+            .map(|error| wrap(error.into()))
+            .collect();
+        let (tokens, include_errors) = self.expand_all(tokens, new_source);
+        errors.extend(include_errors);
+        (tokens, errors)
+    }
+
+    fn files(&self) -> impl Iterator<Item = &PathBuf> {
+        self.root.iter().chain(&self.chain)
+    }
+
+    /// The chain of files leading to `path`, e.g. `a.conf -> b.conf -> a.conf`.
+    fn describe(&self, path: &Path) -> String {
+        self.files()
+            .map(PathBuf::as_path)
+            .chain([path])
+            .map(|file| file.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_path;
+    use kernel::value::Value;
+
+    #[test]
+    fn test_include_depth() {
+        let dir = std::env::temp_dir().join(format!("sorbe_tpl_include_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for level in 0..=MAX_INCLUDE_DEPTH + 1 {
+            let contents = format!("include 'level{}.conf'\nlevel{} = true\n", level + 1, level);
+            fs::write(dir.join(format!("level{}.conf", level)), contents).unwrap();
+        }
+        fs::write(dir.join(format!("level{}.conf", MAX_INCLUDE_DEPTH + 2)), "").unwrap();
+
+        let result: Result<Value, Error> = from_path(dir.join("level0.conf"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(
+            result,
+            Err(Error::IncludeDepth { chain, max })
+                if max == MAX_INCLUDE_DEPTH
+                    && chain.matches(" -> ").count() == MAX_INCLUDE_DEPTH + 1
+        ));
+    }
+
+    #[test]
+    fn test_include_path() {
+        let line = |tokens: Vec<Token>| -> Vec<SpannedToken> {
+            tokens.into_iter().map(SpannedToken::from).collect()
+        };
+
+        let include = line(vec![
+            Token::Ident("include".into()),
+            Token::QuotedIdent("a.conf".into()),
+            Token::Newline,
+        ]);
+        assert_eq!(Includes::include_path(&include), Some("a.conf"));
+
+        let key = line(vec![
+            Token::Ident("include".into()),
+            Token::Separator,
+            Token::QuotedIdent("a.conf".into()),
+        ]);
+        assert_eq!(Includes::include_path(&key), None);
+    }
+}
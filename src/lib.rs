@@ -1,17 +1,31 @@
+mod builder;
 mod cast;
+mod from_path;
 mod from_reader;
 mod from_schema;
 mod from_str;
 mod from_str_with_schema;
+mod include;
+mod origin;
+mod overrides;
 mod validation;
 
+pub use builder::ConfigBuilder;
+pub use from_path::*;
 pub use from_reader::*;
 pub use from_str::*;
 pub use from_str_with_schema::*;
+pub use origin::{Origin, Origins};
+pub use overrides::{apply_overrides, apply_overrides_with_env};
 pub use sorbe_macro::config;
 
 pub use kernel::{
-    error::Error,
+    datetime::Datetime,
+    de::DeserializeError,
+    diagnostic::{DiagnosticRenderer, Style},
+    env::{Environment, ProcessEnvironment},
+    error::{Error, Errors},
+    options::{EmptyValue, ParseOptions},
     schema::Schema,
     shared::Map,
     value::{Number, Value},
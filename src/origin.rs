@@ -0,0 +1,114 @@
+use parser::display_key;
+
+use std::fmt::Display;
+use std::path::PathBuf;
+
+/// Where a value of a layered configuration was set.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    /// The text given to `ConfigBuilder::with_defaults`.
+    Defaults { line: usize },
+    /// A config file, or a file included from one.
+    File { path: PathBuf, line: usize },
+    /// A prefixed environment variable.
+    Env { name: String },
+    /// A `key.path=value` override.
+    Override { arg: String },
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Origin::Defaults { line } => write!(f, "defaults, line {}", line),
+            Origin::File { path, line } => write!(f, "{}, line {}", path.display(), line),
+            Origin::Env { name } => write!(f, "environment variable {}", name),
+            Origin::Override { arg } => write!(f, "override '{}'", arg),
+        }
+    }
+}
+
+/// The origin of every key of a layered configuration.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Origins {
+    /// The keys that were set, each replacing the keys below it.
+    keys: Vec<(Vec<String>, Origin)>,
+}
+
+impl Origins {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The origin of `key`, written as in a config file, e.g. `server.port`.
+    /// Keys inside an array that was set as a whole, such as `hosts.0`, have
+    /// the origin of the array.
+    pub fn origin_of(&self, key: &str) -> Option<&Origin> {
+        self.keys
+            .iter()
+            .rev()
+            .find(|(key_parts, _)| {
+                let set = display_key(key_parts);
+                key.strip_prefix(&set)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+            .map(|(_, origin)| origin)
+    }
+
+    /// Every key that was set, with its origin, in the order they were set.
+    pub fn iter(&self) -> impl Iterator<Item = (String, &Origin)> {
+        self.keys
+            .iter()
+            .map(|(key_parts, origin)| (display_key(key_parts), origin))
+    }
+
+    pub(crate) fn record(&mut self, key_parts: &[String], origin: Origin) {
+        self.keys.retain(|(set, _)| !set.starts_with(key_parts));
+        self.keys.push((key_parts.to_vec(), origin));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str) -> Vec<String> {
+        key.split('.').map(String::from).collect()
+    }
+
+    fn file(line: usize) -> Origin {
+        Origin::File {
+            path: "app.conf".into(),
+            line,
+        }
+    }
+
+    #[test]
+    fn test_origin_of() {
+        let mut origins = Origins::new();
+        origins.record(&key("server.port"), file(1));
+        origins.record(&key("server.hosts"), file(2));
+        origins.record(&key("server.hosts.1"), file(3));
+
+        assert_eq!(origins.origin_of("server.port"), Some(&file(1)));
+        assert_eq!(origins.origin_of("server.hosts.0"), Some(&file(2)));
+        assert_eq!(origins.origin_of("server.hosts.1"), Some(&file(3)));
+        assert_eq!(origins.origin_of("server.portal"), None);
+        assert_eq!(origins.origin_of("server"), None);
+    }
+
+    #[test]
+    fn test_record_replaces_keys_below() {
+        let env = Origin::Env {
+            name: "HOSTS".into(),
+        };
+        let mut origins = Origins::new();
+        origins.record(&key("hosts.0"), file(1));
+        origins.record(&key("hosts.1"), file(2));
+        origins.record(&key("hosts"), env.clone());
+
+        assert_eq!(
+            origins.iter().collect::<Vec<_>>(),
+            vec![("hosts".to_string(), &env)]
+        );
+    }
+}
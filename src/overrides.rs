@@ -0,0 +1,271 @@
+use kernel::{
+    env::{Environment, ProcessEnvironment},
+    error::{Error, ParseError, SyntaxValidationError},
+    options::ParseOptions,
+    shared::Map,
+    token::Token,
+    tokenize::Tokenize,
+    value::Value,
+};
+use lexer::{ConfigSource, Lexer};
+use parser::{Entry, Parser, display_key};
+
+use std::cmp::Ordering;
+
+/// Applies `key.path=value` arguments, as passed to `--set`, on top of
+/// `value`, later arguments taking precedence. Each argument is read like a
+/// line of a config file, so its key follows the same rules and may not turn
+/// a table into a value or a value into a table. Nothing is changed if any
+/// argument is rejected.
+pub fn apply_overrides<I, S>(value: &mut Value, overrides: I) -> Result<(), Error>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    apply_overrides_with_env(value, overrides, &ProcessEnvironment)
+}
+
+/// Like `apply_overrides`, looking up `${env:NAME}` references in `env`
+/// instead of the process environment.
+pub fn apply_overrides_with_env<I, S>(
+    value: &mut Value,
+    overrides: I,
+    env: &dyn Environment,
+) -> Result<(), Error>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut updated = value.clone();
+    for arg in overrides {
+        let arg = arg.as_ref();
+        apply_override(&mut updated, arg, env).map_err(|error| Error::Override {
+            arg: arg.to_string(),
+            source: Box::new(error),
+        })?;
+    }
+
+    *value = updated;
+    Ok(())
+}
+
+fn apply_override(value: &mut Value, arg: &str, env: &dyn Environment) -> Result<(), Error> {
+    set_entries(value, override_entries(arg, env)?, set_entry)
+}
+
+/// Reads a single `key.path=value` argument. Anything else a config file
+/// may hold, such as several lines or a section header, is rejected.
+pub(crate) fn override_entries(arg: &str, env: &dyn Environment) -> Result<Vec<Entry>, Error> {
+    let tokens = Lexer::tokenize(ConfigSource::new(arg.to_string()))?;
+    let is_section_header = tokens
+        .first()
+        .is_some_and(|t| t.token == Token::LeftBracket);
+    if is_section_header || tokens.iter().any(|t| t.token == Token::Newline) {
+        return Err(Error::InvalidOverride);
+    }
+
+    // Without newlines the argument is at most one statement, which an
+    // inline table value flattens into several entries.
+    let entries = Parser::parse_entries(tokens, ParseOptions::default(), env)?;
+    if entries.is_empty() {
+        return Err(Error::InvalidOverride);
+    }
+    Ok(entries)
+}
+
+/// Where a key path runs into the existing value, as the number of key parts
+/// that name the offending key.
+#[derive(Debug, PartialEq)]
+enum PathError {
+    Conflict(usize),
+    IndexOutOfSequence(usize),
+}
+
+/// Writes each entry with `set`, in order. List indices may come in any
+/// order within one layer, as in a config file, so an entry past the end of
+/// a list waits until the entries before it have been written. What is still
+/// out of sequence after that is reported.
+pub(crate) fn set_entries<T>(
+    target: &mut Value,
+    entries: Vec<T>,
+    mut set: impl FnMut(&mut Value, &T) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let mut pending = entries;
+    loop {
+        let count = pending.len();
+        let mut deferred = Vec::new();
+        let mut first_error = None;
+        for entry in pending {
+            match set(target, &entry) {
+                Ok(()) => {}
+                Err(error) if is_index_out_of_sequence(&error) => {
+                    first_error.get_or_insert(error);
+                    deferred.push(entry);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        match first_error {
+            None => return Ok(()),
+            Some(error) if deferred.len() == count => return Err(error),
+            Some(_) => pending = deferred,
+        }
+    }
+}
+
+fn is_index_out_of_sequence(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::Parse(ParseError::Syntax(
+            SyntaxValidationError::IndexOutOfSequence { .. }
+                | SyntaxValidationError::EnvOverrideIndexOutOfSequence { .. }
+        ))
+    )
+}
+
+/// Writes the value of `entry` at its key, creating the tables and lists
+/// along the way as a config file would.
+pub(crate) fn set_entry(target: &mut Value, entry: &Entry) -> Result<(), Error> {
+    let span = entry.span;
+    set_entry_reporting(target, entry, |error, key| match error {
+        PathError::Conflict(_) => SyntaxValidationError::KeyPathConflict { key, span },
+        PathError::IndexOutOfSequence(_) => SyntaxValidationError::IndexOutOfSequence { key, span },
+    })
+}
+
+/// Like `set_entry`, for an entry taken from the environment variable
+/// `name`, which is what errors point at.
+pub(crate) fn set_env_entry(target: &mut Value, entry: &Entry, name: &str) -> Result<(), Error> {
+    let name = name.to_string();
+    set_entry_reporting(target, entry, |error, key| match error {
+        PathError::Conflict(_) => SyntaxValidationError::EnvOverrideConflict { name, key },
+        PathError::IndexOutOfSequence(_) => {
+            SyntaxValidationError::EnvOverrideIndexOutOfSequence { name, key }
+        }
+    })
+}
+
+fn set_entry_reporting(
+    target: &mut Value,
+    entry: &Entry,
+    report: impl FnOnce(PathError, String) -> SyntaxValidationError,
+) -> Result<(), Error> {
+    set_path(target, &entry.key_parts, entry.value.clone()).map_err(|error| {
+        let len = match error {
+            PathError::Conflict(len) | PathError::IndexOutOfSequence(len) => len,
+        };
+        let key = display_key(&entry.key_parts[..len]);
+        Error::Parse(ParseError::Syntax(report(error, key)))
+    })
+}
+
+fn set_path(target: &mut Value, key_parts: &[String], value: Value) -> Result<(), PathError> {
+    let (last, parents) = key_parts
+        .split_last()
+        .unwrap_or_else(|| unreachable!("key should not be empty"));
+
+    let mut current = target;
+    for (depth, next) in key_parts.iter().skip(1).enumerate() {
+        let (slot, is_new) = slot(current, &parents[depth], depth)?;
+        if is_new {
+            *slot = container_for(next);
+        }
+        current = slot;
+    }
+
+    let (slot, _) = slot(current, last, parents.len())?;
+    let replaces_container = match slot {
+        Value::Dict(_) => !matches!(value, Value::Dict(_)),
+        Value::Array(_) => !matches!(value, Value::Array(_)),
+        _ => false,
+    };
+    if replaces_container {
+        return Err(PathError::Conflict(key_parts.len()));
+    }
+
+    *slot = value;
+    Ok(())
+}
+
+/// The place of the key part at `depth` inside `target`, and whether it was
+/// just created.
+fn slot<'v>(
+    target: &'v mut Value,
+    part: &str,
+    depth: usize,
+) -> Result<(&'v mut Value, bool), PathError> {
+    match target {
+        Value::Dict(map) => {
+            let is_new = !map.contains_key(part);
+            Ok((map.entry(part.to_string()).or_insert(Value::Null), is_new))
+        }
+        Value::Array(items) => {
+            let index = part
+                .parse::<usize>()
+                .map_err(|_| PathError::Conflict(depth))?;
+            match index.cmp(&items.len()) {
+                Ordering::Less => Ok((&mut items[index], false)),
+                Ordering::Equal => {
+                    items.push(Value::Null);
+                    Ok((&mut items[index], true))
+                }
+                Ordering::Greater => Err(PathError::IndexOutOfSequence(depth + 1)),
+            }
+        }
+        _ => Err(PathError::Conflict(depth)),
+    }
+}
+
+fn container_for(next: &str) -> Value {
+    if next.chars().all(|c| c.is_ascii_digit()) {
+        Value::Array(Vec::new())
+    } else {
+        Value::Dict(Map::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+
+    fn key(key: &str) -> Vec<String> {
+        key.split('.').map(String::from).collect()
+    }
+
+    #[test]
+    fn test_set_path() {
+        let mut value: Value = from_str("server.port = 80\nhosts = [a]").unwrap();
+
+        set_path(&mut value, &key("server.port"), Value::String("x".into())).unwrap();
+        set_path(
+            &mut value,
+            &key("server.tls.cert"),
+            Value::String("c".into()),
+        )
+        .unwrap();
+        set_path(&mut value, &key("hosts.1"), Value::String("b".into())).unwrap();
+        set_path(&mut value, &key("routes.0.path"), Value::String("/".into())).unwrap();
+        assert_eq!(
+            value.to_string(),
+            "{server: {port: x, tls: {cert: c}}, hosts: [a, b], routes: [{path: /}]}"
+        );
+    }
+
+    #[test]
+    fn test_set_path_conflicts() {
+        let mut value: Value = from_str("server.port = 80\nhosts = [a]").unwrap();
+
+        let cases = [
+            ("server.port.number", PathError::Conflict(2)),
+            ("server", PathError::Conflict(1)),
+            ("hosts.name", PathError::Conflict(1)),
+            ("hosts.2", PathError::IndexOutOfSequence(2)),
+        ];
+        for (path, expected) in cases {
+            let result = set_path(&mut value, &key(path), Value::Null);
+            assert_eq!(result, Err(expected), "{}", path);
+        }
+    }
+}
@@ -6,26 +6,64 @@ use kernel::{
 };
 
 pub fn validate(value: &Value, schema: &Schema) -> Result<(), Error> {
-    if let Some(result) = validate_basic_types(value, schema) {
-        return result;
+    match validate_all(value, schema).into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+pub fn validate_all(value: &Value, schema: &Schema) -> Vec<Error> {
+    let mut errors = Vec::new();
+    collect_errors(value, schema, "", &mut errors);
+    errors
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn collect_errors(value: &Value, schema: &Schema, path: &str, errors: &mut Vec<Error>) {
+    if let Some(result) = validate_basic_types(value, schema, path) {
+        if let Err(error) = result {
+            errors.push(error);
+        }
+        return;
     }
 
     match (value, schema) {
-        (value, Schema::Optional(inner_schema)) => validate(value, inner_schema),
+        (value, Schema::Optional(inner_schema)) => {
+            collect_errors(value, inner_schema, path, errors)
+        }
+
+        (Value::Array(items), Schema::Array(item_schema)) => {
+            for (index, item) in items.iter().enumerate() {
+                collect_errors(
+                    item,
+                    item_schema,
+                    &join_path(path, &index.to_string()),
+                    errors,
+                );
+            }
+        }
 
         (Value::Dict(value_map), Schema::Dict(schema_map)) => {
-            validate_dict_keys(value_map, schema_map)?;
+            validate_dict_keys(value_map, schema_map, path, errors);
 
             for (key, schema) in schema_map {
-                let value = &value_map[key];
-                validate(value, schema)?;
+                if let Some(value) = value_map.get(key) {
+                    collect_errors(value, schema, &join_path(path, key), errors);
+                }
             }
-            Ok(())
         }
 
-        _ => Err(Error::TypeMismatch {
+        _ => errors.push(Error::TypeMismatch {
+            key: path.to_string(),
             expected: schema.to_string(),
-            found: value.to_string(),
+            found: value.kind().to_string(),
         }),
     }
 }
@@ -33,35 +71,63 @@ pub fn validate(value: &Value, schema: &Schema) -> Result<(), Error> {
 fn validate_dict_keys(
     value_map: &Map<String, Value>,
     schema_map: &Map<String, Schema>,
-) -> Result<(), Error> {
+    path: &str,
+    errors: &mut Vec<Error>,
+) {
     for key in value_map.keys() {
         if !schema_map.contains_key(key) {
-            return Err(Error::UnknownKey { key: key.clone() });
+            errors.push(Error::UnknownKey {
+                key: join_path(path, key),
+            });
         }
     }
 
     for key in schema_map.keys() {
         if !value_map.contains_key(key) {
-            return Err(Error::MissingKey { key: key.clone() });
+            errors.push(Error::MissingKey {
+                key: join_path(path, key),
+            });
         }
     }
-
-    Ok(())
 }
 
-fn validate_basic_types(value: &Value, schema: &Schema) -> Option<Result<(), Error>> {
+fn validate_basic_types(value: &Value, schema: &Schema, path: &str) -> Option<Result<(), Error>> {
     match (value, schema) {
-        (Value::String(_), Schema::String) => Some(Ok(())),
+        // A bare value that reads as a datetime, duration or size is still
+        // text to a `string` field, as it was before they were recognized.
+        (
+            Value::String(_) | Value::Datetime(..) | Value::Duration(..) | Value::Size(..),
+            Schema::String,
+        ) => Some(Ok(())),
         (Value::Bool(_), Schema::Bool) => Some(Ok(())),
-        (Value::Number(num), Schema::Integer) => Some(validate_as_integer(num)),
-        (Value::Number(num), Schema::UnsignedInteger) => Some(validate_as_unsigned_integer(num)),
+        (Value::Number(num), Schema::Integer) => Some(validate_as_integer(num, path)),
+        (Value::Number(num), Schema::UnsignedInteger) => {
+            Some(validate_as_unsigned_integer(num, path))
+        }
         (Value::Number(_), Schema::Float) => Some(Ok(())),
+        (Value::Datetime(dt, _), Schema::Date | Schema::Time | Schema::DateTime)
+            if dt.kind() == schema.to_string() =>
+        {
+            Some(Ok(()))
+        }
+        (Value::Duration(..), Schema::Duration) => Some(Ok(())),
+        (Value::Size(..), Schema::Size) => Some(Ok(())),
+        (Value::Number(num), Schema::Size) => Some(
+            validate_as_unsigned_integer(num, path).map_err(|error| match error {
+                Error::TypeMismatch { key, found, .. } => Error::TypeMismatch {
+                    key,
+                    expected: schema.to_string(),
+                    found,
+                },
+                other => other,
+            }),
+        ),
         (Value::Null, Schema::Optional(_)) => Some(Ok(())),
         _ => None,
     }
 }
 
-fn validate_as_integer(num: &Number) -> Result<(), Error> {
+fn validate_as_integer(num: &Number, path: &str) -> Result<(), Error> {
     match num {
         Number::Int(_) => Ok(()),
         Number::UInt(_) => Ok(()),
@@ -70,22 +136,29 @@ fn validate_as_integer(num: &Number) -> Result<(), Error> {
                 Ok(())
             } else {
                 Err(Error::TypeMismatch {
+                    key: path.to_string(),
                     expected: "integer".into(),
-                    found: f.to_string(),
+                    found: "float".into(),
                 })
             }
         }
     }
 }
 
-fn validate_as_unsigned_integer(num: &Number) -> Result<(), Error> {
+fn validate_as_unsigned_integer(num: &Number, path: &str) -> Result<(), Error> {
     match num {
         Number::UInt(_) => Ok(()),
         Number::Int(i) if *i >= 0 => Ok(()),
         Number::Float(f) if f.fract() == 0.0 && *f >= 0.0 => Ok(()),
+        Number::Float(f) if f.fract() != 0.0 => Err(Error::TypeMismatch {
+            key: path.to_string(),
+            expected: "unsigned integer".into(),
+            found: "float".into(),
+        }),
         _ => Err(Error::TypeMismatch {
+            key: path.to_string(),
             expected: "unsigned integer".into(),
-            found: num.to_string(),
+            found: "negative number".into(),
         }),
     }
 }
@@ -102,6 +175,14 @@ mod tests {
             let value = Value::String("Hello".into());
             let schema = Schema::String;
             assert!(validate(&value, &schema).is_ok());
+
+            let value = Value::Datetime("2024-02-29".parse().unwrap(), "2024-02-29".into());
+            assert!(validate(&value, &schema).is_ok());
+
+            let value = Value::Duration(std::time::Duration::from_secs(30), "30s".into());
+            assert!(validate(&value, &schema).is_ok());
+
+            assert!(validate(&Value::Size(1024, "1KiB".into()), &schema).is_ok());
         }
 
         #[test]
@@ -216,6 +297,16 @@ mod tests {
 
             assert!(validate(&value, &schema).is_ok());
         }
+
+        #[test]
+        fn test_validate_array() {
+            let value = Value::Array(vec![Value::String("a".into()), Value::String("b".into())]);
+            let schema = Schema::Array(Box::new(Schema::String));
+            assert!(validate(&value, &schema).is_ok());
+
+            let value = Value::Array(vec![]);
+            assert!(validate(&value, &schema).is_ok());
+        }
     }
 
     mod invalid {
@@ -319,7 +410,113 @@ mod tests {
             let value = Value::Dict(outer_value_map);
             let schema = Schema::Dict(outer_schema_map);
 
-            assert!(validate(&value, &schema).is_err());
+            let error = validate(&value, &schema).unwrap_err();
+            assert!(matches!(
+                error,
+                Error::TypeMismatch { key, expected, found }
+                    if key == "user.name" && expected == "string" && found == "number"
+            ));
+        }
+
+        #[test]
+        fn test_validate_nested_unknown_and_missing_keys() {
+            let mut inner_value_map = Map::new();
+            inner_value_map.insert("debug".into(), Value::Bool(true));
+
+            let mut value_map = Map::new();
+            value_map.insert("server".into(), Value::Dict(inner_value_map));
+
+            let mut inner_schema_map = Map::new();
+            inner_schema_map.insert("port".into(), Schema::Integer);
+
+            let mut schema_map = Map::new();
+            schema_map.insert("server".into(), Schema::Dict(inner_schema_map));
+
+            let value = Value::Dict(value_map);
+            let schema = Schema::Dict(schema_map);
+
+            let errors = validate_all(&value, &schema);
+            assert_eq!(errors.len(), 2);
+            assert!(matches!(&errors[0], Error::UnknownKey { key } if key == "server.debug"));
+            assert!(matches!(&errors[1], Error::MissingKey { key } if key == "server.port"));
+        }
+
+        #[test]
+        fn test_validate_reports_number_kind() {
+            let mut value_map = Map::new();
+            value_map.insert("ratio".into(), Value::Number(Number::Float(1.5)));
+            value_map.insert("count".into(), Value::Number(Number::Int(-1)));
+
+            let mut schema_map = Map::new();
+            schema_map.insert("ratio".into(), Schema::Integer);
+            schema_map.insert("count".into(), Schema::UnsignedInteger);
+
+            let value = Value::Dict(value_map);
+            let schema = Schema::Dict(schema_map);
+
+            let errors = validate_all(&value, &schema);
+            assert_eq!(errors.len(), 2);
+            assert!(matches!(
+                &errors[0],
+                Error::TypeMismatch { key, found, .. } if key == "ratio" && found == "float"
+            ));
+            assert!(matches!(
+                &errors[1],
+                Error::TypeMismatch { key, found, .. } if key == "count" && found == "negative number"
+            ));
+        }
+
+        #[test]
+        fn test_validate_array_element_type_mismatch() {
+            let mut value_map = Map::new();
+            value_map.insert(
+                "ports".into(),
+                Value::Array(vec![
+                    Value::Number(Number::UInt(80)),
+                    Value::String("https".into()),
+                ]),
+            );
+
+            let mut schema_map = Map::new();
+            schema_map.insert(
+                "ports".into(),
+                Schema::Array(Box::new(Schema::UnsignedInteger)),
+            );
+
+            let value = Value::Dict(value_map);
+            let schema = Schema::Dict(schema_map);
+
+            let error = validate(&value, &schema).unwrap_err();
+            assert!(matches!(
+                error,
+                Error::TypeMismatch { key, found, .. } if key == "ports.1" && found == "string"
+            ));
+        }
+
+        #[test]
+        fn test_validate_all_collects_every_error() {
+            let mut inner_value_map = Map::new();
+            inner_value_map.insert("name".into(), Value::Number(Number::Int(123)));
+
+            let mut value_map = Map::new();
+            value_map.insert("user".into(), Value::Dict(inner_value_map));
+            value_map.insert("extra".into(), Value::Bool(true));
+
+            let mut inner_schema_map = Map::new();
+            inner_schema_map.insert("name".into(), Schema::String);
+
+            let mut schema_map = Map::new();
+            schema_map.insert("user".into(), Schema::Dict(inner_schema_map));
+            schema_map.insert("port".into(), Schema::Integer);
+
+            let value = Value::Dict(value_map);
+            let schema = Schema::Dict(schema_map);
+
+            let errors = validate_all(&value, &schema);
+            assert_eq!(errors.len(), 3);
+            assert!(matches!(&errors[0], Error::UnknownKey { key } if key == "extra"));
+            assert!(matches!(&errors[1], Error::MissingKey { key } if key == "port"));
+            assert!(matches!(&errors[2], Error::TypeMismatch { key, .. } if key == "user.name"));
         }
     }
 }
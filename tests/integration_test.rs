@@ -1,7 +1,13 @@
 use sorbe_tpl::{
-    Error, Map, Number, Value, from_reader, from_reader_with_schema, from_str, from_str_with_schema,
+    ConfigBuilder, DiagnosticRenderer, EmptyValue, Error, Map, Number, Origin, ParseOptions,
+    ProcessEnvironment, Value, apply_overrides, apply_overrides_with_env, from_path,
+    from_path_with_schema, from_reader, from_reader_with_schema, from_str, from_str_recovering,
+    from_str_with_env, from_str_with_options, from_str_with_schema, from_str_with_schema_and_env,
+    from_str_with_schema_recovering,
 };
 
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::io::Cursor;
 
 mod valid {
@@ -130,6 +136,815 @@ mod valid {
         expected.insert("database".into(), Value::Dict(expected_database));
         assert_eq!(config, Value::Dict(expected));
     }
+
+    #[test]
+    fn test_array_values_with_schema() {
+        let config_content = r#"
+            server.allowed_hosts = [a, 'b.example.com', c]
+            server.ports = [80, 443]
+            matrix = [[1, 2], []]
+        "#;
+        let schema_content = r#"
+            server.allowed_hosts: [string]
+            server.ports: [integer]
+            matrix: [[unsigned_integer]]
+        "#;
+
+        let config = from_str_with_schema(config_content, schema_content).unwrap();
+        let Value::Dict(root) = &config else {
+            panic!("expected dict");
+        };
+        let Value::Dict(server) = &root["server"] else {
+            panic!("expected dict");
+        };
+
+        assert_eq!(
+            server["allowed_hosts"],
+            Value::Array(vec![
+                Value::String("a".into()),
+                Value::String("b.example.com".into()),
+                Value::String("c".into()),
+            ])
+        );
+        assert_eq!(
+            server["ports"],
+            Value::Array(vec![
+                Value::Number(Number::Int(80)),
+                Value::Number(Number::Int(443)),
+            ])
+        );
+        assert_eq!(root["matrix"].to_string(), "[[1, 2], []]");
+    }
+
+    #[test]
+    fn test_indexed_keys_with_schema() {
+        let config_content = r#"
+            servers.0.host = a
+            servers.0.port = 80
+            servers.1.host = b
+            servers.1.port = 8080
+        "#;
+        let schema_content = r#"
+            servers.*.host: string
+            servers.*.port: unsigned_integer
+        "#;
+
+        let config = from_str_with_schema(config_content, schema_content).unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{servers: [{host: a, port: 80}, {host: b, port: 8080}]}"
+        );
+
+        let result =
+            from_str_with_schema("servers.0.host = a\nservers.0.port = -1", schema_content);
+        assert!(matches!(
+            result,
+            Err(Error::TypeMismatch { key, .. }) if key == "servers.0.port"
+        ));
+    }
+
+    #[test]
+    fn test_inline_tables() {
+        let config_content = r#"
+            database.primary = { host = 'db1', port = 5432 }
+            database.primary.user = admin
+            database.replicas = [{ host = 'db2' }, { host = 'db3', pool = { size = 2 } }]
+            cache = {}
+        "#;
+        let schema_content = r#"
+            database.primary: { host: string, port: unsigned_integer, user: string }
+            database.replicas: [{ host: string, pool: { size: integer } }]
+            cache: {}
+        "#;
+
+        let config: Value = from_str(config_content).unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{database: {primary: {host: db1, port: 5432, user: admin}, \
+             replicas: [{host: db2}, {host: db3, pool: {size: 2}}]}, cache: {}}"
+        );
+
+        let result = from_str_with_schema(config_content, schema_content);
+        assert!(
+            matches!(result, Err(Error::MissingKey { key }) if key == "database.replicas.0.pool")
+        );
+    }
+
+    #[test]
+    fn test_datetime_values_with_schema() {
+        let config_content = r#"
+            release.date = 2024-02-29
+            release.window = 23:30:00
+            release.published = 2024-03-01T09:15:30.250+09:00
+            release.local = 2024-03-01T09:15:30
+            release.label = '2024-02-29'
+            release.tag = 2024-02-29
+        "#;
+        let schema_content = r#"
+            release.date: date
+            release.window: time
+            release.published: datetime
+            release.local: datetime
+            release.label: string
+            release.tag: string
+        "#;
+
+        let config = from_str_with_schema(config_content, schema_content).unwrap();
+        let release = match &config {
+            Value::Dict(map) => &map["release"],
+            other => panic!("expected dict, found {}", other),
+        };
+        assert_eq!(
+            release.to_string(),
+            "{date: 2024-02-29, window: 23:30:00, published: 2024-03-01T09:15:30.25+09:00, \
+             local: 2024-03-01T09:15:30, label: 2024-02-29, tag: 2024-02-29}"
+        );
+        match release {
+            Value::Dict(map) => {
+                assert_eq!(
+                    map["date"],
+                    Value::Datetime("2024-02-29".parse().unwrap(), "2024-02-29".into())
+                );
+                assert_eq!(map["label"], Value::String("2024-02-29".into()));
+                assert_eq!(map["tag"], Value::String("2024-02-29".into()));
+            }
+            other => panic!("expected dict, found {}", other),
+        }
+    }
+
+    #[test]
+    fn test_duration_and_size_values_with_schema() {
+        let config_content = r#"
+            http.timeout = 30s
+            http.keepalive = 1h30m
+            http.retry_delay = 1.5s
+            http.max_body = 512KiB
+            http.buffer = 10MB
+            http.chunk = 4096
+            http.label = 30s
+            http.limit = 10MB
+        "#;
+        let schema_content = r#"
+            http.timeout: duration
+            http.keepalive: duration
+            http.retry_delay: duration
+            http.max_body: size
+            http.buffer: size
+            http.chunk: size
+            http.label: string
+            http.limit: string
+        "#;
+
+        let config = from_str_with_schema(config_content, schema_content).unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{http: {timeout: 30s, keepalive: 1h30m, retry_delay: 1s500ms, \
+             max_body: 524288B, buffer: 10000000B, chunk: 4096B, \
+             label: 30s, limit: 10MB}}"
+        );
+        match &config {
+            Value::Dict(root) => match &root["http"] {
+                Value::Dict(http) => {
+                    assert_eq!(http["label"], Value::String("30s".into()));
+                    assert_eq!(http["limit"], Value::String("10MB".into()));
+                }
+                other => panic!("expected dict, found {}", other),
+            },
+            other => panic!("expected dict, found {}", other),
+        }
+    }
+
+    #[test]
+    fn test_extended_number_literals() {
+        let config_content = r#"
+            file.mode = 0o755
+            file.mask = 0xFF
+            file.flags = 0b1010
+            file.offset = -0x10
+            limits.requests = 1_000_000
+            limits.epsilon = 1e-3
+            limits.scale = 2.5E+2
+            limits.ceiling = inf
+            limits.floor = -inf
+        "#;
+
+        let config: Value = from_str(config_content).unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{file: {mode: 493, mask: 255, flags: 10, offset: -16}, \
+             limits: {requests: 1000000, epsilon: 0.001, scale: 250, ceiling: inf, floor: -inf}}"
+        );
+
+        let config: Value = from_str("ratio = nan").unwrap();
+        assert!(matches!(
+            config,
+            Value::Dict(map) if matches!(map["ratio"], Value::Number(Number::Float(f)) if f.is_nan())
+        ));
+    }
+
+    #[test]
+    fn test_unicode_values() {
+        let config_content = r#"
+            # アプリケーション設定
+            app.title = 'こんにちは'
+            app.banner = "ようこそ\t🎉"
+        "#;
+
+        let config: Value = from_str(config_content).unwrap();
+        let mut app = Map::new();
+        app.insert("title".into(), Value::String("こんにちは".into()));
+        app.insert("banner".into(), Value::String("ようこそ\t🎉".into()));
+        let mut expected = Map::new();
+        expected.insert("app".into(), Value::Dict(app));
+
+        assert_eq!(config, Value::Dict(expected));
+    }
+
+    #[test]
+    fn test_unicode_bare_values_with_options() {
+        let config_content = "app.title = 東京";
+
+        let result: Result<Value, Error> = from_str(config_content);
+        assert!(matches!(result, Err(Error::Lexer(_))));
+
+        let options = ParseOptions::new().with_unicode_idents(true);
+        let config: Value = from_str_with_options(config_content, options).unwrap();
+        let mut app = Map::new();
+        app.insert("title".into(), Value::String("東京".into()));
+        let mut expected = Map::new();
+        expected.insert("app".into(), Value::Dict(app));
+
+        assert_eq!(config, Value::Dict(expected));
+    }
+
+    #[test]
+    fn test_multi_line_strings() {
+        let config_content = r#"
+tls.cert = """
+-----BEGIN CERTIFICATE-----
+MIIBszCCAVmgAwIBAgIU
+-----END CERTIFICATE-----
+"""
+tls.note = """Tab\there, "quoted" and ""doubled"" """
+db.query = '''
+    SELECT *
+      FROM users
+     WHERE name = 'a\b'
+    '''
+db.pool = 5
+"#;
+
+        let config: Value = from_str(config_content).unwrap();
+        let Value::Dict(root) = &config else {
+            panic!("expected dict, found {}", config);
+        };
+        let (Value::Dict(tls), Value::Dict(db)) = (&root["tls"], &root["db"]) else {
+            panic!("expected nested dicts, found {}", config);
+        };
+
+        assert_eq!(
+            tls["cert"],
+            Value::String(
+                "-----BEGIN CERTIFICATE-----\nMIIBszCCAVmgAwIBAgIU\n-----END CERTIFICATE-----\n"
+                    .into()
+            )
+        );
+        assert_eq!(
+            tls["note"],
+            Value::String("Tab\there, \"quoted\" and \"\"doubled\"\" ".into())
+        );
+        assert_eq!(
+            db["query"],
+            Value::String("    SELECT *\n      FROM users\n     WHERE name = 'a\\b'\n    ".into())
+        );
+        assert_eq!(db["pool"], Value::Number(Number::UInt(5)));
+
+        let options = ParseOptions::new().with_strip_indent(true);
+        let config: Value = from_str_with_options(config_content, options).unwrap();
+        let Value::Dict(root) = &config else {
+            panic!("expected dict, found {}", config);
+        };
+        let Value::Dict(db) = &root["db"] else {
+            panic!("expected nested dict, found {}", config);
+        };
+        assert_eq!(
+            db["query"],
+            Value::String("SELECT *\n  FROM users\n WHERE name = 'a\\b'\n".into())
+        );
+    }
+
+    #[test]
+    fn test_null_values() {
+        let config_content = "server.proxy = null\nserver.name =";
+        let schema_content = "server.proxy: string?\nserver.name: string";
+
+        let config = from_str_with_schema(config_content, schema_content).unwrap();
+        assert_eq!(config.to_string(), "{server: {proxy: null, name: }}");
+
+        let options = ParseOptions::new().with_empty_value(EmptyValue::Null);
+        let config: Value = from_str_with_options(config_content, options.clone()).unwrap();
+        assert_eq!(config.to_string(), "{server: {proxy: null, name: null}}");
+
+        let config: Value = from_str_with_options("server.name = ''", options).unwrap();
+        assert_eq!(config.to_string(), "{server: {name: }}");
+    }
+
+    #[test]
+    fn test_optional_array_schema() {
+        let schema_content = "ports: [integer]?\nhosts: [string?]";
+
+        let config =
+            from_str_with_schema("ports = [80, 443]\nhosts = [a, null]", schema_content).unwrap();
+        assert_eq!(config.to_string(), "{ports: [80, 443], hosts: [a, null]}");
+
+        let config = from_str_with_schema("ports = null\nhosts = []", schema_content).unwrap();
+        assert_eq!(config.to_string(), "{ports: null, hosts: []}");
+    }
+
+    #[test]
+    fn test_quoted_keys() {
+        let config_content = r#"
+            routes."/api/v1".timeout = 30
+            routes."/api/v2".timeout = 60
+            labels.'app.kubernetes.io/name' = web
+            labels = { "team name" = platform }
+            "user agent" = curl
+        "#;
+
+        let config: Value = from_str(config_content).unwrap();
+        let Value::Dict(root) = &config else {
+            panic!("expected dict, found {}", config);
+        };
+        let Value::Dict(routes) = &root["routes"] else {
+            panic!("expected nested dict, found {}", config);
+        };
+        assert_eq!(
+            routes.keys().collect::<Vec<_>>(),
+            vec!["/api/v1", "/api/v2"]
+        );
+        let Value::Dict(labels) = &root["labels"] else {
+            panic!("expected nested dict, found {}", config);
+        };
+        assert_eq!(
+            labels["app.kubernetes.io/name"],
+            Value::String("web".into())
+        );
+        assert_eq!(labels["team name"], Value::String("platform".into()));
+        assert_eq!(root["user agent"], Value::String("curl".into()));
+
+        let config: Value = from_str("a.'b.c' = 1\na.b.c = 2").unwrap();
+        assert_eq!(config.to_string(), "{a: {b.c: 1, b: {c: 2}}}");
+
+        let config = from_str_with_schema(
+            "routes.\"/api/v1\".timeout = 30",
+            "routes.\"/api/v1\".timeout: integer",
+        )
+        .unwrap();
+        assert_eq!(config.to_string(), "{routes: {/api/v1: {timeout: 30}}}");
+    }
+
+    #[test]
+    fn test_section_headers() {
+        let config_content = r#"
+            name = app
+
+            [server]
+            host = localhost
+            port = 8080
+
+            [server.tls]
+            cert = '/etc/tls/cert.pem'
+
+            [database.replica]
+            hosts = [a, b]
+            pool = { size = 5 }
+        "#;
+
+        let schema_content = r#"
+            name: string
+
+            [server]
+            host: string
+            port: unsigned_integer
+            tls.cert: string
+
+            [database.replica]
+            hosts: [string]
+            pool.size: integer
+        "#;
+
+        let config = from_str_with_schema(config_content, schema_content).unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{name: app, server: {host: localhost, port: 8080, tls: {cert: /etc/tls/cert.pem}}, \
+             database: {replica: {hosts: [a, b], pool: {size: 5}}}}"
+        );
+
+        let config: Value = from_str("[routes.\"/api/v1\"]\ntimeout = 30s").unwrap();
+        assert_eq!(config.to_string(), "{routes: {/api/v1: {timeout: 30s}}}");
+    }
+
+    #[test]
+    fn test_include() {
+        let config: Value = from_path("tests/fixtures/include/app.conf").unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{logging: {level: info, format: json}, name: app, \
+             server: {host: localhost, port: 8080, tls: {cert: /etc/tls/cert.pem}}}"
+        );
+
+        let config = from_path_with_schema(
+            "tests/fixtures/include/app.conf",
+            "tests/fixtures/include/app.schema",
+        )
+        .unwrap();
+        assert_eq!(config.to_string().matches("logging").count(), 1);
+
+        let config: Value =
+            from_str("include 'tests/fixtures/include/logging.conf'\nname = app").unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{logging: {level: info, format: json}, name: app}"
+        );
+    }
+
+    #[test]
+    fn test_escape_sequences() {
+        let config_content = r#"
+            greeting = "caf\u00E9 \u{1F600}"
+            quote = "say \"hi\""
+            raw = 'C:\temp\q'
+        "#;
+
+        let config: Value = from_str(config_content).unwrap();
+        let Value::Dict(root) = &config else {
+            panic!("expected dict, found {}", config);
+        };
+        assert_eq!(root["greeting"], Value::String("café 😀".into()));
+        assert_eq!(root["quote"], Value::String("say \"hi\"".into()));
+        assert_eq!(root["raw"], Value::String("C:\\temp\\q".into()));
+
+        let config: Value = from_str(r#"path = "C:\qux""#).unwrap();
+        assert_eq!(config.to_string(), "{path: C:\\qux}");
+    }
+
+    #[test]
+    fn test_references() {
+        let config_content = r#"
+            app.home = '/srv/app'
+            log.dir = '${app.home}/logs'
+            log.literal = 'costs $${price}'
+            log.pattern = $${year}-${primary.port}
+
+            primary.port = 5432
+            primary.hosts = [a, b]
+            replica.port = ${primary.port}
+            replica.hosts = ${primary.hosts}
+            replica.label = replica-${primary.port}
+
+            [backup]
+            port = ${replica.port}
+            tls = ${primary}
+        "#;
+
+        let schema_content = r#"
+            app.home: string
+            log.dir: string
+            log.literal: string
+            log.pattern: string
+            primary.port: unsigned_integer
+            primary.hosts: [string]
+            replica.port: unsigned_integer
+            replica.hosts: [string]
+            replica.label: string
+            backup.port: integer
+            backup.tls.port: integer
+            backup.tls.hosts: [string]
+        "#;
+
+        let config = from_str_with_schema(config_content, schema_content).unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{app: {home: /srv/app}, log: {dir: /srv/app/logs, literal: costs ${price}, pattern: ${year}-5432}, \
+             primary: {port: 5432, hosts: [a, b]}, \
+             replica: {port: 5432, hosts: [a, b], label: replica-5432}, \
+             backup: {port: 5432, tls: {port: 5432, hosts: [a, b]}}}"
+        );
+
+        let config: Value = from_path("tests/fixtures/include/references.conf").unwrap();
+        let Value::Dict(root) = &config else {
+            panic!("expected dict, found {}", config);
+        };
+        assert_eq!(
+            root["health"].to_string(),
+            "{url: http://localhost:8080/health}"
+        );
+        assert_eq!(root["replica"].to_string(), "{port: 8080}");
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let env: HashMap<String, String> = [
+            ("MYAPP__SERVER__PORT", "9000"),
+            ("MYAPP__SERVER__TLS__ENABLED", "true"),
+            ("OTHER__SERVER__PORT", "1"),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+        let config_content = r#"
+            server.host = localhost
+            server.port = 8080
+            replica.port = ${server.port}
+        "#;
+        let schema_content = r#"
+            server.host: string
+            server.port: unsigned_integer
+            server.tls.enabled: bool
+            replica.port: unsigned_integer
+        "#;
+
+        let options = ParseOptions::new().with_env_prefix("MYAPP");
+        let config =
+            from_str_with_schema_and_env(config_content, schema_content, options, &env).unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{server: {host: localhost, port: 9000, tls: {enabled: true}}, replica: {port: 9000}}"
+        );
+
+        let config: Value =
+            from_str_with_env(config_content, ParseOptions::default(), &env).unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{server: {host: localhost, port: 8080}, replica: {port: 8080}}"
+        );
+    }
+
+    #[test]
+    fn test_profiles() {
+        let config_content = r#"
+            server.host = localhost
+            server.port = 8080
+            url = 'http://${server.host}:${server.port}'
+
+            [profile.production]
+            server.host = 'example.com'
+            server.port = 443
+
+            [profile.staging]
+            server.host = staging
+            debug = true
+        "#;
+        let schema_content = r#"
+            server.host: string
+            server.port: unsigned_integer
+            url: string
+        "#;
+
+        let options = ParseOptions::new().with_profile("production");
+        let config = from_str_with_schema_and_env(
+            config_content,
+            schema_content,
+            options,
+            &ProcessEnvironment,
+        )
+        .unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{server: {host: example.com, port: 443}, url: http://example.com:443}"
+        );
+
+        let config = from_str_with_schema_and_env(
+            config_content,
+            schema_content,
+            ParseOptions::new().with_profile("development"),
+            &ProcessEnvironment,
+        )
+        .unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{server: {host: localhost, port: 8080}, url: http://localhost:8080}"
+        );
+
+        // Without a profile, `profile` is an ordinary key.
+        let config: Value = from_str("profile.name = x\nprofile.avatar.url = y").unwrap();
+        assert_eq!(config.to_string(), "{profile: {name: x, avatar: {url: y}}}");
+
+        let config_content = r#"
+            servers.0.host = a
+            servers.1.host = b
+
+            [profile.production]
+            servers.1.host = c
+        "#;
+        let options = ParseOptions::new().with_profile("production");
+        let config: Value = from_str_with_options(config_content, options).unwrap();
+        assert_eq!(config.to_string(), "{servers: [{host: a}, {host: c}]}");
+
+        let config = ConfigBuilder::new()
+            .with_file("tests/fixtures/layers/base.conf")
+            .with_file("tests/fixtures/layers/profiles.conf")
+            .with_profile("production")
+            .build_value()
+            .unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{name: app, server: {host: localhost, port: 443, hosts: [a, b]}}"
+        );
+    }
+
+    #[test]
+    fn test_overrides() {
+        let mut config: Value = from_str(
+            r#"
+            server.host = localhost
+            server.port = 8080
+            server.hosts = [a, b]
+            features.beta = false
+        "#,
+        )
+        .unwrap();
+
+        let args = [
+            "server.port=9000",
+            "server.hosts = [c]",
+            "features.beta=true",
+            "server.name='my app'",
+            "server.port=9001",
+        ];
+        apply_overrides(&mut config, args).unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{server: {host: localhost, port: 9001, hosts: [c], name: my app}, \
+             features: {beta: true}}"
+        );
+
+        let env: HashMap<String, String> =
+            HashMap::from([("PORT".to_string(), "9100".to_string())]);
+        apply_overrides_with_env(&mut config, ["server.port=${env:PORT}"], &env).unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{server: {host: localhost, port: 9100, hosts: [c], name: my app}, \
+             features: {beta: true}}"
+        );
+
+        let mut config: Value =
+            from_str("servers.0.host = a\nservers.1.host = b\nhosts = [x, y]").unwrap();
+        let args = [
+            "servers.1.host=c",
+            "servers.2.host=d",
+            "hosts.1=z",
+            "b.c.d={x=1, y={z=2}}",
+        ];
+        apply_overrides(&mut config, args).unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{servers: [{host: a}, {host: c}, {host: d}], hosts: [x, z], \
+             b: {c: {d: {x: 1, y: {z: 2}}}}}"
+        );
+    }
+
+    #[test]
+    fn test_environment_variables() {
+        let env: HashMap<String, String> = [("DB_PASSWORD", "s3cret"), ("HOST", "db1")]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        let config_content = r#"
+            db.password = ${env:DB_PASSWORD}
+            db.port = ${env:PORT:-5432}
+            db.url = 'postgres://${env:HOST}:${db.port}'
+            db.tls = ${env:TLS:-true}
+        "#;
+
+        let config: Value =
+            from_str_with_env(config_content, ParseOptions::default(), &env).unwrap();
+        assert_eq!(
+            config,
+            from_str::<Value>(
+                "db.password = s3cret\ndb.port = 5432\n\
+                 db.url = 'postgres://db1:5432'\ndb.tls = true"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_config_builder() {
+        let env: HashMap<String, String> = [("MYAPP__SERVER__PORT", "9000"), ("CACHE_TTL", "120")]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        let builder = ConfigBuilder::new()
+            .with_defaults("debug = false\ncache.size = 16")
+            .with_file("tests/fixtures/layers/base.conf")
+            .with_optional_file("tests/fixtures/layers/production.conf")
+            .with_optional_file("tests/fixtures/layers/missing.conf")
+            .with_dir("tests/fixtures/layers/conf.d")
+            .with_dir("tests/fixtures/layers/missing.d")
+            .with_env_prefix("MYAPP")
+            .with_env(&env)
+            .with_overrides(["cache.ttl=${env:CACHE_TTL}"])
+            .with_schema_file("tests/fixtures/layers/app.schema");
+
+        let (config, origins) = builder.build_value_with_origins().unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{name: app, debug: false, \
+             server: {host: example.com, port: 9000, hosts: [c], tls: true}, \
+             cache: {size: 64, ttl: 120}}"
+        );
+        assert_eq!(builder.build_value().unwrap(), config);
+
+        let file = |path: &str, line| Origin::File {
+            path: format!("tests/fixtures/layers/{}", path).into(),
+            line,
+        };
+        let cases = [
+            ("name", file("base.conf", 1)),
+            ("debug", Origin::Defaults { line: 1 }),
+            ("server.host", file("production.conf", 4)),
+            ("server.hosts.0", file("production.conf", 5)),
+            ("server.tls", file("tls.conf", 1)),
+            (
+                "server.port",
+                Origin::Env {
+                    name: "MYAPP__SERVER__PORT".into(),
+                },
+            ),
+            ("cache.size", file("conf.d/10-cache.conf", 1)),
+            (
+                "cache.ttl",
+                Origin::Override {
+                    arg: "cache.ttl=${env:CACHE_TTL}".into(),
+                },
+            ),
+        ];
+        for (key, expected) in cases {
+            assert_eq!(origins.origin_of(key), Some(&expected), "{}", key);
+        }
+        assert_eq!(origins.origin_of("server"), None);
+        assert_eq!(
+            origins.origin_of("server.port").unwrap().to_string(),
+            "environment variable MYAPP__SERVER__PORT"
+        );
+        assert_eq!(
+            origins.origin_of("cache.size").unwrap().to_string(),
+            "tests/fixtures/layers/conf.d/10-cache.conf, line 1"
+        );
+    }
+
+    #[test]
+    fn test_config_builder_list_elements() {
+        let env: HashMap<String, String> =
+            HashMap::from([("MYAPP__SERVERS__2__HOST".to_string(), "d".to_string())]);
+
+        let config = ConfigBuilder::new()
+            .with_defaults("servers.0.host = a\nservers.1.host = b")
+            .with_file("tests/fixtures/layers/servers.conf")
+            .with_env_prefix("MYAPP")
+            .with_env(&env)
+            .with_overrides(["servers.1.host=c", "servers.3={host=e}"])
+            .build_value()
+            .unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{servers: [{host: a, port: 8080}, {host: c, port: 8081}, {host: d}, {host: e}]}"
+        );
+
+        let config = ConfigBuilder::new()
+            .with_file("tests/fixtures/layers/servers.conf")
+            .build_value()
+            .unwrap();
+        assert_eq!(
+            config.to_string(),
+            "{servers: [{port: 8080}, {port: 8081}]}"
+        );
+    }
+
+    #[test]
+    fn test_config_builder_into_struct() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Server {
+            host: String,
+            port: u16,
+        }
+
+        let server: Server = ConfigBuilder::new()
+            .with_defaults("host = localhost\nport = 8080")
+            .with_overrides(["port=9000"])
+            .with_schema("host: string\nport: unsigned_integer")
+            .build()
+            .unwrap();
+        assert_eq!(
+            server,
+            Server {
+                host: "localhost".into(),
+                port: 9000
+            }
+        );
+    }
 }
 
 mod invalid {
@@ -209,4 +1024,788 @@ mod invalid {
         let result = from_reader_with_schema(config_reader, schema_reader);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_invalid_array_values() {
+        let result: Result<Value, Error> = from_str("hosts = [a, b");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Token validation error: value validation error: \
+             Unterminated array at line 1, column 9"
+        );
+
+        let result: Result<Value, Error> = from_str("hosts = [a,, b]");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Token validation error: value validation error: \
+             Empty array element at line 1, column 12"
+        );
+
+        let result: Result<Value, Error> = from_str("hosts = a, b");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Token validation error: value validation error: \
+             Unexpected delimiter at line 1, column 10"
+        );
+
+        let result = from_str_with_schema("hosts = [a]", "hosts: [string, integer]");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             array type must name exactly one element type at line 1, column 8"
+        );
+
+        let result = from_str_with_schema("hosts = [a]", "hosts: []");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             array type must name exactly one element type at line 1, column 8"
+        );
+
+        let result: Result<Value, Error> = from_str("hosts = [a]?");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             optional marker not allowed in this context at line 1, column 9"
+        );
+
+        let result = from_str_with_schema("hosts = a", "hosts: [string]?");
+        assert!(matches!(result, Err(Error::TypeMismatch { .. })));
+
+        let result = from_str_with_schema("hosts = [a]", "hosts: [string] ?");
+        assert!(matches!(result, Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn test_invalid_indexed_keys() {
+        let result: Result<Value, Error> = from_str("servers.0.host = a\nservers.2.host = b");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             array index out of sequence: 'servers.2' at line 2, column 1"
+        );
+
+        let result: Result<Value, Error> = from_str("servers.0.host = a\nservers.name = b");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             key path conflict: 'servers' at line 2, column 1"
+        );
+
+        let result = from_str_with_schema("servers.0.host = a", "servers.0.host: string");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             invalid schema key: 'servers.0.host' at line 1, column 1"
+        );
+
+        let result = from_str_with_schema("port = 1", "port: number");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             unknown schema type: 'number' at line 1, column 7"
+        );
+    }
+
+    #[test]
+    fn test_invalid_datetime_values() {
+        let result: Result<Value, Error> = from_str("release.date = 2023-02-29");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Token validation error: value validation error: \
+             Invalid date or time at line 1, column 16"
+        );
+
+        let result: Result<Value, Error> = from_str("release.at = 2024-01-15T24:00:00.5Z");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Token validation error: value validation error: \
+             Invalid date or time at line 1, column 14"
+        );
+
+        let result = from_str_with_schema("release = 10:30:00", "release: date");
+        assert!(matches!(
+            result,
+            Err(Error::TypeMismatch { key, expected, found })
+                if key == "release" && expected == "date" && found == "time"
+        ));
+
+        let result = from_str_with_schema("release = '2024-01-15'", "release: date");
+        assert!(matches!(
+            result,
+            Err(Error::TypeMismatch { found, .. }) if found == "string"
+        ));
+    }
+
+    #[test]
+    fn test_invalid_duration_and_size_values() {
+        let result = from_str_with_schema("timeout = 30", "timeout: duration");
+        assert!(matches!(
+            result,
+            Err(Error::TypeMismatch { key, expected, found })
+                if key == "timeout" && expected == "duration" && found == "number"
+        ));
+
+        let result = from_str_with_schema("timeout = 30x", "timeout: duration");
+        assert!(matches!(
+            result,
+            Err(Error::TypeMismatch { found, .. }) if found == "string"
+        ));
+
+        let result = from_str_with_schema("buffer = -1", "buffer: size");
+        assert!(matches!(
+            result,
+            Err(Error::TypeMismatch { expected, found, .. })
+                if expected == "size" && found == "negative number"
+        ));
+
+        let result: Result<Value, Error> = from_str("timeout = 1.5x");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Token validation error: value validation error: \
+             Invalid value format at line 1, column 11"
+        );
+    }
+
+    #[test]
+    fn test_invalid_durations_and_sizes() {
+        let cases = [
+            ("retry = 1h1h", "Duration units out of order"),
+            ("retry = 1ms5s", "Duration units out of order"),
+            ("retry = 99999999999999999999d", "Duration out of range"),
+            ("cache = 18446744073709551616PiB", "Size out of range"),
+        ];
+
+        for (input, message) in cases {
+            let result: Result<Value, Error> = from_str(input);
+            let error = result.unwrap_err().to_string();
+            assert!(
+                error.starts_with(&format!(
+                    "parser error: Token validation error: value validation error: {} at line 1",
+                    message
+                )),
+                "{}: {}",
+                input,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_number_literals() {
+        let cases = [
+            ("mask = 0xFG", "Invalid hexadecimal literal"),
+            ("mode = 0o758", "Invalid octal literal"),
+            ("flags = 0b102", "Invalid binary literal"),
+            ("count = 1__000", "Misplaced digit separator"),
+            ("count = 1_000_", "Misplaced digit separator"),
+            ("epsilon = 1e-", "Invalid exponent"),
+            ("scale = 2.5e", "Invalid exponent"),
+            ("mask = 0x1_0000_0000_0000_0000", "Number out of range"),
+            ("count = 99999999999999999999", "Number out of range"),
+            ("scale = 1.5e400", "Number out of range"),
+            ("count = 1_000.5.5", "Invalid decimal literal"),
+        ];
+
+        for (input, message) in cases {
+            let result: Result<Value, Error> = from_str(input);
+            let error = result.unwrap_err().to_string();
+            assert!(
+                error.starts_with(&format!(
+                    "parser error: Token validation error: value validation error: {} at line 1",
+                    message
+                )),
+                "{}: {}",
+                input,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_null_values() {
+        let result = from_str_with_schema("server.proxy = null", "server.proxy: string");
+        assert!(matches!(
+            result,
+            Err(Error::TypeMismatch { key, found, .. }) if key == "server.proxy" && found == "null"
+        ));
+
+        let options = ParseOptions::new().with_empty_value(EmptyValue::Error);
+        let result: Result<Value, Error> =
+            from_str_with_options("server.host = a\nserver.name =", options.clone());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             empty value: 'server.name' at line 2, column 13"
+        );
+
+        let result: Result<Value, Error> = from_str_with_options("servers = [{ name = }]", options);
+        assert!(matches!(result, Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn test_invalid_quoted_keys() {
+        let result: Result<Value, Error> = from_str("routes.\"/api\" = 1\nroutes.'/api' = 2");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             duplicate key found: 'routes.\"/api\"' at line 2, column 1"
+        );
+
+        let result = from_str_with_schema("key = value", "key: 'string'");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             quoted value not allowed in this context at line 1, column 6"
+        );
+
+        for input in ["servers.'0' = a", "servers.\"\" = a"] {
+            let result: Result<Value, Error> = from_str(input);
+            let error = result.unwrap_err().to_string();
+            assert!(
+                error.starts_with(
+                    "parser error: Token validation error: key validation error: \
+                     Invalid quoted key"
+                ),
+                "{}: {}",
+                input,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_section_headers() {
+        let result: Result<Value, Error> = from_str("[server]\nhost = a\n\n[server]\nhost = b");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             duplicate key found: 'server.host' at line 5, column 1"
+        );
+
+        let result: Result<Value, Error> = from_str("server.port = 80\n[server.port]\nnumber = 1");
+        assert!(matches!(result, Err(Error::Parse(_))));
+
+        let result: Result<Value, Error> = from_str("[server\nhost = a");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Token validation error: Line structure validation error: \
+             Invalid section header at line 1, column 1"
+        );
+    }
+
+    #[test]
+    fn test_invalid_include() {
+        let result: Result<Value, Error> = from_path("tests/fixtures/include/cycle_a.conf");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "include error: cycle detected: tests/fixtures/include/cycle_a.conf -> \
+             tests/fixtures/include/cycle_b.conf -> tests/fixtures/include/cycle_a.conf"
+        );
+
+        let result: Result<Value, Error> = from_path("tests/fixtures/include/duplicate.conf");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             duplicate key found: 'logging.level' at line 2, column 1"
+        );
+
+        let result: Result<Value, Error> = from_path("tests/fixtures/include/dangling.conf");
+        assert!(matches!(
+            result,
+            Err(Error::Included { path, source })
+                if path == "tests/fixtures/include/missing.conf"
+                    && matches!(*source, Error::Io(_))
+        ));
+
+        let result: Result<Value, Error> = from_path("tests/fixtures/include/includes_broken.conf");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "in included file 'tests/fixtures/include/broken.conf': \
+             token error: Unterminated string literal at line 1, column 9"
+        );
+
+        let result = from_str_recovering::<Value>(
+            "include 'tests/fixtures/include/dangling.conf'\nname = a\nport",
+        );
+        assert_eq!(result.unwrap_err().len(), 2);
+
+        let result: Result<Value, Error> =
+            from_str("name = a\ninclude 'tests/fixtures/include/invalid_value.conf'");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "in included file 'tests/fixtures/include/invalid_value.conf': \
+             parser error: Token validation error: value validation error: \
+             Invalid value format at line 1, column 5"
+        );
+
+        let result: Result<Value, Error> =
+            from_str("include 'tests/fixtures/include/unresolved.conf'\nport = 80");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "in included file 'tests/fixtures/include/unresolved.conf': \
+             parser error: Key validation error: \
+             unresolved reference: 'nope' at line 2, column 7"
+        );
+
+        let result = from_str_recovering::<Value>(
+            "include 'tests/fixtures/include/unresolved.conf'\nport = 80",
+        );
+        let errors = result.unwrap_err();
+        assert!(matches!(
+            errors.iter().next(),
+            Some(Error::Included { path, .. })
+                if path == "tests/fixtures/include/unresolved.conf"
+        ));
+    }
+
+    #[test]
+    fn test_invalid_escape_sequences() {
+        let cases = [
+            (r#"path = "C:\qux""#, 11),
+            (r#"emoji = "\u{1F60}\u{110000}""#, 18),
+            (r#"letter = "\u00G9""#, 11),
+        ];
+
+        for (input, column) in cases {
+            let options = ParseOptions::new().with_strict_escapes(true);
+            let result: Result<Value, Error> = from_str_with_options(input, options);
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                format!(
+                    "token error: Invalid escape sequence at line 1, column {}",
+                    column
+                ),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_references() {
+        let result: Result<Value, Error> = from_str("log.dir = '${app.home}/logs'");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             unresolved reference: 'app.home' at line 1, column 11"
+        );
+
+        let result: Result<Value, Error> = from_str("a = ${b}\nb = ${c}\nc = ${a}");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             circular reference: a -> b -> c -> a at line 3, column 5"
+        );
+
+        let result: Result<Value, Error> = from_str("hosts = [a, b]\nlabel = 'hosts: ${hosts}'");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             invalid reference: 'hosts' at line 2, column 9"
+        );
+
+        let result: Result<Value, Error> = from_str("home = $HOME");
+        assert!(matches!(result, Err(Error::Lexer(_))));
+
+        let result: Result<Value, Error> = from_str("${env}.port = 80");
+        assert!(matches!(result, Err(Error::Parse(_))));
+
+        let result = from_str_recovering::<Value>("a = ${missing}\nb = ${a}\nc = ${c}");
+        assert_eq!(result.unwrap_err().len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_env_overrides() {
+        let env: HashMap<String, String> =
+            HashMap::from([("MYAPP__SERVER__PORT".to_string(), "high".to_string())]);
+        let options = ParseOptions::new().with_env_prefix("MYAPP");
+        let result = from_str_with_schema_and_env(
+            "server.port = 8080",
+            "server.port: unsigned_integer",
+            options,
+            &env,
+        );
+        assert!(matches!(
+            result,
+            Err(Error::TypeMismatch { key, .. }) if key == "server.port"
+        ));
+
+        let env: HashMap<String, String> =
+            HashMap::from([("MYAPP__SERVER__TLS".to_string(), "false".to_string())]);
+        let options = ParseOptions::new().with_env_prefix("MYAPP");
+        let result: Result<Value, Error> = from_str_with_env(
+            "server.port = 8080\nserver.tls.cert = 'cert.pem'",
+            options,
+            &env,
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             environment variable 'MYAPP__SERVER__TLS' conflicts with key 'server.tls'"
+        );
+
+        let cases = [
+            (
+                vec![("MYAPP__A__B", "1"), ("MYAPP__A", "2")],
+                "environment variable 'MYAPP__A__B' conflicts with key 'a'",
+            ),
+            (
+                vec![("MYAPP__Y.Z", "1")],
+                "environment variable 'MYAPP__Y.Z' does not name a valid key",
+            ),
+            (
+                vec![("MYAPP__SERVERS__5__HOST", "b")],
+                "environment variable 'MYAPP__SERVERS__5__HOST' \
+                 sets array index out of sequence: 'servers.5'",
+            ),
+        ];
+        for (vars, message) in cases {
+            let env: HashMap<String, String> = vars
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect();
+            let options = ParseOptions::new().with_env_prefix("MYAPP");
+            let result: Result<Value, Error> =
+                from_str_with_env("servers.0.host = a", options, &env);
+            let error = result.unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("parser error: Key validation error: {}", message)
+            );
+            assert_eq!(error.span(), None);
+        }
+
+        let env: HashMap<String, String> =
+            HashMap::from([("MYAPP__SERVERS__5__HOST".to_string(), "b".to_string())]);
+        let result = ConfigBuilder::new()
+            .with_defaults("servers.0.host = a")
+            .with_env_prefix("MYAPP")
+            .with_env(&env)
+            .build_value();
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: environment variable \
+             'MYAPP__SERVERS__5__HOST' sets array index out of sequence: 'servers.5'"
+        );
+    }
+
+    #[test]
+    fn test_invalid_config_builder() {
+        let result = ConfigBuilder::new()
+            .with_defaults("servers.0.host = a")
+            .with_overrides(["servers.2.host=c"])
+            .build_value();
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "in override 'servers.2.host=c': parser error: Key validation error: \
+             array index out of sequence: 'servers.2' at line 1, column 1"
+        );
+
+        let result = ConfigBuilder::new()
+            .with_defaults("hosts.0 = a\nhosts.2 = c")
+            .build_value();
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             array index out of sequence: 'hosts.2' at line 2, column 1"
+        );
+
+        let result = ConfigBuilder::new()
+            .with_file("tests/fixtures/layers/base.conf")
+            .with_file("tests/fixtures/layers/conflict.conf")
+            .build_value();
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "in file 'tests/fixtures/layers/conflict.conf': parser error: \
+             Key validation error: key path conflict: 'server.port' at line 1, column 1"
+        );
+
+        let result = ConfigBuilder::new()
+            .with_file("tests/fixtures/layers/missing.conf")
+            .build_value();
+        assert!(matches!(
+            result,
+            Err(Error::InFile { ref path, ref source })
+                if path == "tests/fixtures/layers/missing.conf"
+                    && matches!(**source, Error::Io(_))
+        ));
+
+        let result = ConfigBuilder::new()
+            .with_file("tests/fixtures/layers/base.conf")
+            .with_schema_file("tests/fixtures/layers/app.schema")
+            .build_value();
+        assert!(matches!(result, Err(Error::MissingKey { .. })));
+
+        let result = ConfigBuilder::new()
+            .with_file("tests/fixtures/layers/base.conf")
+            .with_schema_file("tests/fixtures/layers/missing.schema")
+            .build_value();
+        assert!(matches!(
+            result,
+            Err(Error::InFile { ref path, ref source })
+                if path == "tests/fixtures/layers/missing.schema"
+                    && matches!(**source, Error::Io(_))
+        ));
+
+        let result = ConfigBuilder::new()
+            .with_file("tests/fixtures/layers/base.conf")
+            .with_schema_file("tests/fixtures/layers/broken.schema")
+            .build_value();
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "in file 'tests/fixtures/layers/broken.schema': parser error: \
+             Key validation error: unknown schema type: 'number' at line 2, column 14"
+        );
+    }
+
+    #[test]
+    fn test_invalid_profiles() {
+        let options = ParseOptions::new().with_profile("production");
+        let result: Result<Value, Error> =
+            from_str_with_options("profile = production\nport = 80", options);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             invalid profile key: 'profile' at line 1, column 1"
+        );
+
+        let options = ParseOptions::new().with_profile("production");
+        let result: Result<Value, Error> = from_str_with_options(
+            "server.tls = false\n[profile.production]\nserver.tls.cert = 'cert.pem'",
+            options,
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             key path conflict: 'server.tls' at line 1, column 1"
+        );
+
+        let options = ParseOptions::new().with_profile("production");
+        let result: Result<Value, Error> = from_str_with_options(
+            "servers.0.host = a\n[profile.production]\nservers.2.host = c",
+            options,
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             array index out of sequence: 'servers.2' at line 3, column 1"
+        );
+    }
+
+    #[test]
+    fn test_invalid_overrides() {
+        let original: Value = from_str("server.port = 8080").unwrap();
+
+        let cases = [
+            (
+                "server.port.number=1",
+                "in override 'server.port.number=1': parser error: Key validation error: \
+                 key path conflict: 'server.port' at line 1, column 1",
+            ),
+            (
+                "server=1",
+                "in override 'server=1': parser error: Key validation error: \
+                 key path conflict: 'server' at line 1, column 1",
+            ),
+            (
+                "-port=1",
+                "in override '-port=1': parser error: Token validation error: \
+                 key validation error: Key cannot start with hyphen: '-port' at line 1, column 1",
+            ),
+            (
+                "server.port",
+                "in override 'server.port': parser error: Token validation error: \
+                 Line structure validation error: Missing separator in line at line 1, column 1",
+            ),
+            (
+                "hosts.1=a",
+                "in override 'hosts.1=a': parser error: Key validation error: \
+                 array index out of sequence: 'hosts.1' at line 1, column 1",
+            ),
+        ];
+
+        for (arg, expected) in cases {
+            let mut config = original.clone();
+            let result = apply_overrides(&mut config, ["server.host=a", arg]);
+            assert_eq!(result.unwrap_err().to_string(), expected, "{}", arg);
+            assert_eq!(config, original);
+        }
+
+        let args = [
+            "server.port=1\nserver.host=b",
+            "server.port=1\n",
+            "[server]",
+            "# server.port=1",
+            "",
+        ];
+        for arg in args {
+            let mut config = original.clone();
+            let result = apply_overrides(&mut config, [arg]);
+            assert!(
+                matches!(
+                    &result,
+                    Err(Error::Override { arg: a, source })
+                        if a == arg && matches!(**source, Error::InvalidOverride)
+                ),
+                "{:?}: {:?}",
+                arg,
+                result
+            );
+            assert_eq!(config, original);
+        }
+    }
+
+    #[test]
+    fn test_invalid_environment_variables() {
+        let env = HashMap::new();
+        let result: Result<Value, Error> = from_str_with_env(
+            "db.password = ${env:DB_PASSWORD}",
+            ParseOptions::default(),
+            &env,
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             undefined environment variable: 'DB_PASSWORD' at line 1, column 15"
+        );
+    }
+
+    #[test]
+    fn test_invalid_inline_tables() {
+        let result: Result<Value, Error> = from_str("db = { host = 'a' }\ndb.host = 'b'");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             duplicate key found: 'db.host' at line 2, column 1"
+        );
+
+        let result: Result<Value, Error> = from_str("db = { host = { name = a }, host = b }");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             key path conflict: 'db.host' at line 1, column 29"
+        );
+
+        let result: Result<Value, Error> = from_str("db = [{ a = 1, a = 2 }]");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Key validation error: \
+             duplicate key found: 'a' at line 1, column 16"
+        );
+
+        let result: Result<Value, Error> = from_str("db = { host = a");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "parser error: Token validation error: value validation error: \
+             Unterminated inline table at line 1, column 6"
+        );
+    }
+
+    #[test]
+    fn test_error_reports_location() {
+        let config_content = "server.host = localhost\nserver.host = other\n";
+
+        let result: Result<Value, Error> = from_str(config_content);
+        let err = result.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "parser error: Key validation error: duplicate key found: 'server.host' at line 2, column 1"
+        );
+
+        let config_content = "server.host = localhost\n    invalid_syntax\n";
+
+        let result: Result<Value, Error> = from_str(config_content);
+        let err = result.unwrap_err();
+        assert!(err.to_string().ends_with("at line 2, column 5"));
+    }
+
+    #[test]
+    fn test_render_diagnostic() {
+        let config_content = "server.host = localhost\nserver.port = 80 80\n";
+
+        let err = from_str::<Value>(config_content).unwrap_err();
+        let rendered = DiagnosticRenderer::new(config_content)
+            .with_origin("app.conf")
+            .render(&err);
+
+        let expected = [
+            "error: Invalid value format",
+            " --> app.conf:2:15",
+            "  |",
+            "2 | server.port = 80 80",
+            "  |               ^^^^^",
+            "  |",
+            "  = help: wrap the value in quotes if it is meant to be a string",
+            "",
+        ];
+        assert_eq!(rendered, expected.join("\n"));
+    }
+
+    #[test]
+    fn test_recovering_collects_all_errors() {
+        let config_content = r#"
+            server.host = @localhost
+            server.port = 80 80
+            server.name = 'web
+            database.enabled = true
+            database.enabled = false
+        "#;
+
+        let errors = from_str_recovering::<Value>(config_content).unwrap_err();
+        let lines: Vec<usize> = errors.iter().map(|e| e.span().unwrap().line).collect();
+        assert_eq!(lines, vec![2, 4, 3, 6]);
+    }
+
+    #[test]
+    fn test_recovering_collects_schema_errors() {
+        let config_content = r#"
+            server.host = localhost
+            server.port = 80 80
+            server.debug = true
+            database.enabled = yes
+        "#;
+
+        let schema_content = r#"
+            server.host: string
+            server.port: integer
+            database.enabled: bool
+            database.max_connections: unsigned_integer
+        "#;
+
+        let errors = from_str_with_schema_recovering(config_content, schema_content).unwrap_err();
+        let errors: Vec<&Error> = errors.iter().collect();
+
+        assert_eq!(errors.len(), 5);
+        assert!(matches!(errors[0], Error::Parse(_)));
+        assert!(matches!(errors[1], Error::UnknownKey { key } if key == "server.debug"));
+        assert!(matches!(errors[2], Error::MissingKey { key } if key == "server.port"));
+        assert!(matches!(
+            errors[3],
+            Error::MissingKey { key } if key == "database.max_connections"
+        ));
+        assert!(matches!(
+            errors[4],
+            Error::TypeMismatch { key, expected, found }
+                if key == "database.enabled" && expected == "bool" && found == "string"
+        ));
+    }
+
+    #[test]
+    fn test_recovering_matches_strict_on_valid_input() {
+        let config_content = r#"
+            server.host = localhost
+            server.port = 8080
+        "#;
+
+        let schema_content = r#"
+            server.host: string
+            server.port: integer
+        "#;
+
+        assert_eq!(
+            from_str_with_schema_recovering(config_content, schema_content).unwrap(),
+            from_str_with_schema(config_content, schema_content).unwrap()
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use kernel::{token::Token, tokenize::Tokenize};
+use kernel::{options::ParseOptions, token::Token, tokenize::Tokenize};
 use lexer::{ConfigSource, Lexer};
 
 #[test]
@@ -362,7 +362,7 @@ fn test_invalid_case() {
     assert!(result.is_err());
     assert_eq!(
         result.unwrap_err().to_string(),
-        "Unterminated string literal"
+        "Unterminated string literal at line 1, column 7"
     );
 
     let text = "key = \"value";
@@ -371,12 +371,181 @@ fn test_invalid_case() {
     assert!(result.is_err());
     assert_eq!(
         result.unwrap_err().to_string(),
-        "Unterminated string literal"
+        "Unterminated string literal at line 1, column 7"
     );
 
     let text = "@key = value";
     let source = ConfigSource::new(text.to_string());
     let result = Lexer::tokenize(source);
     assert!(result.is_err());
-    assert_eq!(result.unwrap_err().to_string(), "Invalid character: '@'");
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Invalid character: '@' at line 1, column 1"
+    );
+}
+
+#[test]
+fn test_token_spans() {
+    let text = "key = value\nkey.sub = 'quoted'";
+    let source = ConfigSource::new(text.to_string());
+    let result = Lexer::tokenize(source).unwrap();
+
+    let spans: Vec<_> = result
+        .iter()
+        .map(|t| (t.span.start, t.span.end, t.span.line, t.span.column))
+        .collect();
+
+    assert_eq!(
+        spans,
+        vec![
+            (0, 3, 1, 1),
+            (4, 5, 1, 5),
+            (6, 11, 1, 7),
+            (11, 12, 1, 12),
+            (12, 15, 2, 1),
+            (15, 16, 2, 4),
+            (16, 19, 2, 5),
+            (20, 21, 2, 9),
+            (22, 30, 2, 11),
+            (30, 30, 2, 19),
+        ]
+    );
+}
+
+#[test]
+fn test_unicode_case() {
+    let text = "app.title = 'こんにちは' # 日本語のコメント\nlog.message = \"起動しました\"";
+    let source = ConfigSource::new(text.to_string());
+    let result = Lexer::tokenize(source).unwrap();
+    assert_eq!(
+        result,
+        vec![
+            Token::Ident("app".to_string()),
+            Token::Dot,
+            Token::Ident("title".to_string()),
+            Token::Separator,
+            Token::QuotedIdent("こんにちは".to_string()),
+            Token::Newline,
+            Token::Ident("log".to_string()),
+            Token::Dot,
+            Token::Ident("message".to_string()),
+            Token::Separator,
+            Token::QuotedIdent("起動しました".to_string()),
+            Token::Eof,
+        ]
+    );
+    assert_eq!((result[5].span.line, result[5].span.column), (1, 31));
+}
+
+#[test]
+fn test_unicode_ident_case() {
+    let text = "app.title = こんにちは";
+    let source = ConfigSource::new(text.to_string());
+    let result = Lexer::tokenize(source);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Invalid character: 'こ' at line 1, column 13"
+    );
+
+    let options = ParseOptions::new().with_unicode_idents(true);
+    let source = ConfigSource::new(text.to_string()).with_options(options.clone());
+    let result = Lexer::tokenize(source).unwrap();
+    assert_eq!(result[4], Token::Ident("こんにちは".to_string()));
+
+    let text = "app.title = こんにちは、世界";
+    let source = ConfigSource::new(text.to_string()).with_options(options);
+    let result = Lexer::tokenize(source);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Invalid character: '、' at line 1, column 18"
+    );
+}
+
+#[test]
+fn test_array_case() {
+    let text = "hosts = [a, 'b.example.com',c]";
+    let source = ConfigSource::new(text.to_string());
+    let result = Lexer::tokenize(source).unwrap();
+    assert_eq!(
+        result,
+        vec![
+            Token::Ident("hosts".to_string()),
+            Token::Separator,
+            Token::LeftBracket,
+            Token::Ident("a".to_string()),
+            Token::Comma,
+            Token::QuotedIdent("b.example.com".to_string()),
+            Token::Comma,
+            Token::Ident("c".to_string()),
+            Token::RightBracket,
+            Token::Eof,
+        ]
+    );
+}
+
+#[test]
+fn test_multi_line_string_literal_case() {
+    let text = "cert = \"\"\"\n-----BEGIN-----\nMIIB\n-----END-----\n\"\"\"\nname = x";
+    let source = ConfigSource::new(text.to_string());
+    let result = Lexer::tokenize(source).unwrap();
+    assert_eq!(
+        result,
+        vec![
+            Token::Ident("cert".to_string()),
+            Token::Separator,
+            Token::QuotedIdent("-----BEGIN-----\nMIIB\n-----END-----\n".to_string()),
+            Token::Newline,
+            Token::Ident("name".to_string()),
+            Token::Separator,
+            Token::Ident("x".to_string()),
+            Token::Eof,
+        ]
+    );
+
+    let source = ConfigSource::new(text.to_string());
+    let (tokens, _) = Lexer::tokenize_all(source);
+    let spans: Vec<_> = tokens
+        .iter()
+        .map(|t| (t.span.line, t.span.column))
+        .collect();
+    assert_eq!(
+        spans,
+        vec![
+            (1, 1),
+            (1, 6),
+            (1, 8),
+            (5, 4),
+            (6, 1),
+            (6, 6),
+            (6, 8),
+            (6, 9)
+        ]
+    );
+}
+
+#[test]
+fn test_unterminated_multi_line_string_case() {
+    let text = "sql = '''\nSELECT 1\n";
+    let source = ConfigSource::new(text.to_string());
+    let result = Lexer::tokenize(source);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Unterminated string literal at line 1, column 7"
+    );
+}
+
+#[test]
+fn test_invalid_escape_sequence_case() {
+    let text = "key = \"ok\"\npath = \"C:\\temp\\qux\"\n";
+    let source = ConfigSource::new(text.to_string())
+        .with_options(ParseOptions::new().with_strict_escapes(true));
+    let result = Lexer::tokenize(source);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Invalid escape sequence at line 2, column 16"
+    );
+
+    let source = ConfigSource::new(text.to_string());
+    let result = Lexer::tokenize(source).unwrap();
+    assert_eq!(result[6], Token::QuotedIdent("C:\temp\\qux".to_string()));
 }
@@ -144,16 +144,72 @@ fn test_with_invalid_case() {
     assert!(result.is_err());
 
     let text = r#"
-        key: "value"
+        key: "value
     "#;
     let source = SchemaSource::new(text.to_string());
     let result = Lexer::tokenize(source);
     assert!(result.is_err());
+}
 
+#[test]
+fn test_quoted_case() {
     let text = r#"
-        key: 'value'
+        include 'shared/logging.schema'
+        routes."/api/v1".timeout: duration
     "#;
     let source = SchemaSource::new(text.to_string());
-    let result = Lexer::tokenize(source);
-    assert!(result.is_err());
+    let result = Lexer::tokenize(source).unwrap();
+    assert_eq!(
+        result,
+        vec![
+            Token::Newline,
+            Token::Ident("include".to_string()),
+            Token::QuotedIdent("shared/logging.schema".to_string()),
+            Token::Newline,
+            Token::Ident("routes".to_string()),
+            Token::Dot,
+            Token::QuotedIdent("/api/v1".to_string()),
+            Token::Dot,
+            Token::Ident("timeout".to_string()),
+            Token::Separator,
+            Token::Ident("duration".to_string()),
+            Token::Newline,
+            Token::Eof,
+        ]
+    );
+}
+
+#[test]
+fn test_array_type_case() {
+    let text = "hosts: [string]";
+    let source = SchemaSource::new(text.to_string());
+    let result = Lexer::tokenize(source).unwrap();
+    assert_eq!(
+        result,
+        vec![
+            Token::Ident("hosts".to_string()),
+            Token::Separator,
+            Token::LeftBracket,
+            Token::Ident("string".to_string()),
+            Token::RightBracket,
+            Token::Eof,
+        ]
+    );
+
+    let text = "db: { host: string }";
+    let source = SchemaSource::new(text.to_string());
+    let result = Lexer::tokenize(source).unwrap();
+    assert_eq!(
+        result,
+        vec![
+            Token::Ident("db".to_string()),
+            Token::Separator,
+            Token::LeftBrace,
+            Token::Ident("host".to_string()),
+            Token::Separator,
+            Token::Ident("string".to_string()),
+            Token::RightBrace,
+            Token::Eof,
+        ]
+    );
 }
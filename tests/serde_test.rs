@@ -107,6 +107,16 @@ fn test_optional_fields() {
     assert_eq!(config.name, "test");
     assert_eq!(config.port, Some(8080));
     assert_eq!(config.debug, None);
+
+    let config_str = r#"
+        name = "test"
+        port = null
+        debug = null
+    "#;
+
+    let config: ConfigWithOptional = from_str(config_str).unwrap();
+    assert_eq!(config.port, None);
+    assert_eq!(config.debug, None);
 }
 
 #[test]
@@ -331,3 +341,342 @@ fn test_deep_nesting() {
     assert_eq!(config.level1.value, "test");
     assert_eq!(config.level1.level2.level3.deep_value, 42);
 }
+
+#[test]
+fn test_error_reports_key_path() {
+    let config_str = r#"
+        server.host = "localhost"
+        server.port = -1
+        database.url = "postgres://localhost/mydb"
+        database.max_connections = 100
+    "#;
+
+    let result: Result<NestedConfig, Error> = from_str(config_str);
+    let Err(Error::Serde(err)) = result else {
+        panic!("expected a serde error");
+    };
+
+    assert_eq!(err.path(), "server.port");
+    assert_eq!(err.path_segments(), ["server", "port"]);
+    assert_eq!(err.message(), "expected u16, found -1");
+    assert_eq!(err.to_string(), "server.port: expected u16, found -1");
+}
+
+#[test]
+fn test_error_reports_parent_path_for_missing_field() {
+    let config_str = r#"
+        server.host = "localhost"
+        database.url = "postgres://localhost/mydb"
+        database.max_connections = 100
+    "#;
+
+    let result: Result<NestedConfig, Error> = from_str(config_str);
+    let Err(Error::Serde(err)) = result else {
+        panic!("expected a serde error");
+    };
+
+    assert_eq!(err.path(), "server");
+    assert_eq!(err.message(), "missing field `port`");
+}
+
+#[test]
+fn test_error_describes_found_value() {
+    #[derive(Deserialize, Debug)]
+    struct TypedConfig {
+        _flag: bool,
+    }
+
+    let result: Result<TypedConfig, Error> = from_str("_flag = 'yes'");
+    let Err(Error::Serde(err)) = result else {
+        panic!("expected a serde error");
+    };
+
+    assert_eq!(err.to_string(), "_flag: expected bool, found 'yes'");
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct ArrayConfig {
+    server: AllowedHostsConfig,
+    ports: Vec<u16>,
+    origin: (String, u16),
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct AllowedHostsConfig {
+    allowed_hosts: Vec<String>,
+}
+
+#[test]
+fn test_array_deserialize() {
+    let config_str = r#"
+        server.allowed_hosts = [a, 'b.example.com', c]
+        ports = [80, 443]
+        origin = [localhost, 8080]
+    "#;
+
+    let config: ArrayConfig = from_str(config_str).unwrap();
+    assert_eq!(
+        config,
+        ArrayConfig {
+            server: AllowedHostsConfig {
+                allowed_hosts: vec!["a".into(), "b.example.com".into(), "c".into()],
+            },
+            ports: vec![80, 443],
+            origin: ("localhost".into(), 8080),
+        }
+    );
+}
+
+#[test]
+fn test_array_error_reports_index() {
+    let config_str = "ports = [80, -1]";
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Ports {
+        ports: Vec<u16>,
+    }
+
+    let err = from_str::<Ports>(config_str).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "serde error: ports.1: expected u16, found -1"
+    );
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Pair {
+        pair: (u16, u16),
+    }
+
+    let err = from_str::<Pair>("pair = [1, 2, 3]").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "serde error: pair: expected 2 elements, found 3"
+    );
+}
+
+#[test]
+fn test_indexed_keys_deserialize() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Upstreams {
+        servers: Vec<ServerConfig>,
+    }
+
+    let config_str = r#"
+        servers.0.host = 'a.example.com'
+        servers.0.port = 80
+        servers.1.host = 'b.example.com'
+        servers.1.port = 8080
+    "#;
+
+    let config: Upstreams = from_str(config_str).unwrap();
+    assert_eq!(
+        config.servers,
+        vec![
+            ServerConfig {
+                host: "a.example.com".into(),
+                port: 80,
+            },
+            ServerConfig {
+                host: "b.example.com".into(),
+                port: 8080,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_inline_table_deserialize() {
+    let config_str = r#"
+        server = { host = localhost, port = 8080 }
+        database = { url = 'postgres://localhost/app' }
+        database.max_connections = 10
+    "#;
+
+    let config: NestedConfig = from_str(config_str).unwrap();
+    assert_eq!(
+        config,
+        NestedConfig {
+            server: ServerConfig {
+                host: "localhost".into(),
+                port: 8080,
+            },
+            database: DatabaseConfig {
+                url: "postgres://localhost/app".into(),
+                max_connections: 10,
+            },
+        }
+    );
+}
+
+#[test]
+fn test_quoted_keys_deserialize() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Route {
+        timeout: u64,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Config {
+        routes: std::collections::BTreeMap<String, Route>,
+        labels: std::collections::BTreeMap<String, String>,
+    }
+
+    let config_str = r#"
+        routes."/api/v1".timeout = 30
+        labels.'app.kubernetes.io/name' = web
+    "#;
+
+    let config: Config = from_str(config_str).unwrap();
+    assert_eq!(config.routes["/api/v1"], Route { timeout: 30 });
+    assert_eq!(config.labels["app.kubernetes.io/name"], "web");
+}
+
+#[test]
+fn test_datetime_deserialize() {
+    use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Release {
+        date: NaiveDate,
+        window: NaiveTime,
+        published: DateTime<FixedOffset>,
+        local: NaiveDateTime,
+        label: String,
+    }
+
+    let config_str = r#"
+        date = 2024-02-29
+        window = 23:30:00
+        published = 2024-03-01T09:15:30.250+09:00
+        local = 2024-03-01T09:15:30
+        label = 2024-01-15t10:30:00.500z
+    "#;
+
+    let release: Release = from_str(config_str).unwrap();
+    assert_eq!(release.date, NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+    assert_eq!(release.window, NaiveTime::from_hms_opt(23, 30, 0).unwrap());
+    assert_eq!(
+        release.published,
+        DateTime::parse_from_rfc3339("2024-03-01T09:15:30.25+09:00").unwrap()
+    );
+    assert_eq!(
+        release.local,
+        NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 15, 30)
+            .unwrap()
+    );
+    assert_eq!(release.label, "2024-01-15t10:30:00.500z");
+}
+
+#[test]
+fn test_datetime_deserialize_any() {
+    use std::collections::HashMap;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(untagged)]
+    enum Version {
+        Number(u64),
+        Text(String),
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Release {
+        version: Version,
+        labels: HashMap<String, String>,
+    }
+
+    let config_str = r#"
+        version = 2024-02-29
+        labels.date = 2024-02-29
+        labels.at = 10:30:00
+    "#;
+
+    let release: Release = from_str(config_str).unwrap();
+    assert_eq!(release.version, Version::Text("2024-02-29".into()));
+    assert_eq!(release.labels["date"], "2024-02-29");
+    assert_eq!(release.labels["at"], "10:30:00");
+
+    let value: Value = from_str(config_str).unwrap();
+    let Value::Dict(root) = &value else {
+        panic!("expected dict, found {}", value);
+    };
+    assert_eq!(
+        root["version"],
+        Value::Datetime("2024-02-29".parse().unwrap(), "2024-02-29".into())
+    );
+}
+
+#[test]
+fn test_duration_and_size_deserialize() {
+    use std::time::Duration;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Http {
+        timeout: Duration,
+        retry_delay: Option<Duration>,
+        max_body: u64,
+        buffer: usize,
+        label: String,
+        limit: String,
+    }
+
+    let config_str = r#"
+        timeout = 1h30m
+        retry_delay = 250ms
+        max_body = 512KiB
+        buffer = 10MB
+        label = 30s
+        limit = 1000MB
+    "#;
+
+    let http: Http = from_str(config_str).unwrap();
+    assert_eq!(
+        http,
+        Http {
+            timeout: Duration::from_secs(5400),
+            retry_delay: Some(Duration::from_millis(250)),
+            max_body: 512 * 1024,
+            buffer: 10_000_000,
+            label: "30s".into(),
+            limit: "1000MB".into(),
+        }
+    );
+
+    let value: Value = from_str("timeout = 30s\nmax_body = 1GiB").unwrap();
+    assert_eq!(value.to_string(), "{timeout: 30s, max_body: 1073741824B}");
+}
+
+#[test]
+fn test_duration_and_size_deserialize_any() {
+    use std::collections::HashMap;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(untagged)]
+    enum Limit {
+        Bytes(u64),
+        Text(String),
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Http {
+        limit: Limit,
+        timeouts: HashMap<String, String>,
+        sizes: HashMap<String, u64>,
+    }
+
+    let config_str = r#"
+        limit = 10MB
+        timeouts.read = 1h30m
+        timeouts.write = 30s
+        sizes.body = 512KiB
+    "#;
+
+    let http: Http = from_str(config_str).unwrap();
+    assert_eq!(http.limit, Limit::Bytes(10_000_000));
+    assert_eq!(http.timeouts["read"], "1h30m");
+    assert_eq!(http.timeouts["write"], "30s");
+    assert_eq!(http.sizes["body"], 512 * 1024);
+}